@@ -0,0 +1,44 @@
+//! Compares day 5's diagnostic program on the interpreter with the Rust it
+//! transpiles to. Run with `cargo bench --bench transpile`.
+
+use advent_of_code_2019::day5;
+use advent_of_code_2019::intcode::transpile;
+use advent_of_code_2019::intcode::Machine;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+const RUNS: u32 = 10_000;
+
+fn time(name: &str, mut f: impl FnMut() -> Vec<i32>) -> Duration {
+    let start = Instant::now();
+    let mut last = vec![];
+    for _ in 0..RUNS {
+        last = f();
+    }
+    let each = start.elapsed() / RUNS;
+    println!("{:<12} {:>10?} per run, output {:?}", name, each, last);
+    each
+}
+
+fn main() {
+    let program = day5::converter(include_str!("../input/2019/day5.txt"));
+    for &input in &[1, 5] {
+        println!("input {}", input);
+        let interpreted = time("interpreter", || {
+            let mut machine = Machine::new(&program);
+            machine.push_input(input);
+            machine.run().unwrap();
+            machine.take_output()
+        });
+        let transpiled = time("transpiled", || {
+            let mut memory = program.clone();
+            let mut output = vec![];
+            transpile::day5::run(&mut memory, &mut VecDeque::from(vec![input]), &mut output);
+            output
+        });
+        println!(
+            "{:.1}x faster",
+            interpreted.as_secs_f64() / transpiled.as_secs_f64()
+        );
+    }
+}
@@ -0,0 +1,46 @@
+//! An interactive shell for Intcode programs. Given a file, runs the
+//! commands in it instead, stopping at the first one that fails.
+
+use advent_of_code_2019::intcode::shell::Shell;
+use std::io::{self, BufRead, Write};
+use std::{env, fs, process};
+
+fn main() {
+    let mut shell = Shell::new();
+    if let Some(file) = env::args().nth(1) {
+        let script = fs::read_to_string(&file).unwrap_or_else(|error| {
+            eprintln!("Couldn't read {}: {}", file, error);
+            process::exit(2)
+        });
+        for (number, line) in script.lines().enumerate() {
+            match shell.execute(line) {
+                Ok(text) if text.is_empty() => {}
+                Ok(text) => println!("{}", text),
+                Err(error) => {
+                    eprintln!("{}:{}: {}", file, number + 1, error);
+                    process::exit(1)
+                }
+            }
+            if shell.finished() {
+                break;
+            }
+        }
+        return;
+    }
+
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines();
+    while !shell.finished() {
+        print!("intcode> ");
+        io::stdout().flush().unwrap();
+        let line = match lines.next() {
+            Some(Ok(line)) => line,
+            _ => break,
+        };
+        match shell.execute(&line) {
+            Ok(text) if text.is_empty() => {}
+            Ok(text) => println!("{}", text),
+            Err(error) => println!("error: {}", error),
+        }
+    }
+}
@@ -0,0 +1,139 @@
+use std::fmt;
+
+/// A sequence rewritten as a main routine of calls into a few shorter functions.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Compression<T> {
+    pub main: Vec<usize>,
+    pub functions: Vec<Vec<T>>,
+}
+
+impl<T: Clone + fmt::Display> Compression<T> {
+    /// The main routine as comma separated calls, naming functions `A`, `B`, `C`...
+    pub fn main_routine(&self) -> String {
+        self.main
+            .iter()
+            .map(|&f| ((b'A' + f as u8) as char).to_string())
+            .collect::<Vec<String>>()
+            .join(",")
+    }
+
+    pub fn function_routine(&self, f: usize) -> String {
+        routine(&self.functions[f])
+    }
+
+    pub fn expand(&self) -> Vec<T> {
+        self.main
+            .iter()
+            .flat_map(|&f| self.functions[f].iter().cloned())
+            .collect()
+    }
+}
+
+fn routine<T: fmt::Display>(items: &[T]) -> String {
+    items
+        .iter()
+        .map(|item| item.to_string())
+        .collect::<Vec<String>>()
+        .join(",")
+}
+
+/// Finds a way to write `sequence` as calls to at most `max_functions`
+/// functions, with the main routine and every function no longer than
+/// `max_length` characters when written out with commas.
+pub fn compress<T>(
+    sequence: &[T],
+    max_functions: usize,
+    max_length: usize,
+) -> Option<Compression<T>>
+where
+    T: Clone + PartialEq + fmt::Display,
+{
+    let mut compression = Compression {
+        main: vec![],
+        functions: vec![],
+    };
+    if search(sequence, max_functions, max_length, &mut compression) {
+        Some(compression)
+    } else {
+        None
+    }
+}
+
+fn search<T>(
+    rest: &[T],
+    max_functions: usize,
+    max_length: usize,
+    compression: &mut Compression<T>,
+) -> bool
+where
+    T: Clone + PartialEq + fmt::Display,
+{
+    if rest.is_empty() {
+        return true;
+    }
+    // Each call takes one letter plus a comma before all but the first.
+    if compression.main.len() * 2 + 1 > max_length {
+        return false;
+    }
+
+    for f in 0..compression.functions.len() {
+        let function = &compression.functions[f];
+        if rest.starts_with(function) {
+            let len = function.len();
+            compression.main.push(f);
+            if search(&rest[len..], max_functions, max_length, compression) {
+                return true;
+            }
+            compression.main.pop();
+        }
+    }
+
+    if compression.functions.len() < max_functions {
+        let longest = (1..=rest.len())
+            .take_while(|&len| routine(&rest[..len]).len() <= max_length)
+            .last()
+            .unwrap_or(0);
+        for len in (1..=longest).rev() {
+            compression.functions.push(rest[..len].to_vec());
+            compression.main.push(compression.functions.len() - 1);
+            if search(&rest[len..], max_functions, max_length, compression) {
+                return true;
+            }
+            compression.main.pop();
+            compression.functions.pop();
+        }
+    }
+
+    false
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const PATH: &str = "R,8,R,8,R,4,R,4,R,8,L,6,L,2,R,4,R,4,R,8,R,8,R,8,L,6,L,2";
+
+    #[test]
+    fn compresses_the_scaffold_example() {
+        let path: Vec<&str> = PATH.split(',').collect();
+        let compression = compress(&path, 3, 20).unwrap();
+        assert_eq!(compression.expand(), path);
+        assert!(compression.main_routine().len() <= 20);
+        for f in 0..compression.functions.len() {
+            assert!(compression.function_routine(f).len() <= 20);
+        }
+    }
+
+    #[test]
+    fn counts_multi_character_items_towards_length() {
+        let sequence = vec![10, 10, 10, 10];
+        let compression = compress(&sequence, 1, 5).unwrap();
+        assert_eq!(compression.functions, vec![vec![10, 10]]);
+        assert_eq!(compression.main_routine(), "A,A")
+    }
+
+    #[test]
+    fn gives_up_when_functions_run_out() {
+        assert_eq!(compress(&['a', 'b', 'c', 'd'], 1, 3), None)
+    }
+}
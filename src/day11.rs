@@ -0,0 +1,177 @@
+use crate::grid::{Direction, Grid, Point};
+use crate::intcode::{IntCodeError, Machine, State};
+use failure::Fail;
+use std::collections::HashSet;
+
+#[derive(Debug, Fail)]
+pub enum HullError {
+    #[fail(
+        display = "Expected a colour and a turn for each panel, got: {:?}",
+        output
+    )]
+    UnpairedOutput { output: Vec<i32> },
+
+    #[fail(display = "{}", _0)]
+    IntCode(#[cause] IntCodeError),
+}
+
+impl From<IntCodeError> for HullError {
+    fn from(error: IntCodeError) -> HullError {
+        HullError::IntCode(error)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Colour {
+    Black = 0,
+    White = 1,
+}
+
+#[derive(Debug)]
+pub struct Robot {
+    position: Point,
+    heading: Direction,
+    hull: Grid<Colour>,
+    painted: HashSet<Point>,
+}
+
+impl Robot {
+    pub fn new(starting_panel: Colour) -> Robot {
+        let mut hull = Grid::new();
+        hull.set((0, 0), starting_panel);
+        Robot {
+            position: (0, 0),
+            heading: Direction::Up,
+            hull,
+            painted: HashSet::new(),
+        }
+    }
+
+    pub fn camera(&self) -> Colour {
+        self.hull.get(self.position).unwrap_or(Colour::Black)
+    }
+
+    /// Applies one pair of outputs from the brain: the colour to paint the
+    /// current panel, then 0 to turn left or 1 to turn right before moving.
+    pub fn act(&mut self, paint: i32, turn: i32) {
+        let colour = if paint == 1 {
+            Colour::White
+        } else {
+            Colour::Black
+        };
+        self.hull.set(self.position, colour);
+        self.painted.insert(self.position);
+
+        self.heading = if turn == 0 {
+            self.heading.turn_left()
+        } else {
+            self.heading.turn_right()
+        };
+        self.position = self.heading.step(self.position);
+    }
+
+    pub fn panels_painted(&self) -> usize {
+        self.painted.len()
+    }
+
+    pub fn render(&self) -> String {
+        self.hull.render(|panel| match panel {
+            Some(Colour::White) => '#',
+            _ => '.',
+        })
+    }
+
+    /// Renders the hull as a plain (P1) PBM image, where 1 is a black pixel.
+    pub fn render_pbm(&self) -> String {
+        let ((min_x, min_y), (max_x, max_y)) = self.hull.bounds().unwrap();
+        let pixels = self.hull.render(|panel| match panel {
+            Some(Colour::White) => '0',
+            _ => '1',
+        });
+        format!(
+            "P1\n{} {}\n{}\n",
+            max_x - min_x + 1,
+            max_y - min_y + 1,
+            pixels
+        )
+    }
+}
+
+pub fn paint_hull(program: &[i32], starting_panel: Colour) -> Result<Robot, HullError> {
+    let mut robot = Robot::new(starting_panel);
+    let mut brain = Machine::new(program);
+
+    loop {
+        brain.push_input(robot.camera() as i32);
+        let state = brain.run()?;
+        let output = brain.take_output();
+        let instructions = output.chunks_exact(2);
+        if !instructions.remainder().is_empty() {
+            return Err(HullError::UnpairedOutput { output });
+        }
+        for instruction in instructions {
+            robot.act(instruction[0], instruction[1]);
+        }
+        if state == State::Halted {
+            return Ok(robot);
+        }
+    }
+}
+
+pub fn count_painted_panels(program: &[i32]) -> usize {
+    paint_hull(program, Colour::Black).unwrap().panels_painted()
+}
+
+pub fn registration_identifier(program: &[i32]) -> String {
+    format!("\n{}", paint_hull(program, Colour::White).unwrap().render())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // A brain that ignores its camera and replays the outputs from the puzzle example.
+    fn example_brain() -> Vec<i32> {
+        let outputs = [(1, 0), (0, 0), (1, 0), (1, 0), (0, 1), (1, 0), (1, 0)];
+        let mut program: Vec<i32> = outputs
+            .iter()
+            .flat_map(|&(paint, turn)| vec![3, 100, 104, paint, 104, turn])
+            .collect();
+        program.push(99);
+        program
+    }
+
+    #[test]
+    fn example_paints_6_panels() {
+        assert_eq!(count_painted_panels(&example_brain()), 6)
+    }
+
+    #[test]
+    fn example_renders_final_hull() {
+        assert_eq!(
+            paint_hull(&example_brain(), Colour::Black)
+                .unwrap()
+                .render(),
+            "..#\n..#\n##."
+        )
+    }
+
+    #[test]
+    fn example_renders_as_pbm() {
+        assert_eq!(
+            paint_hull(&example_brain(), Colour::Black)
+                .unwrap()
+                .render_pbm(),
+            "P1\n3 3\n110\n110\n001\n"
+        )
+    }
+
+    #[test]
+    fn unpaired_output_is_an_error() {
+        // Paints white, then halts without saying which way to turn.
+        match paint_hull(&[3, 100, 104, 1, 99], Colour::Black) {
+            Err(HullError::UnpairedOutput { output }) => assert_eq!(output, vec![1]),
+            other => panic!("expected unpaired output, got {:?}", other),
+        }
+    }
+}
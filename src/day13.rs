@@ -0,0 +1,268 @@
+use crate::grid::{Grid, Point};
+use crate::intcode::{IntCodeError, Machine, State};
+use failure::Fail;
+use std::io::{BufRead, Write};
+
+#[derive(Debug, Fail)]
+pub enum ArcadeError {
+    #[fail(display = "Not a recognised tile id: {}", id)]
+    UnknownTile { id: i32 },
+
+    #[fail(display = "Output is not a whole number of draw instructions: {}", len)]
+    IncompleteDrawInstruction { len: usize },
+
+    #[fail(display = "Joystick failed: {}", message)]
+    JoystickError { message: String },
+
+    #[fail(display = "{}", _0)]
+    IntCode(#[cause] IntCodeError),
+}
+
+impl From<IntCodeError> for ArcadeError {
+    fn from(error: IntCodeError) -> ArcadeError {
+        ArcadeError::IntCode(error)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Tile {
+    Empty,
+    Wall,
+    Block,
+    Paddle,
+    Ball,
+}
+
+impl Tile {
+    fn from_i32(id: i32) -> Result<Tile, ArcadeError> {
+        match id {
+            0 => Ok(Tile::Empty),
+            1 => Ok(Tile::Wall),
+            2 => Ok(Tile::Block),
+            3 => Ok(Tile::Paddle),
+            4 => Ok(Tile::Ball),
+            _ => Err(ArcadeError::UnknownTile { id }),
+        }
+    }
+
+    fn to_char(self) -> char {
+        match self {
+            Tile::Empty => ' ',
+            Tile::Wall => '#',
+            Tile::Block => '=',
+            Tile::Paddle => '-',
+            Tile::Ball => 'o',
+        }
+    }
+}
+
+/// What the cabinet has drawn so far. `(-1, 0)` is not a tile, it's the score.
+#[derive(Debug)]
+pub struct Screen {
+    tiles: Grid<Tile>,
+    score: i32,
+}
+
+impl Default for Screen {
+    fn default() -> Screen {
+        Screen::new()
+    }
+}
+
+impl Screen {
+    pub fn new() -> Screen {
+        Screen {
+            tiles: Grid::new(),
+            score: 0,
+        }
+    }
+
+    /// Applies output from the cabinet, which comes in `(x, y, tile)` triples.
+    pub fn draw(&mut self, output: &[i32]) -> Result<(), ArcadeError> {
+        let instructions = output.chunks_exact(3);
+        if !instructions.remainder().is_empty() {
+            return Err(ArcadeError::IncompleteDrawInstruction { len: output.len() });
+        }
+
+        for instruction in instructions {
+            match (instruction[0], instruction[1]) {
+                (-1, 0) => self.score = instruction[2],
+                position => self.tiles.set(position, Tile::from_i32(instruction[2])?),
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn score(&self) -> i32 {
+        self.score
+    }
+
+    pub fn count(&self, tile: Tile) -> usize {
+        self.tiles.iter().filter(|(_, &t)| t == tile).count()
+    }
+
+    pub fn find(&self, tile: Tile) -> Option<Point> {
+        self.tiles
+            .iter()
+            .find(|(_, &t)| t == tile)
+            .map(|(&position, _)| position)
+    }
+
+    pub fn render(&self) -> String {
+        format!(
+            "Score: {}\n{}",
+            self.score,
+            self.tiles
+                .render_screen(|tile| tile.unwrap_or(Tile::Empty).to_char())
+        )
+    }
+}
+
+/// Decides how to tilt the joystick each time the cabinet asks: -1 for left,
+/// 0 for neutral and 1 for right.
+pub trait Joystick {
+    fn tilt(&mut self, screen: &Screen) -> Result<i32, ArcadeError>;
+}
+
+/// Keeps the paddle underneath the ball.
+pub struct Autopilot;
+
+impl Joystick for Autopilot {
+    fn tilt(&mut self, screen: &Screen) -> Result<i32, ArcadeError> {
+        match (screen.find(Tile::Ball), screen.find(Tile::Paddle)) {
+            (Some((ball, _)), Some((paddle, _))) => Ok((ball - paddle).signum()),
+            _ => Ok(0),
+        }
+    }
+}
+
+/// Shows the screen and reads a move per frame: `a` for left, `d` for right,
+/// anything else to stay put.
+pub struct Terminal<R: BufRead, W: Write> {
+    input: R,
+    output: W,
+}
+
+impl<R: BufRead, W: Write> Terminal<R, W> {
+    pub fn new(input: R, output: W) -> Terminal<R, W> {
+        Terminal { input, output }
+    }
+}
+
+impl<R: BufRead, W: Write> Joystick for Terminal<R, W> {
+    fn tilt(&mut self, screen: &Screen) -> Result<i32, ArcadeError> {
+        let io_error = |e: std::io::Error| ArcadeError::JoystickError {
+            message: e.to_string(),
+        };
+
+        writeln!(self.output, "{}", screen.render()).map_err(io_error)?;
+        let mut line = String::new();
+        self.input.read_line(&mut line).map_err(io_error)?;
+        match line.trim() {
+            "a" => Ok(-1),
+            "d" => Ok(1),
+            _ => Ok(0),
+        }
+    }
+}
+
+pub struct Arcade {
+    cabinet: Machine,
+    screen: Screen,
+}
+
+impl Arcade {
+    pub fn new(program: &[i32]) -> Arcade {
+        Arcade {
+            cabinet: Machine::new(program),
+            screen: Screen::new(),
+        }
+    }
+
+    /// Boots the cabinet with two quarters in address 0, so the game can be played.
+    pub fn free_play(program: &[i32]) -> Arcade {
+        let mut arcade = Arcade::new(program);
+        arcade.cabinet.poke(0, 2);
+        arcade
+    }
+
+    pub fn screen(&self) -> &Screen {
+        &self.screen
+    }
+
+    /// Runs the game to completion and returns the final score.
+    pub fn play<J: Joystick>(&mut self, joystick: &mut J) -> Result<i32, ArcadeError> {
+        loop {
+            let state = self.cabinet.run()?;
+            self.screen.draw(&self.cabinet.take_output())?;
+            if state == State::Halted {
+                return Ok(self.screen.score());
+            }
+            self.cabinet.push_input(joystick.tilt(&self.screen)?);
+        }
+    }
+}
+
+pub fn count_blocks(program: &[i32]) -> usize {
+    let mut arcade = Arcade::new(program);
+    arcade.play(&mut Autopilot).unwrap();
+    arcade.screen().count(Tile::Block)
+}
+
+pub fn beat_the_game(program: &[i32]) -> i32 {
+    Arcade::free_play(program).play(&mut Autopilot).unwrap()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::io::Cursor;
+
+    // Draws a paddle at (1, 2) and a ball at (3, 1), then reports the joystick
+    // position back as the score.
+    const CABINET: [i32; 28] = [
+        104, 1, 104, 2, 104, 3, 104, 3, 104, 1, 104, 4, 3, 100, 104, -1, 104, 0, 4, 100, 104, 0,
+        104, 0, 104, 2, 99, 0,
+    ];
+
+    #[test]
+    fn draw_decodes_tiles_and_score() {
+        let mut screen = Screen::new();
+        screen.draw(&[1, 2, 3, 6, 5, 4, -1, 0, 12345]).unwrap();
+        assert_eq!(screen.find(Tile::Paddle), Some((1, 2)));
+        assert_eq!(screen.find(Tile::Ball), Some((6, 5)));
+        assert_eq!(screen.score(), 12345)
+    }
+
+    #[test]
+    fn draw_rejects_unknown_tiles() {
+        match Screen::new().draw(&[0, 0, 7]) {
+            Err(ArcadeError::UnknownTile { id: 7 }) => (),
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn autopilot_follows_the_ball() {
+        let mut arcade = Arcade::new(&CABINET);
+        assert_eq!(arcade.play(&mut Autopilot).unwrap(), 1);
+        assert_eq!(arcade.screen().count(Tile::Block), 1)
+    }
+
+    #[test]
+    fn terminal_shows_the_screen_and_reads_moves() {
+        let mut output = vec![];
+        let score = Arcade::new(&CABINET)
+            .play(&mut Terminal::new(Cursor::new("a\n"), &mut output))
+            .unwrap();
+        assert_eq!(score, -1);
+        assert_eq!(String::from_utf8(output).unwrap(), "Score: 0\n  o\n-  \n")
+    }
+
+    #[test]
+    fn free_play_inserts_quarters() {
+        assert_eq!(Arcade::free_play(&CABINET).cabinet.memory()[0], 2);
+        assert_eq!(Arcade::free_play(&[]).cabinet.memory(), &[2])
+    }
+}
@@ -0,0 +1,264 @@
+use crate::grid::{Direction, Grid, Point};
+use crate::intcode::{IntCodeError, Machine};
+use failure::Fail;
+use std::collections::{HashMap, VecDeque};
+
+const DIRECTIONS: [Direction; 4] = [
+    Direction::Up,
+    Direction::Down,
+    Direction::Left,
+    Direction::Right,
+];
+
+#[derive(Debug, Fail)]
+pub enum DroidError {
+    #[fail(display = "Not a recognised status code: {}", status)]
+    UnknownStatus { status: i32 },
+
+    #[fail(display = "Expected a single status code, got: {:?}", output)]
+    UnexpectedResponse { output: Vec<i32> },
+
+    #[fail(display = "{}", _0)]
+    IntCode(#[cause] IntCodeError),
+}
+
+impl From<IntCodeError> for DroidError {
+    fn from(error: IntCodeError) -> DroidError {
+        DroidError::IntCode(error)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Cell {
+    Wall,
+    Open,
+    OxygenSystem,
+}
+
+/// Anything that can be asked to move one step and report what it found there.
+/// Cloning a droid must snapshot its state, so exploration can fork it at each branch.
+pub trait Droid: Clone {
+    fn travel(&mut self, direction: Direction) -> Result<Cell, DroidError>;
+}
+
+/// A droid controlled remotely by an Intcode program.
+#[derive(Clone, Debug)]
+pub struct RepairDroid {
+    controller: Machine,
+}
+
+impl RepairDroid {
+    pub fn new(program: &[i32]) -> RepairDroid {
+        RepairDroid {
+            controller: Machine::new(program),
+        }
+    }
+}
+
+impl Droid for RepairDroid {
+    fn travel(&mut self, direction: Direction) -> Result<Cell, DroidError> {
+        let command = match direction {
+            Direction::Up => 1,
+            Direction::Down => 2,
+            Direction::Left => 3,
+            Direction::Right => 4,
+        };
+        self.controller.push_input(command);
+        self.controller.run()?;
+
+        match self.controller.take_output().as_slice() {
+            [0] => Ok(Cell::Wall),
+            [1] => Ok(Cell::Open),
+            [2] => Ok(Cell::OxygenSystem),
+            [status] => Err(DroidError::UnknownStatus { status: *status }),
+            output => Err(DroidError::UnexpectedResponse {
+                output: output.to_vec(),
+            }),
+        }
+    }
+}
+
+/// Everything the droid has seen, relative to where it started at `(0, 0)`.
+#[derive(Debug)]
+pub struct Map {
+    cells: Grid<Cell>,
+}
+
+impl Map {
+    pub fn oxygen_system(&self) -> Option<Point> {
+        self.cells
+            .iter()
+            .find(|(_, &cell)| cell == Cell::OxygenSystem)
+            .map(|(&position, _)| position)
+    }
+
+    /// Steps needed to reach every open cell from `origin`.
+    fn distances(&self, origin: Point) -> HashMap<Point, usize> {
+        let mut distances = HashMap::new();
+        let mut queue = VecDeque::new();
+        distances.insert(origin, 0);
+        queue.push_back(origin);
+
+        while let Some(position) = queue.pop_front() {
+            let distance = distances[&position];
+            for direction in DIRECTIONS.iter() {
+                let next = direction.step(position);
+                let open = matches!(
+                    self.cells.get(next),
+                    Some(Cell::Open) | Some(Cell::OxygenSystem)
+                );
+                if open && !distances.contains_key(&next) {
+                    distances.insert(next, distance + 1);
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        distances
+    }
+
+    pub fn route_to_oxygen_system(&self) -> Option<usize> {
+        let oxygen_system = self.oxygen_system()?;
+        self.distances((0, 0)).get(&oxygen_system).copied()
+    }
+
+    /// Minutes until oxygen spreading from the system fills every open cell.
+    pub fn oxygen_fill_time(&self) -> Option<usize> {
+        let oxygen_system = self.oxygen_system()?;
+        self.distances(oxygen_system).values().max().copied()
+    }
+
+    pub fn render(&self) -> String {
+        self.cells.render(|cell| match cell {
+            Some(Cell::Wall) => '#',
+            Some(Cell::Open) => '.',
+            Some(Cell::OxygenSystem) => 'O',
+            None => ' ',
+        })
+    }
+}
+
+/// Maps the whole area reachable by the droid with a breadth-first search,
+/// forking a copy of the droid for every step instead of backtracking.
+pub fn explore<D: Droid>(droid: D) -> Result<Map, DroidError> {
+    let mut cells = Grid::new();
+    let mut queue = VecDeque::new();
+    cells.set((0, 0), Cell::Open);
+    queue.push_back(((0, 0), droid));
+
+    while let Some((position, droid)) = queue.pop_front() {
+        for &direction in DIRECTIONS.iter() {
+            let next = direction.step(position);
+            if cells.get(next).is_some() {
+                continue;
+            }
+
+            let mut fork = droid.clone();
+            let cell = fork.travel(direction)?;
+            cells.set(next, cell);
+            if cell != Cell::Wall {
+                queue.push_back((next, fork));
+            }
+        }
+    }
+
+    Ok(Map { cells })
+}
+
+pub fn fewest_movement_commands(program: &[i32]) -> usize {
+    explore(RepairDroid::new(program))
+        .unwrap()
+        .route_to_oxygen_system()
+        .unwrap()
+}
+
+pub fn minutes_to_fill_with_oxygen(program: &[i32]) -> usize {
+    explore(RepairDroid::new(program))
+        .unwrap()
+        .oxygen_fill_time()
+        .unwrap()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::collections::HashSet;
+
+    const MAZE: &str = " ##   \n#D.## \n#.#..#\n#.O.# \n ###  ";
+
+    // A droid walking around a drawn maze, where `D` is its starting point.
+    #[derive(Clone)]
+    struct MazeDroid {
+        open: HashSet<Point>,
+        oxygen_system: Point,
+        position: Point,
+    }
+
+    impl MazeDroid {
+        fn new(maze: &str) -> MazeDroid {
+            let mut cells = vec![];
+            for (row, line) in maze.lines().enumerate() {
+                for (column, c) in line.chars().enumerate() {
+                    cells.push(((column as i32, -(row as i32)), c));
+                }
+            }
+            let &((start_x, start_y), _) = cells.iter().find(|(_, c)| *c == 'D').unwrap();
+            let relative = |(x, y): Point| (x - start_x, y - start_y);
+
+            MazeDroid {
+                open: cells
+                    .iter()
+                    .filter(|(_, c)| "D.O".contains(*c))
+                    .map(|&(position, _)| relative(position))
+                    .collect(),
+                oxygen_system: cells
+                    .iter()
+                    .find(|(_, c)| *c == 'O')
+                    .map(|&(position, _)| relative(position))
+                    .unwrap(),
+                position: (0, 0),
+            }
+        }
+    }
+
+    impl Droid for MazeDroid {
+        fn travel(&mut self, direction: Direction) -> Result<Cell, DroidError> {
+            let next = direction.step(self.position);
+            if !self.open.contains(&next) {
+                return Ok(Cell::Wall);
+            }
+            self.position = next;
+            if next == self.oxygen_system {
+                Ok(Cell::OxygenSystem)
+            } else {
+                Ok(Cell::Open)
+            }
+        }
+    }
+
+    #[test]
+    fn finds_shortest_route_to_oxygen_system() {
+        let map = explore(MazeDroid::new(MAZE)).unwrap();
+        assert_eq!(map.route_to_oxygen_system(), Some(3))
+    }
+
+    #[test]
+    fn oxygen_fills_example_in_4_minutes() {
+        let map = explore(MazeDroid::new(MAZE)).unwrap();
+        assert_eq!(map.oxygen_fill_time(), Some(4))
+    }
+
+    #[test]
+    fn renders_everything_explored() {
+        let map = explore(MazeDroid::new(MAZE)).unwrap();
+        assert_eq!(map.render(), MAZE.replace('D', "."))
+    }
+
+    #[test]
+    fn repair_droid_reports_walls_from_intcode() {
+        // Reads a movement command, reports a wall and waits for the next one.
+        let map = explore(RepairDroid::new(&[3, 100, 104, 0, 1105, 1, 0])).unwrap();
+        assert_eq!(map.render(), " # \n#.#\n # ");
+        assert_eq!(map.route_to_oxygen_system(), None)
+    }
+}
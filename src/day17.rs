@@ -0,0 +1,204 @@
+use crate::compress::compress;
+use crate::grid::{Direction, Point};
+use crate::intcode::ascii::AsciiMachine;
+use std::collections::HashSet;
+use std::fmt;
+
+const DIRECTIONS: [Direction; 4] = [
+    Direction::Up,
+    Direction::Down,
+    Direction::Left,
+    Direction::Right,
+];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Move {
+    Left,
+    Right,
+    Forward(usize),
+}
+
+impl fmt::Display for Move {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Move::Left => write!(f, "L"),
+            Move::Right => write!(f, "R"),
+            Move::Forward(n) => write!(f, "{}", n),
+        }
+    }
+}
+
+/// The view from the ASCII camera, in screen coordinates with `(0, 0)` at the top left.
+#[derive(Debug)]
+pub struct Camera {
+    scaffold: HashSet<Point>,
+    robot: Option<(Point, Direction)>,
+}
+
+impl Camera {
+    pub fn parse(view: &str) -> Camera {
+        let mut scaffold = HashSet::new();
+        let mut robot = None;
+        for (y, line) in view.lines().enumerate() {
+            for (x, c) in line.chars().enumerate() {
+                let position = (x as i32, y as i32);
+                let heading = match c {
+                    '#' => None,
+                    '^' => Some(Direction::Up),
+                    'v' => Some(Direction::Down),
+                    '<' => Some(Direction::Left),
+                    '>' => Some(Direction::Right),
+                    _ => continue,
+                };
+                scaffold.insert(position);
+                if let Some(heading) = heading {
+                    robot = Some((position, heading));
+                }
+            }
+        }
+
+        Camera { scaffold, robot }
+    }
+
+    pub fn intersections(&self) -> Vec<Point> {
+        let mut intersections: Vec<Point> = self
+            .scaffold
+            .iter()
+            .filter(|&&position| {
+                DIRECTIONS
+                    .iter()
+                    .all(|d| self.scaffold.contains(&d.step_screen(position)))
+            })
+            .copied()
+            .collect();
+        intersections.sort();
+        intersections
+    }
+
+    pub fn alignment_parameters(&self) -> i32 {
+        self.intersections().iter().map(|(x, y)| x * y).sum()
+    }
+
+    /// Follows the scaffold from the robot to its far end, going straight
+    /// across every intersection.
+    pub fn path(&self) -> Vec<Move> {
+        let (mut position, mut heading) = match self.robot {
+            Some(robot) => robot,
+            None => return vec![],
+        };
+        let on_scaffold =
+            |heading: Direction, position| self.scaffold.contains(&heading.step_screen(position));
+
+        let mut path = vec![];
+        loop {
+            // Apart from possibly at the start, we've always just gone as far as we can.
+            if !on_scaffold(heading, position) {
+                if on_scaffold(heading.turn_left(), position) {
+                    heading = heading.turn_left();
+                    path.push(Move::Left);
+                } else if on_scaffold(heading.turn_right(), position) {
+                    heading = heading.turn_right();
+                    path.push(Move::Right);
+                } else {
+                    return path;
+                }
+            }
+
+            let mut distance = 0;
+            while on_scaffold(heading, position) {
+                position = heading.step_screen(position);
+                distance += 1;
+            }
+            path.push(Move::Forward(distance));
+        }
+    }
+}
+
+fn look(program: &[i32]) -> Camera {
+    let (_, output) = AsciiMachine::new(program).run().unwrap();
+    Camera::parse(&output.text)
+}
+
+pub fn sum_of_alignment_parameters(program: &[i32]) -> i32 {
+    look(program).alignment_parameters()
+}
+
+/// Wakes the vacuum robot up by writing 2 over address 0.
+fn wake_up(program: &[i32]) -> AsciiMachine {
+    let mut robot = AsciiMachine::new(program);
+    robot.poke(0, 2);
+    robot
+}
+
+pub fn collect_dust(program: &[i32]) -> i32 {
+    let path = look(program).path();
+    let routines = compress(&path, 3, 20).unwrap();
+
+    let mut robot = wake_up(program);
+    robot.send_line(&routines.main_routine());
+    for f in 0..3 {
+        match routines.functions.get(f) {
+            Some(_) => robot.send_line(&routines.function_routine(f)),
+            None => robot.send_line(""),
+        }
+    }
+    robot.send_line("n");
+
+    let (_, output) = robot.run().unwrap();
+    *output.values.last().unwrap()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn alignment_parameters_example() {
+        let camera = Camera::parse(
+            "..#..........\n\
+             ..#..........\n\
+             #######...###\n\
+             #.#...#...#.#\n\
+             #############\n\
+             ..#...#...#..\n\
+             ..#####...^..",
+        );
+        assert_eq!(
+            camera.intersections(),
+            vec![(2, 2), (2, 4), (6, 4), (10, 4)]
+        );
+        assert_eq!(camera.alignment_parameters(), 76)
+    }
+
+    #[test]
+    fn path_example() {
+        let camera = Camera::parse(
+            "#######...#####\n\
+             #.....#...#...#\n\
+             #.....#...#...#\n\
+             ......#...#...#\n\
+             ......#...###.#\n\
+             ......#.....#.#\n\
+             ^########...#.#\n\
+             ......#.#...#.#\n\
+             ......#########\n\
+             ........#...#..\n\
+             ....#########..\n\
+             ....#...#......\n\
+             ....#...#......\n\
+             ....#...#......\n\
+             ....#####......",
+        );
+        let path: Vec<String> = camera.path().iter().map(|m| m.to_string()).collect();
+        assert_eq!(
+            path.join(","),
+            "R,8,R,8,R,4,R,4,R,8,L,6,L,2,R,4,R,4,R,8,R,8,R,8,L,6,L,2"
+        )
+    }
+
+    #[test]
+    fn waking_an_empty_program_does_not_panic() {
+        // Memory becomes just the 2, which runs off the end.
+        assert!(wake_up(&[]).run().is_err())
+    }
+}
@@ -0,0 +1,281 @@
+use crate::grid::Point;
+use crate::intcode::{IntCodeError, Machine};
+use failure::Fail;
+use std::collections::HashMap;
+
+#[derive(Debug, Fail)]
+pub enum TractorBeamError {
+    #[fail(display = "Expected a 0 or 1 from the drone system, got: {:?}", output)]
+    UnexpectedOutput { output: Vec<i32> },
+
+    #[fail(
+        display = "No {}x{} square fits in the first {} rows of the beam",
+        size, size, rows
+    )]
+    NoSquare { size: i32, rows: i32 },
+
+    #[fail(display = "{}", _0)]
+    IntCode(#[cause] IntCodeError),
+}
+
+impl From<IntCodeError> for TractorBeamError {
+    fn from(error: IntCodeError) -> TractorBeamError {
+        TractorBeamError::IntCode(error)
+    }
+}
+
+/// Anything that can tell whether a position is inside the tractor beam.
+pub trait Beam {
+    fn pulled(&mut self, position: Point) -> Result<bool, TractorBeamError>;
+}
+
+/// Deploys a drone by running the drone system program from scratch for
+/// every position.
+pub struct DroneSystem {
+    image: Vec<i32>,
+    machine: Machine,
+}
+
+impl DroneSystem {
+    pub fn new(program: &[i32]) -> DroneSystem {
+        DroneSystem {
+            image: program.to_vec(),
+            machine: Machine::new(program),
+        }
+    }
+}
+
+impl Beam for DroneSystem {
+    fn pulled(&mut self, (x, y): Point) -> Result<bool, TractorBeamError> {
+        self.machine.reset(&self.image);
+        self.machine.push_input(x);
+        self.machine.push_input(y);
+        self.machine.run()?;
+
+        match self.machine.take_output().as_slice() {
+            [0] => Ok(false),
+            [1] => Ok(true),
+            output => Err(TractorBeamError::UnexpectedOutput {
+                output: output.to_vec(),
+            }),
+        }
+    }
+}
+
+/// Remembers every answer from a beam and counts how often it's asked, so
+/// search strategies can be compared.
+pub struct Scanner<B: Beam> {
+    beam: B,
+    cache: HashMap<Point, bool>,
+    lookups: usize,
+}
+
+impl<B: Beam> Scanner<B> {
+    pub fn new(beam: B) -> Scanner<B> {
+        Scanner {
+            beam,
+            cache: HashMap::new(),
+            lookups: 0,
+        }
+    }
+
+    pub fn pulled(&mut self, position: Point) -> Result<bool, TractorBeamError> {
+        self.lookups += 1;
+        if let Some(&pulled) = self.cache.get(&position) {
+            return Ok(pulled);
+        }
+        let pulled = self.beam.pulled(position)?;
+        self.cache.insert(position, pulled);
+        Ok(pulled)
+    }
+
+    /// How many positions have been asked about, including repeats.
+    pub fn lookups(&self) -> usize {
+        self.lookups
+    }
+
+    /// How many times the underlying beam had to be asked.
+    pub fn invocations(&self) -> usize {
+        self.cache.len()
+    }
+}
+
+pub fn points_affected<B: Beam>(
+    scanner: &mut Scanner<B>,
+    size: i32,
+) -> Result<usize, TractorBeamError> {
+    let mut affected = 0;
+    for y in 0..size {
+        for x in 0..size {
+            if scanner.pulled((x, y))? {
+                affected += 1;
+            }
+        }
+    }
+
+    Ok(affected)
+}
+
+/// Finds the top left corner of the nearest `size` × `size` square inside the
+/// beam by following its left edge down, one row at a time, and checking
+/// whether the opposite corner also fits. Gives up after `rows` rows, in case
+/// the beam is too narrow for the square ever to fit.
+///
+/// Until the beam turns up, each row is scanned for `rows` positions. After
+/// that the edges are taken to be straight lines from the emitter, so the
+/// left edge can't be much further out than the last one found, scaled up
+/// to the current row.
+pub fn nearest_square<B: Beam>(
+    scanner: &mut Scanner<B>,
+    size: i32,
+    rows: i32,
+) -> Result<Point, TractorBeamError> {
+    let mut last: Option<Point> = None;
+    for y in size - 1..rows {
+        // Rows close to the emitter can miss the beam entirely.
+        let (from, to) = match last {
+            Some((x, row)) => (
+                x,
+                ((x + 1) as i64 * y as i64 / row.max(1) as i64 + 1) as i32,
+            ),
+            None => (0, rows),
+        };
+        let mut edge = None;
+        for x in from..=to {
+            if scanner.pulled((x, y))? {
+                edge = Some(x);
+                break;
+            }
+        }
+
+        if let Some(x) = edge {
+            last = Some((x, y));
+            if scanner.pulled((x + size - 1, y - size + 1))? {
+                return Ok((x, y - size + 1));
+            }
+        }
+    }
+
+    Err(TractorBeamError::NoSquare { size, rows })
+}
+
+/// The same as `nearest_square`, but checks every position of every row, up
+/// to where the row leaves the beam, instead of following the edge.
+pub fn nearest_square_by_sweep<B: Beam>(
+    scanner: &mut Scanner<B>,
+    size: i32,
+    rows: i32,
+) -> Result<Point, TractorBeamError> {
+    for y in size - 1..rows {
+        let top = y - size + 1;
+        let mut inside = false;
+        for x in 0.. {
+            let pulled = scanner.pulled((x, y))?;
+            if (inside && !pulled) || (!inside && x >= rows) {
+                break;
+            }
+            inside |= pulled;
+            let corners = [(x, y), (x, top), (x + size - 1, y), (x + size - 1, top)];
+            let mut fits = true;
+            for &corner in corners.iter() {
+                fits = fits && scanner.pulled(corner)?;
+            }
+            if fits {
+                return Ok((x, top));
+            }
+        }
+    }
+
+    Err(TractorBeamError::NoSquare { size, rows })
+}
+
+pub fn count_affected_points(program: &[i32]) -> usize {
+    points_affected(&mut Scanner::new(DroneSystem::new(program)), 50).unwrap()
+}
+
+pub fn santas_ship(program: &[i32]) -> i32 {
+    let (x, y) = nearest_square(&mut Scanner::new(DroneSystem::new(program)), 100, 10_000).unwrap();
+    x * 10_000 + y
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // A beam whose edges run from the emitter at slopes of 1.2 and 1.5.
+    struct Wedge;
+
+    impl Beam for Wedge {
+        fn pulled(&mut self, (x, y): Point) -> Result<bool, TractorBeamError> {
+            Ok(10 * x >= 12 * y && 10 * x <= 15 * y)
+        }
+    }
+
+    // Steeper than the puzzle's beam, with slopes of 2.5 and 3.
+    struct Steep;
+
+    impl Beam for Steep {
+        fn pulled(&mut self, (x, y): Point) -> Result<bool, TractorBeamError> {
+            Ok(10 * x >= 25 * y && x <= 3 * y)
+        }
+    }
+
+    #[test]
+    fn scanner_caches_repeated_lookups() {
+        let mut scanner = Scanner::new(Wedge);
+        scanner.pulled((12, 10)).unwrap();
+        scanner.pulled((12, 10)).unwrap();
+        assert_eq!(scanner.lookups(), 2);
+        assert_eq!(scanner.invocations(), 1)
+    }
+
+    #[test]
+    fn edge_following_agrees_with_sweep() {
+        let mut following = Scanner::new(Wedge);
+        let mut sweeping = Scanner::new(Wedge);
+        assert_eq!(
+            nearest_square(&mut following, 10, 1_000).unwrap(),
+            nearest_square_by_sweep(&mut sweeping, 10, 1_000).unwrap()
+        );
+        assert!(following.invocations() * 10 < sweeping.invocations())
+    }
+
+    #[test]
+    fn finds_squares_in_steep_beams() {
+        let following = nearest_square(&mut Scanner::new(Steep), 10, 1_000).unwrap();
+        let sweeping = nearest_square_by_sweep(&mut Scanner::new(Steep), 10, 1_000).unwrap();
+        assert_eq!(following, sweeping);
+        let (x, y) = following;
+        for &corner in &[(x, y), (x + 9, y), (x, y + 9), (x + 9, y + 9)] {
+            assert!(Steep.pulled(corner).unwrap())
+        }
+    }
+
+    #[test]
+    fn gives_up_when_the_beam_is_too_narrow() {
+        // Only ever one position wide.
+        struct Line;
+
+        impl Beam for Line {
+            fn pulled(&mut self, (x, y): Point) -> Result<bool, TractorBeamError> {
+                Ok(x == y)
+            }
+        }
+
+        for search in [nearest_square::<Line>, nearest_square_by_sweep::<Line>].iter() {
+            match search(&mut Scanner::new(Line), 2, 100) {
+                Err(TractorBeamError::NoSquare { size: 2, rows: 100 }) => {}
+                other => panic!("expected no square, got {:?}", other),
+            }
+        }
+    }
+
+    #[test]
+    fn drone_system_is_rerun_for_each_position() {
+        // Pulled only where x == y.
+        let program = [3, 11, 3, 12, 8, 11, 12, 13, 4, 13, 99, 0, 0, 0];
+        let mut scanner = Scanner::new(DroneSystem::new(&program));
+        assert_eq!(points_affected(&mut scanner, 5).unwrap(), 5);
+        assert_eq!(scanner.invocations(), 25)
+    }
+}
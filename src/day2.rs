@@ -1,12 +1,12 @@
-use crate::intcode::Memory;
+use crate::intcode::program;
+use crate::intcode::search::{patch_sweep, Search};
+use crate::intcode::symbolic;
+use crate::intcode::{IntCodeError, Memory};
 use aoc_runner_derive::{aoc, aoc_generator};
 
 #[aoc_generator(day2)]
 pub fn converter(input: &str) -> Vec<i32> {
-    input
-        .split(",")
-        .map(|n| n.parse::<i32>().unwrap())
-        .collect()
+    program::parse(input).unwrap()
 }
 
 #[aoc(day2, part1)]
@@ -17,19 +17,63 @@ pub fn run_program(program: &[i32]) -> i32 {
     memory.run(0).unwrap()
 }
 
-#[aoc(day2, part2)]
-pub fn find_target_inputs(program: &[i32]) -> i32 {
-    for noun in 0..99 {
-        for verb in 0..99 {
-            let memory = &mut program.to_vec();
-            memory[1] = noun;
-            memory[2] = verb;
-            if memory.run(0).unwrap() == 19690720 {
-                return 100 * noun + verb;
-            }
+/// Runs the program with `noun` and `verb` patched in, returning what it
+/// leaves at address 0.
+fn run_with(program: &[i32], noun: i32, verb: i32) -> Result<i32, IntCodeError> {
+    let mut memory = program.to_vec();
+    memory[1] = noun;
+    memory[2] = verb;
+    memory.run(0)
+}
+
+fn search_target_inputs(program: &[i32], target: i32) -> Option<i32> {
+    let (trial, _) =
+        Search::new(program).first(patch_sweep(&[1, 2], 0..=99), move |o| o.memory[0] == target)?;
+    Some(100 * trial.patches[0].1 + trial.patches[1].1)
+}
+
+/// The noun and verb, as `100 * noun + verb`, which leave `target` at address
+/// 0, or `None` if there aren't any. Symbolic execution doesn't overflow
+/// where the machine would, so its answer is checked by running it, and
+/// searched for if that fails or the program couldn't be analysed.
+pub fn target_inputs(program: &[i32], target: i32) -> Option<i32> {
+    match symbolic::solve(program, &[1, 2], target, 0..=99) {
+        Ok(Some(inputs)) if run_with(program, inputs[0], inputs[1]).ok() == Some(target) => {
+            Some(100 * inputs[0] + inputs[1])
         }
+        // Overflow only ever stops the machine, so it can't find solutions
+        // symbolic execution ruled out.
+        Ok(None) => None,
+        Ok(Some(_)) | Err(_) => search_target_inputs(program, target),
     }
+}
 
-    unreachable!()
+#[aoc(day2, part2)]
+pub fn find_target_inputs(program: &[i32]) -> i32 {
+    target_inputs(program, 19690720).expect("no noun and verb leave 19690720 at address 0")
 }
 
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn answers_are_checked_by_running_them() {
+        let program = converter(include_str!("../input/2019/day2.txt").trim());
+        let answer = target_inputs(&program, 19690720).unwrap();
+        assert_eq!(
+            run_with(&program, answer / 100, answer % 100).ok(),
+            Some(19690720)
+        )
+    }
+
+    #[test]
+    fn no_inputs_is_not_an_answer() {
+        // Can only ever leave an even number at address 0.
+        let double = [1101, 0, 0, 0, 1, 0, 0, 0, 99];
+        assert_eq!(target_inputs(&double, 7), None);
+        // Can't be analysed, so has to be searched.
+        let indirect = [1, 0, 0, 0, 99];
+        assert_eq!(target_inputs(&indirect, 1_000_000), None)
+    }
+}
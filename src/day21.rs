@@ -0,0 +1,98 @@
+use crate::intcode::ascii::AsciiMachine;
+use crate::intcode::IntCodeError;
+use crate::springscript::{search, Hull, Mode, Script, SpringscriptError};
+use failure::Fail;
+
+#[derive(Debug, Fail)]
+pub enum SpringdroidError {
+    #[fail(display = "Droid neither reported damage nor fell: {}", text)]
+    UnexpectedOutput { text: String },
+
+    #[fail(display = "Search produced a script that still fell on: {:?}", hull)]
+    RepeatedFall { hull: Hull },
+
+    #[fail(display = "{}", _0)]
+    Springscript(#[cause] SpringscriptError),
+
+    #[fail(display = "{}", _0)]
+    IntCode(#[cause] IntCodeError),
+}
+
+impl From<SpringscriptError> for SpringdroidError {
+    fn from(error: SpringscriptError) -> SpringdroidError {
+        SpringdroidError::Springscript(error)
+    }
+}
+
+impl From<IntCodeError> for SpringdroidError {
+    fn from(error: IntCodeError) -> SpringdroidError {
+        SpringdroidError::IntCode(error)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Outcome {
+    HullDamage(i32),
+    Fell(Hull),
+}
+
+/// Picks the hull out of the droid's last moments, which is the first row
+/// drawn with ground in it.
+pub fn fall_pattern(text: &str) -> Option<Hull> {
+    text.lines()
+        .find(|line| line.contains('#') && line.chars().all(|c| c == '#' || c == '.'))
+        .map(Hull::parse)
+}
+
+pub fn deploy(program: &[i32], script: &Script) -> Result<Outcome, SpringdroidError> {
+    let mut droid = AsciiMachine::new(program);
+    droid.send(&script.to_string());
+    let (_, output) = droid.run()?;
+
+    if let Some(&damage) = output.values.last() {
+        return Ok(Outcome::HullDamage(damage));
+    }
+    match fall_pattern(&output.text) {
+        Some(hull) => Ok(Outcome::Fell(hull)),
+        None => Err(SpringdroidError::UnexpectedOutput { text: output.text }),
+    }
+}
+
+/// Keeps searching for a script that survives every hull seen so far, adding
+/// each hull the droid falls into, until one gets all the way across.
+pub fn survey(program: &[i32], mode: Mode) -> Result<(Script, i32), SpringdroidError> {
+    let mut hulls: Vec<Hull> = vec![];
+    loop {
+        let script = search(&hulls, mode)?;
+        match deploy(program, &script)? {
+            Outcome::HullDamage(damage) => return Ok((script, damage)),
+            Outcome::Fell(hull) => {
+                if hulls.contains(&hull) {
+                    return Err(SpringdroidError::RepeatedFall { hull });
+                }
+                hulls.push(hull);
+            }
+        }
+    }
+}
+
+pub fn hull_damage_walking(program: &[i32]) -> i32 {
+    survey(program, Mode::Walk).unwrap().1
+}
+
+pub fn hull_damage_running(program: &[i32]) -> i32 {
+    survey(program, Mode::Run).unwrap().1
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn fall_pattern_finds_the_hull() {
+        let text = "Input instructions:\n\nWalking...\n\nDidn't make it across:\n\n\
+                    .................\n.................\n@................\n\
+                    #####.#..########\n\n";
+        assert_eq!(fall_pattern(text), Some(Hull::parse("#####.#..########")))
+    }
+}
@@ -0,0 +1,469 @@
+use crate::intcode::ascii::AsciiMachine;
+use crate::intcode::IntCodeError;
+use failure::Fail;
+use std::collections::{HashMap, VecDeque};
+
+/// Items which end the game, or leave the droid unable to move, if taken.
+pub const DANGEROUS_ITEMS: [&str; 5] = [
+    "escape pod",
+    "giant electromagnet",
+    "infinite loop",
+    "molten lava",
+    "photons",
+];
+
+const CHECKPOINT: &str = "Security Checkpoint";
+
+#[derive(Debug, Fail)]
+pub enum CryostasisError {
+    #[fail(display = "Couldn't find a room description in: {}", text)]
+    UnreadableRoom { text: String },
+
+    #[fail(display = "Explored the whole ship without finding the security checkpoint")]
+    NoCheckpoint {},
+
+    #[fail(display = "Transcript expected {:?} but was sent {:?}", expected, sent)]
+    Diverged { expected: String, sent: String },
+
+    #[fail(display = "Transcript ended before {:?} was sent", sent)]
+    TranscriptEnded { sent: String },
+
+    #[fail(display = "No combination of items gets past the pressure-sensitive floor")]
+    NoCombination {},
+
+    #[fail(display = "{}", _0)]
+    IntCode(#[cause] IntCodeError),
+}
+
+impl From<IntCodeError> for CryostasisError {
+    fn from(error: IntCodeError) -> CryostasisError {
+        CryostasisError::IntCode(error)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Room {
+    pub name: String,
+    pub doors: Vec<String>,
+    pub items: Vec<String>,
+}
+
+/// Reads the last room description in some output. Moving onto the
+/// pressure-sensitive floor describes two rooms, and the droid ends up in the second.
+pub fn parse_room(text: &str) -> Option<Room> {
+    let lines: Vec<&str> = text.lines().map(|line| line.trim()).collect();
+    let start = lines
+        .iter()
+        .rposition(|line| line.starts_with("== ") && line.ends_with(" =="))?;
+
+    let mut room = Room {
+        name: lines[start][3..lines[start].len() - 3].to_string(),
+        doors: vec![],
+        items: vec![],
+    };
+    let mut list = None;
+    for line in &lines[start + 1..] {
+        match *line {
+            "Doors here lead:" => list = Some(&mut room.doors),
+            "Items here:" => list = Some(&mut room.items),
+            _ if line.starts_with("- ") => {
+                if let Some(list) = list.as_mut() {
+                    list.push(line[2..].to_string());
+                }
+            }
+            _ => list = None,
+        }
+    }
+
+    Some(room)
+}
+
+fn opposite(direction: &str) -> &str {
+    match direction {
+        "north" => "south",
+        "south" => "north",
+        "east" => "west",
+        _ => "east",
+    }
+}
+
+/// Something that plays the game: the droid itself, or a recording of it.
+pub trait Terminal {
+    /// Everything printed before the first command is needed.
+    fn start(&mut self) -> Result<String, CryostasisError>;
+
+    /// Sends a command and returns everything printed in response.
+    fn command(&mut self, command: &str) -> Result<String, CryostasisError>;
+}
+
+pub struct Droid {
+    machine: AsciiMachine,
+}
+
+impl Droid {
+    pub fn new(program: &[i32]) -> Droid {
+        Droid {
+            machine: AsciiMachine::new(program),
+        }
+    }
+}
+
+impl Terminal for Droid {
+    fn start(&mut self) -> Result<String, CryostasisError> {
+        Ok(self.machine.run()?.1.text)
+    }
+
+    fn command(&mut self, command: &str) -> Result<String, CryostasisError> {
+        self.machine.send_line(command);
+        Ok(self.machine.run()?.1.text)
+    }
+}
+
+/// Replays a recorded session, written as it appeared on screen: each line
+/// after a `Command?` prompt is the command that was typed.
+pub struct Transcript {
+    opening: String,
+    exchanges: VecDeque<(String, String)>,
+}
+
+impl Transcript {
+    pub fn parse(text: &str) -> Transcript {
+        let mut opening = None;
+        let mut exchanges = VecDeque::new();
+        let mut command: Option<String> = None;
+        let mut output = String::new();
+        let mut lines = text.lines();
+
+        while let Some(line) = lines.next() {
+            output.push_str(line);
+            output.push('\n');
+            if line.trim() != "Command?" {
+                continue;
+            }
+
+            let finished = std::mem::take(&mut output);
+            match command.take() {
+                Some(sent) => exchanges.push_back((sent, finished)),
+                None => opening = Some(finished),
+            }
+            command = lines.next().map(|line| line.trim().to_string());
+        }
+
+        match command {
+            Some(sent) => exchanges.push_back((sent, output)),
+            None if opening.is_none() => opening = Some(output),
+            None => (),
+        }
+
+        Transcript {
+            opening: opening.unwrap_or_default(),
+            exchanges,
+        }
+    }
+}
+
+impl Terminal for Transcript {
+    fn start(&mut self) -> Result<String, CryostasisError> {
+        Ok(self.opening.clone())
+    }
+
+    fn command(&mut self, command: &str) -> Result<String, CryostasisError> {
+        match self.exchanges.pop_front() {
+            Some((expected, response)) => {
+                if expected == command {
+                    Ok(response)
+                } else {
+                    Err(CryostasisError::Diverged {
+                        expected,
+                        sent: command.to_string(),
+                    })
+                }
+            }
+            None => Err(CryostasisError::TranscriptEnded {
+                sent: command.to_string(),
+            }),
+        }
+    }
+}
+
+/// What the droid found while exploring. It finishes back where it started.
+#[derive(Debug, Default)]
+pub struct Ship {
+    pub rooms: HashMap<String, Room>,
+    pub inventory: Vec<String>,
+    /// Directions from the start to the security checkpoint, and then the
+    /// direction of the pressure-sensitive floor.
+    pub checkpoint: Option<(Vec<String>, String)>,
+}
+
+fn read_room(text: String) -> Result<Room, CryostasisError> {
+    parse_room(&text).ok_or(CryostasisError::UnreadableRoom { text })
+}
+
+/// Visits every room reachable without crossing the pressure-sensitive floor,
+/// picking up every item not listed in `avoid`.
+pub fn explore<T: Terminal>(terminal: &mut T, avoid: &[&str]) -> Result<Ship, CryostasisError> {
+    let start = read_room(terminal.start()?)?;
+    let mut ship = Ship::default();
+    visit(terminal, start, None, avoid, &mut vec![], &mut ship)?;
+    Ok(ship)
+}
+
+fn visit<T: Terminal>(
+    terminal: &mut T,
+    room: Room,
+    entered_by: Option<&str>,
+    avoid: &[&str],
+    path: &mut Vec<String>,
+    ship: &mut Ship,
+) -> Result<(), CryostasisError> {
+    for item in &room.items {
+        if !avoid.contains(&item.as_str()) {
+            terminal.command(&format!("take {}", item))?;
+            ship.inventory.push(item.clone());
+        }
+    }
+    ship.rooms.insert(room.name.clone(), room.clone());
+
+    let back = entered_by.map(opposite);
+    for door in room
+        .doors
+        .iter()
+        .filter(|&door| Some(door.as_str()) != back)
+    {
+        if room.name == CHECKPOINT {
+            ship.checkpoint = Some((path.clone(), door.clone()));
+            continue;
+        }
+
+        let next = read_room(terminal.command(door)?)?;
+        if !ship.rooms.contains_key(&next.name) {
+            path.push(door.clone());
+            visit(terminal, next, Some(door), avoid, path, ship)?;
+            path.pop();
+        }
+        terminal.command(opposite(door))?;
+    }
+
+    Ok(())
+}
+
+fn password(text: &str) -> Option<String> {
+    let after = &text[text.find("typing ")? + "typing ".len()..];
+    let digits: String = after.chars().take_while(|c| c.is_ascii_digit()).collect();
+    if digits.is_empty() {
+        None
+    } else {
+        Some(digits)
+    }
+}
+
+fn includes(set: usize, subset: usize) -> bool {
+    set & subset == subset
+}
+
+/// Stands at the checkpoint holding every item, trying combinations until
+/// the floor lets the droid through. Each alert says whether the droid was
+/// too heavy or too light, which rules out every superset or subset of it too.
+fn try_combinations<T: Terminal>(
+    terminal: &mut T,
+    items: &[String],
+    floor: &str,
+) -> Result<String, CryostasisError> {
+    let mut holding: Vec<bool> = vec![true; items.len()];
+    let mut too_heavy: Vec<usize> = vec![];
+    let mut too_light: Vec<usize> = vec![];
+
+    for combination in 0..(1usize << items.len()) {
+        if too_heavy.iter().any(|&h| includes(combination, h))
+            || too_light.iter().any(|&l| includes(l, combination))
+        {
+            continue;
+        }
+
+        for (i, item) in items.iter().enumerate() {
+            let wanted = combination & (1 << i) != 0;
+            if wanted != holding[i] {
+                let verb = if wanted { "take" } else { "drop" };
+                terminal.command(&format!("{} {}", verb, item))?;
+                holding[i] = wanted;
+            }
+        }
+
+        let response = terminal.command(floor)?;
+        if response.contains("heavier than the detected value") {
+            too_light.push(combination);
+        } else if response.contains("lighter than the detected value") {
+            too_heavy.push(combination);
+        } else if let Some(password) = password(&response) {
+            return Ok(password);
+        }
+    }
+
+    Err(CryostasisError::NoCombination {})
+}
+
+pub fn find_password<T: Terminal>(
+    terminal: &mut T,
+    avoid: &[&str],
+) -> Result<String, CryostasisError> {
+    let ship = explore(terminal, avoid)?;
+    let (path, floor) = ship.checkpoint.ok_or(CryostasisError::NoCheckpoint {})?;
+    for direction in &path {
+        terminal.command(direction)?;
+    }
+
+    try_combinations(terminal, &ship.inventory, &floor)
+}
+
+pub fn airlock_password(program: &[i32]) -> String {
+    find_password(&mut Droid::new(program), &DANGEROUS_ITEMS).unwrap()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const HULL_BREACH: &str =
+        "\n\n\n== Hull Breach ==\nYou got in through a hole in the floor here.\n\n\
+                               Doors here lead:\n- north\n\nItems here:\n- coin\n\nCommand?\n";
+
+    #[test]
+    fn parse_room_reads_doors_and_items() {
+        assert_eq!(
+            parse_room(HULL_BREACH),
+            Some(Room {
+                name: "Hull Breach".to_string(),
+                doors: vec!["north".to_string()],
+                items: vec!["coin".to_string()],
+            })
+        )
+    }
+
+    #[test]
+    fn explore_replays_a_transcript() {
+        let mut transcript = Transcript::parse(&format!(
+            "{}take coin\n\nYou take the coin.\n\nCommand?\nnorth\n\n\n\n\
+             == Kitchen ==\nEverything is frozen.\n\nDoors here lead:\n- south\n\n\
+             Items here:\n- molten lava\n\nCommand?\nsouth\n{}",
+            HULL_BREACH, HULL_BREACH
+        ));
+        let ship = explore(&mut transcript, &DANGEROUS_ITEMS).unwrap();
+        assert_eq!(ship.inventory, vec!["coin".to_string()]);
+        assert_eq!(ship.rooms.len(), 2);
+        assert_eq!(ship.checkpoint, None)
+    }
+
+    #[test]
+    fn transcript_reports_divergence() {
+        let mut transcript = Transcript::parse(&format!("{}north\n", HULL_BREACH));
+        match transcript.command("south") {
+            Err(CryostasisError::Diverged { expected, sent }) => {
+                assert_eq!((expected.as_str(), sent.as_str()), ("north", "south"))
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    // Hull Breach, with the Kitchen to the north and the Security Checkpoint
+    // to the east, guarding a floor which wants exactly the mug and the coin.
+    struct SimulatedShip {
+        room: &'static str,
+        held: Vec<String>,
+        lying: HashMap<&'static str, Vec<String>>,
+    }
+
+    impl SimulatedShip {
+        fn new() -> SimulatedShip {
+            let mut lying = HashMap::new();
+            lying.insert("Hull Breach", vec!["coin".to_string()]);
+            lying.insert(
+                "Kitchen",
+                vec!["mug".to_string(), "molten lava".to_string()],
+            );
+            lying.insert(CHECKPOINT, vec!["spool of cat6".to_string()]);
+            SimulatedShip {
+                room: "Hull Breach",
+                held: vec![],
+                lying,
+            }
+        }
+
+        fn describe(&self) -> String {
+            let doors: &[&str] = match self.room {
+                "Hull Breach" => &["north", "east"],
+                "Kitchen" => &["south"],
+                _ => &["east", "west"],
+            };
+            let mut text = format!("\n\n\n== {} ==\nA room.\n\nDoors here lead:\n", self.room);
+            for door in doors {
+                text.push_str(&format!("- {}\n", door));
+            }
+            if !self.lying[self.room].is_empty() {
+                text.push_str("\nItems here:\n");
+                for item in &self.lying[self.room] {
+                    text.push_str(&format!("- {}\n", item));
+                }
+            }
+            text + "\nCommand?\n"
+        }
+    }
+
+    impl Terminal for SimulatedShip {
+        fn start(&mut self) -> Result<String, CryostasisError> {
+            Ok(self.describe())
+        }
+
+        fn command(&mut self, command: &str) -> Result<String, CryostasisError> {
+            let weight = |item: &String| match item.as_str() {
+                "mug" => 3,
+                "coin" => 5,
+                _ => 1,
+            };
+
+            let next = match (self.room, command) {
+                ("Hull Breach", "north") => "Kitchen",
+                ("Hull Breach", "east") => CHECKPOINT,
+                ("Kitchen", "south") | (CHECKPOINT, "west") => "Hull Breach",
+                (CHECKPOINT, "east") => {
+                    let carried: i32 = self.held.iter().map(weight).sum();
+                    let verdict = match carried {
+                        8 => {
+                            return Ok(
+                                "You may proceed. Get in by typing 2424 on the keypad.".into()
+                            )
+                        }
+                        w if w < 8 => "heavier",
+                        _ => "lighter",
+                    };
+                    return Ok(format!(
+                        "== Pressure-Sensitive Floor ==\nAlert! Droids on this ship are {} than the detected value!\n{}",
+                        verdict,
+                        self.describe()
+                    ));
+                }
+                _ => {
+                    let (verb, item) = command.split_at(5);
+                    let lying = self.lying.get_mut(self.room).unwrap();
+                    if verb == "take " {
+                        lying.retain(|i| i != item);
+                        self.held.push(item.to_string());
+                    } else {
+                        self.held.retain(|i| i != item);
+                        lying.push(item.to_string());
+                    }
+                    return Ok("\nCommand?\n".to_string());
+                }
+            };
+            self.room = next;
+            Ok(self.describe())
+        }
+    }
+
+    #[test]
+    fn finds_the_password_on_a_simulated_ship() {
+        let mut ship = SimulatedShip::new();
+        assert_eq!(find_password(&mut ship, &DANGEROUS_ITEMS).unwrap(), "2424");
+        assert!(!ship.held.contains(&"molten lava".to_string()))
+    }
+}
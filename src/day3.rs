@@ -1,3 +1,4 @@
+use crate::grid::{manhattan, Point};
 use aoc_runner_derive::{aoc, aoc_generator};
 use array_tool::vec::Intersect;
 
@@ -22,7 +23,7 @@ pub struct PathSegment {
     length: i32,
 }
 
-pub fn get_path(segment: &PathSegment, origin_x: i32, origin_y: i32) -> Vec<(i32, i32)> {
+pub fn get_path(segment: &PathSegment, origin_x: i32, origin_y: i32) -> Vec<Point> {
     match segment.direction {
         'R' => ((origin_x + 1)..=(origin_x + segment.length))
             .map(|n| (n, origin_y))
@@ -42,8 +43,8 @@ pub fn get_path(segment: &PathSegment, origin_x: i32, origin_y: i32) -> Vec<(i32
     }
 }
 
-pub fn get_entire_path(segments: &[PathSegment]) -> Vec<(i32, i32)> {
-    let mut last: (i32, i32) = (0, 0);
+pub fn get_entire_path(segments: &[PathSegment]) -> Vec<Point> {
+    let mut last: Point = (0, 0);
     segments
         .iter()
         .flat_map(|seg| {
@@ -62,7 +63,7 @@ pub fn find_closest_intersection(wires: &[Vec<PathSegment>]) -> i32 {
 
     intersections
         .iter()
-        .map(|(x, y)| x.abs() + y.abs())
+        .map(|&intersection| manhattan((0, 0), intersection))
         .min()
         .unwrap()
 }
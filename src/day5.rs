@@ -1,12 +1,10 @@
+use crate::intcode::program;
 use crate::intcode::Memory;
 use aoc_runner_derive::{aoc, aoc_generator};
 
 #[aoc_generator(day5)]
 pub fn converter(input: &str) -> Vec<i32> {
-    input
-        .split(",")
-        .map(|n| n.parse::<i32>().unwrap())
-        .collect()
+    program::parse(input).unwrap()
 }
 
 #[aoc(day5, part1)]
@@ -20,4 +18,3 @@ pub fn thermal_radiator_diagnostic(program: &[i32]) -> i32 {
     let mut memory = program.to_vec();
     memory.run(5).unwrap()
 }
-
@@ -0,0 +1,181 @@
+use std::collections::HashMap;
+
+/// An `(x, y)` coordinate, with `y` increasing upwards as in `day3`.
+pub type Point = (i32, i32);
+
+pub fn manhattan(a: Point, b: Point) -> i32 {
+    (a.0 - b.0).abs() + (a.1 - b.1).abs()
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Direction {
+    Up,
+    Right,
+    Down,
+    Left,
+}
+
+impl Direction {
+    pub fn turn_left(self) -> Direction {
+        match self {
+            Direction::Up => Direction::Left,
+            Direction::Left => Direction::Down,
+            Direction::Down => Direction::Right,
+            Direction::Right => Direction::Up,
+        }
+    }
+
+    pub fn turn_right(self) -> Direction {
+        match self {
+            Direction::Up => Direction::Right,
+            Direction::Right => Direction::Down,
+            Direction::Down => Direction::Left,
+            Direction::Left => Direction::Up,
+        }
+    }
+
+    pub fn step(self, (x, y): Point) -> Point {
+        match self {
+            Direction::Up => (x, y + 1),
+            Direction::Right => (x + 1, y),
+            Direction::Down => (x, y - 1),
+            Direction::Left => (x - 1, y),
+        }
+    }
+
+    /// Like `step`, but for screen coordinates where `y` increases downwards.
+    pub fn step_screen(self, (x, y): Point) -> Point {
+        let (x, y) = self.step((x, -y));
+        (x, -y)
+    }
+}
+
+/// An unbounded grid which only stores the cells that have been set.
+#[derive(Clone, Debug)]
+pub struct Grid<T> {
+    cells: HashMap<Point, T>,
+}
+
+impl<T: Copy> Default for Grid<T> {
+    fn default() -> Grid<T> {
+        Grid::new()
+    }
+}
+
+impl<T: Copy> Grid<T> {
+    pub fn new() -> Grid<T> {
+        Grid {
+            cells: HashMap::new(),
+        }
+    }
+
+    pub fn get(&self, point: Point) -> Option<T> {
+        self.cells.get(&point).copied()
+    }
+
+    pub fn set(&mut self, point: Point, value: T) {
+        self.cells.insert(point, value);
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&Point, &T)> {
+        self.cells.iter()
+    }
+
+    /// The smallest and largest corners of the area containing every set cell.
+    pub fn bounds(&self) -> Option<(Point, Point)> {
+        let xs = self.cells.keys().map(|&(x, _)| x);
+        let ys = self.cells.keys().map(|&(_, y)| y);
+        Some((
+            (xs.clone().min()?, ys.clone().min()?),
+            (xs.max()?, ys.max()?),
+        ))
+    }
+
+    /// Draws the grid with the highest row first, one character per cell.
+    pub fn render<F>(&self, draw: F) -> String
+    where
+        F: Fn(Option<T>) -> char,
+    {
+        self.render_rows(true, draw)
+    }
+
+    /// Draws the grid with the lowest row first, for cells in screen coordinates.
+    pub fn render_screen<F>(&self, draw: F) -> String
+    where
+        F: Fn(Option<T>) -> char,
+    {
+        self.render_rows(false, draw)
+    }
+
+    fn render_rows<F>(&self, highest_first: bool, draw: F) -> String
+    where
+        F: Fn(Option<T>) -> char,
+    {
+        let ((min_x, min_y), (max_x, max_y)) = match self.bounds() {
+            Some(bounds) => bounds,
+            None => return String::new(),
+        };
+
+        let mut rows: Vec<String> = (min_y..=max_y)
+            .map(|y| {
+                (min_x..=max_x)
+                    .map(|x| draw(self.get((x, y))))
+                    .collect::<String>()
+            })
+            .collect();
+        if highest_first {
+            rows.reverse();
+        }
+
+        rows.join("\n")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn turning_four_times_returns_to_start() {
+        let mut direction = Direction::Up;
+        for _ in 0..4 {
+            direction = direction.turn_right();
+        }
+        assert_eq!(direction, Direction::Up);
+        assert_eq!(Direction::Up.turn_left(), Direction::Left)
+    }
+
+    #[test]
+    fn step_matches_day3_orientation() {
+        assert_eq!(Direction::Up.step((0, 0)), (0, 1));
+        assert_eq!(Direction::Left.step((0, 0)), (-1, 0))
+    }
+
+    #[test]
+    fn step_screen_moves_up_the_screen() {
+        assert_eq!(Direction::Up.step_screen((0, 0)), (0, -1));
+        assert_eq!(Direction::Right.step_screen((0, 0)), (1, 0))
+    }
+
+    #[test]
+    fn render_puts_highest_row_first() {
+        let mut grid = Grid::new();
+        grid.set((0, 1), true);
+        grid.set((1, 0), true);
+        assert_eq!(
+            grid.render(|cell| if cell.is_some() { '#' } else { '.' }),
+            "#.\n.#"
+        )
+    }
+
+    #[test]
+    fn render_screen_puts_lowest_row_first() {
+        let mut grid = Grid::new();
+        grid.set((0, 1), true);
+        grid.set((1, 0), true);
+        assert_eq!(
+            grid.render_screen(|cell| if cell.is_some() { '#' } else { '.' }),
+            ".#\n#."
+        )
+    }
+}
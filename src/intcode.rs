@@ -1,43 +1,77 @@
+use executor::{Executor, Io, Registers, Standard};
 use failure::Fail;
-
+use instruction::Instruction;
+use protection::{Access, Protection};
+use replay::{Event, Recording};
+use space::AddressSpace;
+use std::collections::VecDeque;
+use watch::{Modification, Watcher};
+
+pub mod ascii;
+pub mod asynchronous;
+pub mod cfg;
+pub mod conformance;
+pub mod decompile;
+pub mod diff;
+pub mod executor;
+pub mod fuzz;
+pub mod image;
+pub mod instruction;
+pub mod network;
+pub mod optimise;
+pub mod profile;
+pub mod program;
+pub mod protection;
+pub mod reference;
+pub mod replay;
+pub mod search;
+pub mod shell;
+pub mod space;
+pub mod symbolic;
+pub mod transpile;
+pub mod watch;
+
+/// Runs a program to the end, giving it the same `input` whenever it asks
+/// and printing its output, and returns what's left at address 0.
 pub trait Memory {
-    fn add(&mut self, instruction: FullInstruction);
-    fn get_full_instruction(
-        &self,
-        modes: (ParameterMode, ParameterMode, ParameterMode),
-        ptr: &mut usize,
-    ) -> Result<FullInstruction, IntCodeError>;
-    fn get_simple_instruction(
-        &self,
-        modes: (ParameterMode, ParameterMode, ParameterMode),
-        ptr: &mut usize,
-    ) -> Result<SimpleInstruction, IntCodeError>;
-    fn get_complex_instruction(
-        &self,
-        modes: (ParameterMode, ParameterMode, ParameterMode),
-        ptr: &mut usize,
-    ) -> Result<ComplexInstruction, IntCodeError>;
-    fn get_parameter(&self, parameter: i32, mode: ParameterMode) -> i32;
-    fn jump_true(&self, instruction: ComplexInstruction, ptr: &mut usize);
-    fn jump_false(&self, instruction: ComplexInstruction, ptr: &mut usize);
-    fn lt(&mut self, instruction: FullInstruction);
-    fn eq(&mut self, instruction: FullInstruction);
-    fn mul(&mut self, instruction: FullInstruction);
-    fn put(&mut self, instruction: SimpleInstruction, input: i32);
-    fn out(&self, instruction: SimpleInstruction);
     fn run(&mut self, input: i32) -> Result<i32, IntCodeError>;
 }
 
+/// Always has the same input, and prints output.
+struct Console {
+    input: i32,
+}
+
+impl Io for Console {
+    fn input(&mut self) -> Option<i32> {
+        Some(self.input)
+    }
+
+    fn output(&mut self, value: i32) {
+        println!("{}", value);
+    }
+}
+
+impl<M: AddressSpace> Memory for M {
+    fn run(&mut self, input: i32) -> Result<i32, IntCodeError> {
+        let mut registers = Registers::default();
+        let mut console = Console { input };
+        while registers.ptr < self.len() {
+            let instruction = Instruction::decode(self, registers.ptr)?;
+            if Standard.execute(&mut registers, self, instruction, &mut console)? == State::Halted {
+                break;
+            }
+        }
+
+        self.read(0)
+    }
+}
+
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub enum ParameterMode {
     Position,
     Immediate,
-}
-
-pub enum InstructionLength {
-    Full = 4,
-    Complex = 3,
-    Simple = 2,
+    Relative,
 }
 
 impl ParameterMode {
@@ -45,6 +79,7 @@ impl ParameterMode {
         match val {
             0 => Ok(ParameterMode::Position),
             1 => Ok(ParameterMode::Immediate),
+            2 => Ok(ParameterMode::Relative),
             _ => Err(IntCodeError::UnrecognisedParameterMode { val: val }),
         }
     }
@@ -60,27 +95,28 @@ pub enum IntCodeError {
 
     #[fail(display = "Not a recognised ParameterMode: {}", val)]
     UnrecognisedParameterMode { val: i32 },
-}
 
-#[derive(Clone, Copy, Debug, PartialEq)]
-pub struct FullInstruction {
-    pub address: i32,
-    pub modes: (ParameterMode, ParameterMode, ParameterMode),
-    pub noun: i32,
-    pub verb: i32,
-}
+    #[fail(display = "Attempted to access a negative address: {}", address)]
+    NegativeAddressError { address: i32 },
 
-#[derive(Clone, Copy, Debug, PartialEq)]
-pub struct ComplexInstruction {
-    pub address: i32,
-    pub noun: i32,
-    pub modes: (ParameterMode, ParameterMode, ParameterMode),
-}
+    #[fail(display = "Instruction at {} overflowed", pc)]
+    Overflow { pc: usize },
 
-#[derive(Clone, Copy, Debug, PartialEq)]
-pub struct SimpleInstruction {
-    pub address: i32,
-    pub modes: (ParameterMode, ParameterMode, ParameterMode),
+    #[fail(display = "Attempted to write to read-only address {}", address)]
+    ReadOnlyWrite { address: usize },
+
+    #[fail(display = "Instruction at {} modified code at {}", pc, address)]
+    SelfModification { pc: usize, address: usize },
+
+    #[fail(
+        display = "Instruction at {} isn't allowed {:?} access to {}",
+        pc, kind, address
+    )]
+    ProtectionFault {
+        pc: usize,
+        address: usize,
+        kind: Access,
+    },
 }
 
 fn get_modes(head: i32) -> Result<(ParameterMode, ParameterMode, ParameterMode), IntCodeError> {
@@ -91,156 +127,278 @@ fn get_modes(head: i32) -> Result<(ParameterMode, ParameterMode, ParameterMode),
     Ok((first, second, third))
 }
 
-impl Memory for Vec<i32> {
-    fn add(&mut self, instruction: FullInstruction) {
-        let (noun_mode, verb_mode, _) = instruction.modes;
-        let noun = self.get_parameter(instruction.noun, noun_mode);
-        let verb = self.get_parameter(instruction.verb, verb_mode);
-        self[instruction.address as usize] = noun + verb;
+/// Where a `Machine` stopped after `step` or `run`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum State {
+    Running,
+    AwaitingInput,
+    Halted,
+}
+
+/// A resumable Intcode computer. Unlike `Memory::run`, it keeps its instruction
+/// pointer and relative base between calls, reads from an input queue, captures
+/// output instead of printing it, and grows memory on demand. Memory is a `Vec`
+/// and instructions do what the puzzles say unless `Machine::with` is given
+/// another address space or executor.
+#[derive(Clone, Debug)]
+pub struct Machine<M = Vec<i32>, E = Standard> {
+    memory: M,
+    executor: E,
+    registers: Registers,
+    input: VecDeque<i32>,
+    output: VecDeque<i32>,
+    /// How many instructions have run.
+    steps: usize,
+    watcher: Option<Watcher>,
+    protection: Option<Protection>,
+    recording: Option<Recording>,
+}
+
+impl Machine {
+    pub fn new(program: &[i32]) -> Machine {
+        Machine::with(program.to_vec(), Standard)
     }
 
-    fn eq(&mut self, instruction: FullInstruction) {
-        let (noun_mode, verb_mode, _) = instruction.modes;
-        let noun = self.get_parameter(instruction.noun, noun_mode);
-        let verb = self.get_parameter(instruction.verb, verb_mode);
-        if noun == verb {
-            self[instruction.address as usize] = 1;
-        } else {
-            self[instruction.address as usize] = 0;
+    /// Puts the machine back to how `Machine::new(program)` would create it,
+    /// reusing the memory already allocated.
+    pub fn reset(&mut self, program: &[i32]) {
+        self.memory.clear();
+        self.memory.extend_from_slice(program);
+        self.registers = Registers::default();
+        self.input.clear();
+        self.output.clear();
+        self.steps = 0;
+        if let Some(watcher) = &mut self.watcher {
+            watcher.clear();
+        }
+        if let Some(recording) = &mut self.recording {
+            recording.events.clear();
         }
     }
 
-    fn get_full_instruction(
-        &self,
-        modes: (ParameterMode, ParameterMode, ParameterMode),
-        ptr: &mut usize,
-    ) -> Result<FullInstruction, IntCodeError> {
-        if (*ptr + 3) >= self.len() {
-            return Err(IntCodeError::UnexpectedEndOfInputError {});
+    pub fn memory(&self) -> &[i32] {
+        &self.memory
+    }
+
+    /// Writes straight into memory, growing it if need be.
+    pub fn poke(&mut self, address: usize, value: i32) {
+        if address >= self.memory.len() {
+            self.memory.resize(address + 1, 0);
         }
+        self.memory[address] = value;
+    }
+}
 
-        let noun = self[*ptr + 1];
-        let verb = self[*ptr + 2];
-        let address = self[*ptr + 3];
-        *ptr += InstructionLength::Full as usize;
-
-        Ok(FullInstruction {
-            address,
-            modes,
-            noun,
-            verb,
-        })
-    }
-
-    fn get_complex_instruction(
-        &self,
-        modes: (ParameterMode, ParameterMode, ParameterMode),
-        ptr: &mut usize,
-    ) -> Result<ComplexInstruction, IntCodeError> {
-        if (*ptr + 2) >= self.len() {
-            return Err(IntCodeError::UnexpectedEndOfInputError {});
+impl<M: AddressSpace, E: Executor> Machine<M, E> {
+    pub fn with(memory: M, executor: E) -> Machine<M, E> {
+        Machine {
+            memory,
+            executor,
+            registers: Registers::default(),
+            input: VecDeque::new(),
+            output: VecDeque::new(),
+            steps: 0,
+            watcher: None,
+            protection: None,
+            recording: None,
         }
+    }
+
+    pub fn address_space(&self) -> &M {
+        &self.memory
+    }
 
-        let noun = self[*ptr + 1];
-        let address = self[*ptr + 2];
-        *ptr += InstructionLength::Complex as usize;
+    pub fn executor(&self) -> &E {
+        &self.executor
+    }
 
-        Ok(ComplexInstruction {
-            address,
-            modes,
-            noun,
-        })
+    /// Starts noticing the program writing into its own code. In strict mode
+    /// that stops it with `IntCodeError::SelfModification`; otherwise it's
+    /// recorded in `modifications`. Writing over an instruction which has
+    /// already run counts, so strict mode stops day 2 at its first
+    /// instruction, which stores its result in its own last operand.
+    pub fn watch(&mut self, strict: bool) {
+        self.watcher = Some(Watcher::new(strict));
     }
 
-    fn get_simple_instruction(
-        &self,
-        modes: (ParameterMode, ParameterMode, ParameterMode),
-        ptr: &mut usize,
-    ) -> Result<SimpleInstruction, IntCodeError> {
-        if (*ptr + 1) >= self.len() {
-            return Err(IntCodeError::UnexpectedEndOfInputError {});
+    pub fn modifications(&self) -> &[Modification] {
+        match &self.watcher {
+            Some(watcher) => watcher.modifications(),
+            None => &[],
         }
+    }
+
+    /// Stops the program with `IntCodeError::ProtectionFault` if it writes
+    /// anywhere but read-write data or runs anything but code.
+    pub fn protect(&mut self, protection: Protection) {
+        self.protection = Some(protection);
+    }
 
-        let address = self[*ptr + 1];
-        *ptr += InstructionLength::Simple as usize;
+    /// Starts keeping a `Recording` of every value read and written, which
+    /// `replay::replay` can check a later run against.
+    pub fn record(&mut self) {
+        self.recording = Some(Recording::default());
+    }
+
+    pub fn recording(&self) -> Option<&Recording> {
+        self.recording.as_ref()
+    }
+
+    pub fn steps(&self) -> usize {
+        self.steps
+    }
+
+    pub fn registers(&self) -> Registers {
+        self.registers
+    }
+
+    /// Moves the instruction pointer and relative base, as when going back to
+    /// an earlier state.
+    pub fn set_registers(&mut self, registers: Registers) {
+        self.registers = registers;
+    }
 
-        Ok(SimpleInstruction { address, modes })
+    /// Input that has been pushed but not read yet.
+    pub fn pending_input(&self) -> Vec<i32> {
+        self.input.iter().copied().collect()
     }
 
-    fn get_parameter(&self, parameter: i32, mode: ParameterMode) -> i32 {
-        match mode {
-            ParameterMode::Position => self[parameter as usize],
-            ParameterMode::Immediate => parameter,
+    pub fn push_input(&mut self, value: i32) {
+        self.input.push_back(value);
+    }
+
+    pub fn take_output(&mut self) -> Vec<i32> {
+        self.output.drain(..).collect()
+    }
+
+    pub fn step(&mut self) -> Result<State, IntCodeError> {
+        let pc = self.registers.ptr;
+        if self.memory.peek(pc).is_none() {
+            return Ok(State::Halted);
+        }
+        if let Some(protection) = &self.protection {
+            protection.check(pc, pc, Access::Execute)?;
+        }
+        let instruction = Instruction::decode(&self.memory, pc)?;
+        if matches!(instruction, Instruction::Input(_)) && self.input.is_empty() {
+            return Ok(State::AwaitingInput);
+        }
+        if let Some(protection) = &self.protection {
+            for address in pc + 1..pc + instruction.size() {
+                protection.check(pc, address, Access::Execute)?;
+            }
+        }
+        if let Some(watcher) = &mut self.watcher {
+            watcher.fetch(pc, instruction.size())?;
+        }
+
+        let mut io = Queues {
+            input: &mut self.input,
+            output: &mut self.output,
+            recording: self.recording.as_mut(),
+            step: self.steps,
+        };
+        let state = if self.watcher.is_some() || self.protection.is_some() {
+            let mut memory = Checked {
+                memory: &mut self.memory,
+                watcher: self.watcher.as_mut(),
+                protection: self.protection.as_ref(),
+                pc,
+            };
+            self.executor
+                .execute(&mut self.registers, &mut memory, instruction, &mut io)?
+        } else {
+            self.executor
+                .execute(&mut self.registers, &mut self.memory, instruction, &mut io)?
+        };
+
+        if state == State::Running {
+            self.steps += 1;
         }
+        Ok(state)
     }
 
-    fn jump_true(&self, instruction: ComplexInstruction, ptr: &mut usize) {
-        if self.get_parameter(instruction.noun, instruction.modes.0) != 0 {
-            *ptr = self.get_parameter(instruction.address, instruction.modes.1) as usize;
+    /// Runs until the program halts or needs input that hasn't been provided.
+    pub fn run(&mut self) -> Result<State, IntCodeError> {
+        loop {
+            match self.step()? {
+                State::Running => continue,
+                state => return Ok(state),
+            }
         }
     }
+}
+
+/// A machine's input and output queues, adding what passes through to its
+/// recording if it has one.
+struct Queues<'a> {
+    input: &'a mut VecDeque<i32>,
+    output: &'a mut VecDeque<i32>,
+    recording: Option<&'a mut Recording>,
+    step: usize,
+}
 
-    fn jump_false(&self, instruction: ComplexInstruction, ptr: &mut usize) {
-        if self.get_parameter(instruction.noun, instruction.modes.0) == 0 {
-            *ptr = self.get_parameter(instruction.address, instruction.modes.1) as usize;
+impl<'a> Io for Queues<'a> {
+    fn input(&mut self) -> Option<i32> {
+        let value = self.input.pop_front()?;
+        if let Some(recording) = &mut self.recording {
+            recording.events.push(Event::Input {
+                step: self.step,
+                value,
+            });
         }
+        Some(value)
     }
 
-    fn lt(&mut self, instruction: FullInstruction) {
-        let (noun_mode, verb_mode, _) = instruction.modes;
-        let noun = self.get_parameter(instruction.noun, noun_mode);
-        let verb = self.get_parameter(instruction.verb, verb_mode);
-        if noun < verb {
-            self[instruction.address as usize] = 1;
-        } else {
-            self[instruction.address as usize] = 0;
+    fn output(&mut self, value: i32) {
+        if let Some(recording) = &mut self.recording {
+            recording.events.push(Event::Output {
+                step: self.step,
+                value,
+            });
         }
+        self.output.push_back(value);
     }
+}
 
-    fn mul(&mut self, instruction: FullInstruction) {
-        let (noun_mode, verb_mode, _) = instruction.modes;
-        let noun = self.get_parameter(instruction.noun, noun_mode);
-        let verb = self.get_parameter(instruction.verb, verb_mode);
-        self[instruction.address as usize] = noun * verb;
+/// A machine's memory, checking every write made by the instruction at `pc`
+/// against its protection and telling its watcher about it.
+struct Checked<'a, M> {
+    memory: &'a mut M,
+    watcher: Option<&'a mut Watcher>,
+    protection: Option<&'a Protection>,
+    pc: usize,
+}
+
+impl<'a, M: AddressSpace> AddressSpace for Checked<'a, M> {
+    fn len(&self) -> usize {
+        self.memory.len()
     }
 
-    fn out(&self, instruction: SimpleInstruction) {
-        println!(
-            "{}",
-            self.get_parameter(instruction.address, instruction.modes.0)
-        );
+    fn peek(&self, address: usize) -> Option<i32> {
+        self.memory.peek(address)
     }
 
-    fn put(&mut self, instruction: SimpleInstruction, input: i32) {
-        self[instruction.address as usize] = input;
+    fn read(&mut self, address: usize) -> Result<i32, IntCodeError> {
+        self.memory.read(address)
     }
 
-    fn run(&mut self, input: i32) -> Result<i32, IntCodeError> {
-        let mut ptr = 0;
-        while ptr < self.len() {
-            let opcode = self[ptr] % 100;
-            let modes = get_modes(self[ptr])?;
-
-            match opcode {
-                1 => self.add(self.get_full_instruction(modes, &mut ptr)?),
-                2 => self.mul(self.get_full_instruction(modes, &mut ptr)?),
-                3 => self.put(self.get_simple_instruction(modes, &mut ptr)?, input),
-                4 => self.out(self.get_simple_instruction(modes, &mut ptr)?),
-                5 => self.jump_true(self.get_complex_instruction(modes, &mut ptr)?, &mut ptr),
-                6 => self.jump_false(self.get_complex_instruction(modes, &mut ptr)?, &mut ptr),
-                7 => self.lt(self.get_full_instruction(modes, &mut ptr)?),
-                8 => self.eq(self.get_full_instruction(modes, &mut ptr)?),
-                99 => break,
-                _ => return Err(IntCodeError::UnknownOpcodeError { opcode }),
-            }
+    fn write(&mut self, address: usize, value: i32) -> Result<(), IntCodeError> {
+        if let Some(protection) = self.protection {
+            protection.check(self.pc, address, Access::Write)?;
         }
-
-        Ok(self[0])
+        if let Some(watcher) = &mut self.watcher {
+            watcher.write(self.pc, address)?;
+        }
+        self.memory.write(address, value)
     }
 }
 
 #[cfg(test)]
 mod test {
+    use super::executor::Tracing;
+    use super::instruction::Parameter;
+    use super::space::{ReadOnlyCode, Sparse};
     use super::*;
 
     #[test]
@@ -300,4 +458,83 @@ mod test {
             )
         )
     }
+
+    #[test]
+    fn machine_pauses_for_input_and_resumes() {
+        let mut machine = Machine::new(&[3, 9, 1001, 9, 1, 9, 4, 9, 99, 0]);
+        assert_eq!(machine.run().unwrap(), State::AwaitingInput);
+        machine.push_input(41);
+        assert_eq!(machine.run().unwrap(), State::Halted);
+        assert_eq!(machine.take_output(), vec![42])
+    }
+
+    #[test]
+    fn machine_supports_relative_mode_and_grows_memory() {
+        let quine = vec![
+            109, 1, 204, -1, 1001, 100, 1, 100, 1008, 100, 16, 101, 1006, 101, 0, 99,
+        ];
+        let mut machine = Machine::new(&quine);
+        assert_eq!(machine.run().unwrap(), State::Halted);
+        assert_eq!(machine.take_output(), quine)
+    }
+
+    #[test]
+    fn reset_runs_the_program_again() {
+        let program = vec![3, 9, 1001, 9, 1, 9, 4, 9, 99, 0];
+        let mut machine = Machine::new(&program);
+        machine.push_input(1);
+        machine.run().unwrap();
+        machine.reset(&program);
+        machine.push_input(5);
+        machine.run().unwrap();
+        assert_eq!(machine.take_output(), vec![6])
+    }
+
+    #[test]
+    fn machines_mix_address_spaces_and_executors() {
+        let quine = vec![
+            109, 1, 204, -1, 1001, 100, 1, 100, 1008, 100, 16, 101, 1006, 101, 0, 99,
+        ];
+        let mut machine = Machine::with(Sparse::new(&quine), Tracing::new(Standard));
+        assert_eq!(machine.run().unwrap(), State::Halted);
+        assert_eq!(machine.take_output(), quine);
+        let trace = &machine.executor().trace;
+        assert_eq!(trace.len(), machine.steps() + 1);
+        assert_eq!(
+            trace[0],
+            (
+                0,
+                Instruction::AdjustRelativeBase(Parameter {
+                    value: 1,
+                    mode: ParameterMode::Immediate
+                })
+            )
+        );
+        assert_eq!(trace.last().unwrap().1, Instruction::Halt)
+    }
+
+    #[test]
+    fn read_only_code_stops_programs_writing_to_it() {
+        let program = [1, 0, 0, 0, 99];
+        let mut machine = Machine::with(ReadOnlyCode::new(program.to_vec(), 0..5), Standard);
+        match machine.run() {
+            Err(IntCodeError::ReadOnlyWrite { address }) => assert_eq!(address, 0),
+            other => panic!("expected a read-only write, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn overflow_is_an_error() {
+        for (program, at) in &[
+            (vec![1101, 2_000_000_000, 2_000_000_000, 0, 99], 0),
+            (vec![1102, 100_000, 100_000, 0, 99], 0),
+            (vec![109, 2_000_000_000, 109, 2_000_000_000, 99], 2),
+            (vec![109, 2_000_000_000, 204, 2_000_000_000, 99], 2),
+        ] {
+            match Machine::new(program).run() {
+                Err(IntCodeError::Overflow { pc }) => assert_eq!(pc, *at),
+                other => panic!("expected an overflow, got {:?}", other),
+            }
+        }
+    }
 }
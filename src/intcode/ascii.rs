@@ -0,0 +1,88 @@
+use super::{IntCodeError, Machine, State};
+
+/// Output from a program that talks in ASCII, split into the text and any
+/// values outside the ASCII range (usually the answer the puzzle wants).
+#[derive(Debug, Default, PartialEq)]
+pub struct AsciiOutput {
+    pub text: String,
+    pub values: Vec<i32>,
+}
+
+impl AsciiOutput {
+    fn from_output(output: &[i32]) -> AsciiOutput {
+        let mut ascii = AsciiOutput::default();
+        for &value in output {
+            if (0..128).contains(&value) {
+                ascii.text.push(value as u8 as char);
+            } else {
+                ascii.values.push(value);
+            }
+        }
+
+        ascii
+    }
+}
+
+/// A `Machine` which is sent text and answers with text.
+#[derive(Clone, Debug)]
+pub struct AsciiMachine {
+    machine: Machine,
+}
+
+impl AsciiMachine {
+    pub fn new(program: &[i32]) -> AsciiMachine {
+        AsciiMachine {
+            machine: Machine::new(program),
+        }
+    }
+
+    pub fn send(&mut self, text: &str) {
+        for byte in text.bytes() {
+            self.machine.push_input(byte as i32);
+        }
+    }
+
+    /// Writes `value` into memory before the program runs, growing it if
+    /// needed.
+    pub fn poke(&mut self, address: usize, value: i32) {
+        self.machine.poke(address, value);
+    }
+
+    pub fn send_line(&mut self, line: &str) {
+        self.send(line);
+        self.send("\n");
+    }
+
+    /// Runs until the program halts or wants more input, returning everything it printed.
+    pub fn run(&mut self) -> Result<(State, AsciiOutput), IntCodeError> {
+        let state = self.machine.run()?;
+        Ok((state, AsciiOutput::from_output(&self.machine.take_output())))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn splits_text_from_large_values() {
+        assert_eq!(
+            AsciiOutput::from_output(&[72, 105, 10, 19_355_645]),
+            AsciiOutput {
+                text: "Hi\n".to_string(),
+                values: vec![19_355_645],
+            }
+        )
+    }
+
+    #[test]
+    fn sends_text_as_ascii_codes() {
+        // Echoes the first character it's sent, then reports a score.
+        let mut machine = AsciiMachine::new(&[3, 20, 4, 20, 104, 1000, 99]);
+        machine.send("x\n");
+        let (state, output) = machine.run().unwrap();
+        assert_eq!(state, State::Halted);
+        assert_eq!(output.text, "x");
+        assert_eq!(output.values, vec![1000])
+    }
+}
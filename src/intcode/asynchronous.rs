@@ -0,0 +1,323 @@
+use super::{IntCodeError, Machine, State};
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::future::{self, Future};
+use std::pin::Pin;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll, Wake, Waker};
+
+/// How many instructions a machine runs without any I/O before letting other
+/// tasks have a go.
+const BUDGET: usize = 1_024;
+
+/// Somewhere a machine's input comes from. `None` means nothing more is
+/// coming.
+pub trait Source {
+    fn poll_receive(&mut self, cx: &mut Context) -> Poll<Option<i32>>;
+}
+
+/// Somewhere a machine's output goes.
+pub trait Sink {
+    fn poll_send(&mut self, cx: &mut Context, value: i32) -> Poll<()>;
+}
+
+impl Source for VecDeque<i32> {
+    fn poll_receive(&mut self, _: &mut Context) -> Poll<Option<i32>> {
+        Poll::Ready(self.pop_front())
+    }
+}
+
+impl Sink for Vec<i32> {
+    fn poll_send(&mut self, _: &mut Context, value: i32) -> Poll<()> {
+        self.push(value);
+        Poll::Ready(())
+    }
+}
+
+#[derive(Default)]
+struct Shared {
+    queue: VecDeque<i32>,
+    senders: usize,
+    waker: Option<Waker>,
+}
+
+/// One end of an unbounded channel between machines on the same thread.
+pub struct Sender(Rc<RefCell<Shared>>);
+
+pub struct Receiver(Rc<RefCell<Shared>>);
+
+pub fn channel() -> (Sender, Receiver) {
+    let shared = Rc::new(RefCell::new(Shared {
+        senders: 1,
+        ..Shared::default()
+    }));
+    (Sender(shared.clone()), Receiver(shared))
+}
+
+impl Sender {
+    pub fn send(&self, value: i32) {
+        let mut shared = self.0.borrow_mut();
+        shared.queue.push_back(value);
+        if let Some(waker) = shared.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+impl Clone for Sender {
+    fn clone(&self) -> Sender {
+        self.0.borrow_mut().senders += 1;
+        Sender(self.0.clone())
+    }
+}
+
+impl Drop for Sender {
+    fn drop(&mut self) {
+        let mut shared = self.0.borrow_mut();
+        shared.senders -= 1;
+        if shared.senders == 0 {
+            if let Some(waker) = shared.waker.take() {
+                waker.wake();
+            }
+        }
+    }
+}
+
+impl Sink for Sender {
+    fn poll_send(&mut self, _: &mut Context, value: i32) -> Poll<()> {
+        self.send(value);
+        Poll::Ready(())
+    }
+}
+
+impl Source for Receiver {
+    fn poll_receive(&mut self, cx: &mut Context) -> Poll<Option<i32>> {
+        let mut shared = self.0.borrow_mut();
+        match shared.queue.pop_front() {
+            Some(value) => Poll::Ready(Some(value)),
+            None if shared.senders == 0 => Poll::Ready(None),
+            None => {
+                shared.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+/// Returns `Pending` once, so a busy task lets the others run.
+async fn yield_now() {
+    let mut yielded = false;
+    future::poll_fn(|cx| {
+        if yielded {
+            return Poll::Ready(());
+        }
+        yielded = true;
+        cx.waker().wake_by_ref();
+        Poll::Pending
+    })
+    .await
+}
+
+/// Runs `machine` like `Machine::run`, but waits on `input` whenever the
+/// program wants a value and hands every output to `output` as it's made.
+/// Each instruction is carried out by `Machine::step`, the same as when
+/// running synchronously. Finishes when the program halts, or with
+/// `State::AwaitingInput` once `input` has run dry.
+pub async fn run<I: Source, O: Sink>(
+    machine: &mut Machine,
+    input: &mut I,
+    output: &mut O,
+) -> Result<State, IntCodeError> {
+    let mut quiet = 0;
+    loop {
+        let state = machine.step()?;
+        let values = machine.take_output();
+        quiet = if values.is_empty() { quiet + 1 } else { 0 };
+        for value in values {
+            future::poll_fn(|cx| output.poll_send(cx, value)).await;
+        }
+
+        match state {
+            State::Running if quiet >= BUDGET => {
+                quiet = 0;
+                yield_now().await
+            }
+            State::Running => {}
+            State::AwaitingInput => match future::poll_fn(|cx| input.poll_receive(cx)).await {
+                Some(value) => machine.push_input(value),
+                None => return Ok(State::AwaitingInput),
+            },
+            State::Halted => return Ok(State::Halted),
+        }
+    }
+}
+
+struct Flag(AtomicBool);
+
+impl Wake for Flag {
+    fn wake(self: Arc<Self>) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+}
+
+struct Task<'a> {
+    future: Pin<Box<dyn Future<Output = ()> + 'a>>,
+    woken: Arc<Flag>,
+}
+
+/// A single-threaded executor which polls each task in turn whenever it has
+/// been woken.
+#[derive(Default)]
+pub struct Executor<'a> {
+    tasks: Vec<Task<'a>>,
+}
+
+impl<'a> Executor<'a> {
+    pub fn new() -> Executor<'a> {
+        Executor::default()
+    }
+
+    pub fn spawn(&mut self, future: impl Future<Output = ()> + 'a) {
+        self.tasks.push(Task {
+            future: Box::pin(future),
+            woken: Arc::new(Flag(AtomicBool::new(true))),
+        });
+    }
+
+    /// Runs until every task has finished or none of those left can make
+    /// progress, returning how many were left stuck.
+    pub fn run(&mut self) -> usize {
+        loop {
+            let mut progressed = false;
+            let mut i = 0;
+            while i < self.tasks.len() {
+                let task = &mut self.tasks[i];
+                if !task.woken.0.swap(false, Ordering::SeqCst) {
+                    i += 1;
+                    continue;
+                }
+                progressed = true;
+                let waker = Waker::from(task.woken.clone());
+                let mut cx = Context::from_waker(&waker);
+                match task.future.as_mut().poll(&mut cx) {
+                    Poll::Ready(()) => {
+                        self.tasks.remove(i);
+                    }
+                    Poll::Pending => i += 1,
+                }
+            }
+            if !progressed {
+                return self.tasks.len();
+            }
+        }
+    }
+}
+
+/// Runs a future which doesn't wait on anything outside itself to the end.
+pub fn block_on<T>(future: impl Future<Output = T>) -> T {
+    let mut result = None;
+    let mut executor = Executor::new();
+    executor.spawn(async { result = Some(future.await) });
+    executor.run();
+    drop(executor);
+    result.expect("future waited on something that never happened")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::day5;
+
+    /// Reads values forever, writing each one back plus one.
+    const INCREMENT: [i32; 12] = [3, 11, 1001, 11, 1, 11, 4, 11, 1105, 1, 0, 0];
+
+    #[test]
+    fn gives_the_same_output_as_the_synchronous_machine() {
+        let program = day5::converter(include_str!("../../input/2019/day5.txt"));
+        let mut expected = Machine::new(&program);
+        expected.push_input(5);
+        expected.run().unwrap();
+
+        let mut machine = Machine::new(&program);
+        let mut output = vec![];
+        let state = block_on(run(&mut machine, &mut VecDeque::from(vec![5]), &mut output));
+        assert_eq!(state.unwrap(), State::Halted);
+        assert_eq!(output, expected.take_output())
+    }
+
+    #[test]
+    fn machines_run_together_through_channels() {
+        let (first, mut input) = channel();
+        let mut output = vec![];
+        {
+            let mut executor = Executor::new();
+            for _ in 0..5 {
+                let (sender, receiver) = channel();
+                let mut source = std::mem::replace(&mut input, receiver);
+                executor.spawn(async move {
+                    let mut sender = sender;
+                    let mut machine = Machine::new(&INCREMENT);
+                    run(&mut machine, &mut source, &mut sender).await.unwrap();
+                });
+            }
+            let collect = &mut output;
+            executor.spawn(async move {
+                while let Some(value) = future::poll_fn(|cx| input.poll_receive(cx)).await {
+                    collect.push(value);
+                }
+            });
+            for value in 0..3 {
+                first.send(value);
+            }
+            drop(first);
+            assert_eq!(executor.run(), 0);
+        }
+        assert_eq!(output, vec![5, 6, 7])
+    }
+
+    #[test]
+    fn busy_machines_take_turns() {
+        // Counts down from a large number without any I/O, then outputs 1.
+        let countdown = [1001, 10, -1, 10, 1005, 10, 0, 104, 1, 99, 5_000];
+        let (sender, mut receiver) = channel();
+        let mut order = vec![];
+        {
+            let order = RefCell::new(&mut order);
+            let mut executor = Executor::new();
+            executor.spawn(async {
+                let mut machine = Machine::new(&countdown);
+                run(&mut machine, &mut VecDeque::new(), &mut vec![])
+                    .await
+                    .unwrap();
+                order.borrow_mut().push("busy");
+            });
+            executor.spawn(async {
+                sender.send(1);
+                future::poll_fn(|cx| receiver.poll_receive(cx)).await;
+                order.borrow_mut().push("quick");
+            });
+            assert_eq!(executor.run(), 0);
+        }
+        assert_eq!(order, vec!["quick", "busy"])
+    }
+
+    #[test]
+    fn reports_deadlock() {
+        let (a_out, b_in) = channel();
+        let (b_out, a_in) = channel();
+        let mut executor = Executor::new();
+        for (mut source, mut sink) in [(a_in, a_out), (b_in, b_out)] {
+            executor.spawn(async move {
+                let mut machine = Machine::new(&INCREMENT);
+                run(&mut machine, &mut source, &mut sink).await.unwrap();
+            });
+        }
+        assert_eq!(executor.run(), 2)
+    }
+}
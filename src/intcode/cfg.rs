@@ -0,0 +1,240 @@
+use super::instruction::{Instruction, Parameter};
+use super::ParameterMode;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Where a jump goes: somewhere fixed, or wherever an operand says at the time.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Target {
+    Known(usize),
+    Computed(Parameter),
+}
+
+/// How control leaves a basic block.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Exit {
+    Halt,
+    /// The next instruction can't be decoded.
+    Invalid(usize),
+    Fall(usize),
+    Jump(Target),
+    /// Goes to `target` if `condition` is non-zero and `when` is true, or if
+    /// it's zero and `when` is false; to `next` otherwise.
+    Branch {
+        when: bool,
+        condition: Parameter,
+        target: Target,
+        next: usize,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Block {
+    pub start: usize,
+    /// Every instruction in the block apart from a jump at the end, which is
+    /// described by `exit` instead.
+    pub instructions: Vec<(usize, Instruction)>,
+    pub exit: Exit,
+    /// Just past the last word of the block, including any jump at the end
+    /// or the word which couldn't be decoded.
+    pub end: usize,
+}
+
+impl Block {
+    pub fn successors(&self) -> Vec<usize> {
+        successors(self.exit)
+    }
+}
+
+fn successors(exit: Exit) -> Vec<usize> {
+    match exit {
+        Exit::Halt | Exit::Invalid(_) | Exit::Jump(Target::Computed(_)) => vec![],
+        Exit::Fall(next) | Exit::Jump(Target::Known(next)) => vec![next],
+        Exit::Branch {
+            target: Target::Known(target),
+            next,
+            ..
+        } => vec![target, next],
+        Exit::Branch { next, .. } => vec![next],
+    }
+}
+
+/// The basic blocks of a program, found by following every jump whose
+/// destination is written into the program. Jumps whose condition is an
+/// immediate are treated as always or never taken.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Graph {
+    pub blocks: BTreeMap<usize, Block>,
+}
+
+impl Graph {
+    pub fn build(program: &[i32]) -> Graph {
+        let mut reachable = BTreeMap::new();
+        let mut leaders = BTreeSet::new();
+        leaders.insert(0);
+        let mut pending = vec![0];
+        while let Some(ptr) = pending.pop() {
+            if ptr >= program.len() || reachable.contains_key(&ptr) {
+                continue;
+            }
+            let instruction = Instruction::decode(program, ptr).ok();
+            if let Some(instruction) = &instruction {
+                let exit = exit(ptr, instruction);
+                if let Exit::Jump(_) | Exit::Branch { .. } = exit {
+                    leaders.extend(successors(exit));
+                    leaders.insert(ptr + instruction.size());
+                }
+                pending.extend(successors(exit));
+            }
+            reachable.insert(ptr, instruction);
+        }
+
+        let mut blocks = BTreeMap::new();
+        for &start in leaders.iter().filter(|ptr| reachable.contains_key(ptr)) {
+            let mut block = Block {
+                start,
+                instructions: vec![],
+                exit: Exit::Halt,
+                end: start,
+            };
+            let mut ptr = start;
+            loop {
+                if ptr >= program.len() {
+                    break;
+                }
+                if ptr != start && leaders.contains(&ptr) {
+                    block.exit = Exit::Fall(ptr);
+                    break;
+                }
+                let instruction = match &reachable[&ptr] {
+                    Some(instruction) => instruction,
+                    None => {
+                        block.exit = Exit::Invalid(ptr);
+                        ptr += 1;
+                        break;
+                    }
+                };
+                match exit(ptr, instruction) {
+                    Exit::Fall(next) => {
+                        block.instructions.push((ptr, *instruction));
+                        ptr = next;
+                    }
+                    exit => {
+                        block.exit = exit;
+                        ptr += instruction.size();
+                        break;
+                    }
+                }
+            }
+            block.end = ptr;
+            blocks.insert(start, block);
+        }
+
+        Graph { blocks }
+    }
+
+    /// Every instruction in the graph, including the jumps which end blocks,
+    /// in order of address.
+    pub fn instructions(&self, program: &[i32]) -> Vec<(usize, Instruction)> {
+        let mut instructions = vec![];
+        for block in self.blocks.values() {
+            let end = match block.exit {
+                Exit::Invalid(ptr) => ptr,
+                _ => block.end,
+            };
+            let mut ptr = block.start;
+            while let Some(instruction) =
+                Instruction::decode(program, ptr).ok().filter(|_| ptr < end)
+            {
+                ptr += instruction.size();
+                instructions.push((ptr - instruction.size(), instruction));
+            }
+        }
+        instructions
+    }
+
+    /// Whether every jump goes somewhere known and every reachable word
+    /// decodes, so that nothing can run that isn't in the graph.
+    pub fn complete(&self) -> bool {
+        self.blocks.values().all(|block| {
+            !matches!(
+                block.exit,
+                Exit::Invalid(_)
+                    | Exit::Jump(Target::Computed(_))
+                    | Exit::Branch {
+                        target: Target::Computed(_),
+                        ..
+                    }
+            )
+        })
+    }
+}
+
+/// Where control goes after a single instruction.
+fn exit(ptr: usize, instruction: &Instruction) -> Exit {
+    let next = ptr + instruction.size();
+    let (when, condition, target) = match *instruction {
+        Instruction::Halt => return Exit::Halt,
+        Instruction::JumpIfTrue(condition, target) => (true, condition, target),
+        Instruction::JumpIfFalse(condition, target) => (false, condition, target),
+        _ => return Exit::Fall(next),
+    };
+    let target = match target {
+        Parameter {
+            value,
+            mode: ParameterMode::Immediate,
+        } if value >= 0 => Target::Known(value as usize),
+        target => Target::Computed(target),
+    };
+    match condition {
+        Parameter {
+            value,
+            mode: ParameterMode::Immediate,
+        } => {
+            if (value != 0) == when {
+                Exit::Jump(target)
+            } else {
+                Exit::Fall(next)
+            }
+        }
+        condition => Exit::Branch {
+            when,
+            condition,
+            target,
+            next,
+        },
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn splits_blocks_at_jumps() {
+        // Counts down from the input, outputting each number.
+        let program = [3, 20, 4, 20, 1001, 20, -1, 20, 1005, 20, 2, 99];
+        let graph = Graph::build(&program);
+        let starts: Vec<usize> = graph.blocks.keys().copied().collect();
+        assert_eq!(starts, vec![0, 2, 11]);
+        assert_eq!(graph.blocks[&0].exit, Exit::Fall(2));
+        assert_eq!(graph.blocks[&2].end, 11);
+        assert_eq!(
+            graph.blocks[&2].exit,
+            Exit::Branch {
+                when: true,
+                condition: Parameter::position(20),
+                target: Target::Known(2),
+                next: 11
+            }
+        );
+        assert_eq!(graph.blocks[&11].exit, Exit::Halt)
+    }
+
+    #[test]
+    fn constant_conditions_are_followed_one_way() {
+        let program = [1106, 0, 4, 77, 99];
+        let graph = Graph::build(&program);
+        assert_eq!(graph.blocks[&0].exit, Exit::Jump(Target::Known(4)));
+        assert!(!graph.blocks.contains_key(&3))
+    }
+}
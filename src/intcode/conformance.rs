@@ -0,0 +1,250 @@
+use super::fuzz::Backend;
+use super::program::{LoadError, Program};
+use super::reference::{Failure, Outcome, Status};
+use failure::Fail;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+/// How many instructions a case may run before it counts as stuck. Every
+/// case is expected to stop well before this.
+const LIMIT: usize = 10_000;
+
+#[derive(Debug, Fail)]
+pub enum SuiteError {
+    #[fail(display = "Couldn't read {}: {}", file, message)]
+    Unreadable { file: String, message: String },
+
+    #[fail(display = "{}: {}", file, error)]
+    InvalidProgram { file: String, error: LoadError },
+
+    #[fail(display = "{}: unknown status {:?}", file, status)]
+    UnknownStatus { file: String, status: String },
+}
+
+/// A program along with how running it should turn out.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Case {
+    pub file: String,
+    pub program: Vec<i32>,
+    pub input: Vec<i32>,
+    pub expected: Outcome,
+}
+
+/// Turns the `status` written in a case into what a backend reports.
+pub fn status(text: &str) -> Option<Status> {
+    Some(match text {
+        "halted" => Status::Halted,
+        "awaiting input" => Status::AwaitingInput,
+        "invalid instruction" => Status::Failed(Failure::InvalidInstruction),
+        "truncated" => Status::Failed(Failure::Truncated),
+        "negative address" => Status::Failed(Failure::NegativeAddress),
+        "overflow" => Status::Failed(Failure::Overflow),
+        _ => return None,
+    })
+}
+
+impl Case {
+    /// Reads a case from a program with metadata. A case which doesn't say
+    /// how it stops should halt, and one which doesn't give its final memory
+    /// has only its output checked.
+    pub fn parse(file: &str, text: &str) -> Result<Case, SuiteError> {
+        let program = Program::parse(text).map_err(|error| SuiteError::InvalidProgram {
+            file: file.to_string(),
+            error,
+        })?;
+        let metadata = program.metadata;
+        let expected_status = match metadata.status {
+            Some(text) => status(&text).ok_or_else(|| SuiteError::UnknownStatus {
+                file: file.to_string(),
+                status: text,
+            })?,
+            None => Status::Halted,
+        };
+        Ok(Case {
+            file: file.to_string(),
+            program: program.memory,
+            input: metadata.inputs,
+            expected: Outcome {
+                status: expected_status,
+                output: metadata.outputs,
+                memory: metadata.memory,
+            },
+        })
+    }
+}
+
+/// Reads every `.int` file in `directory`, in order of name.
+pub fn load(directory: &Path) -> Result<Vec<Case>, SuiteError> {
+    let unreadable = |file: &Path, error: std::io::Error| SuiteError::Unreadable {
+        file: file.display().to_string(),
+        message: error.to_string(),
+    };
+    let mut files = vec![];
+    for entry in fs::read_dir(directory).map_err(|error| unreadable(directory, error))? {
+        let path = entry.map_err(|error| unreadable(directory, error))?.path();
+        if path.extension().and_then(|extension| extension.to_str()) == Some("int") {
+            files.push(path);
+        }
+    }
+    files.sort();
+
+    files
+        .iter()
+        .map(|path| {
+            let text = fs::read_to_string(path).map_err(|error| unreadable(path, error))?;
+            let name = path.file_name().unwrap().to_string_lossy();
+            Case::parse(&name, &text)
+        })
+        .collect()
+}
+
+/// A case which a backend got wrong.
+#[derive(Clone, Debug)]
+pub struct Mismatch {
+    pub file: String,
+    pub backend: String,
+    pub expected: Outcome,
+    pub actual: Outcome,
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{}: {} got it wrong", self.file, self.backend)?;
+        writeln!(f, "expected: {:?}", self.expected)?;
+        write!(f, "actual: {:?}", self.actual)
+    }
+}
+
+/// Runs every case on `backend`, returning those it got wrong.
+pub fn check(cases: &[Case], backend: &dyn Backend) -> Vec<Mismatch> {
+    cases
+        .iter()
+        .filter_map(|case| {
+            let actual = backend.run(&case.program, &case.input, LIMIT);
+            if case.expected.agrees(&actual, case.program.len()) {
+                return None;
+            }
+            Some(Mismatch {
+                file: case.file.clone(),
+                backend: backend.name().to_string(),
+                expected: case.expected.clone(),
+                actual,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::intcode::executor::{Standard, Tracing};
+    use crate::intcode::fuzz::{Interpreter, Optimised, Reference};
+    use crate::intcode::instruction::{Instruction, Parameter};
+    use crate::intcode::{Machine, ParameterMode};
+    use std::collections::BTreeSet;
+
+    fn suite() -> Vec<Case> {
+        let directory = concat!(env!("CARGO_MANIFEST_DIR"), "/programs/conformance");
+        load(Path::new(directory)).unwrap()
+    }
+
+    fn assert_conforms(backend: &dyn Backend) {
+        let mismatches = check(&suite(), backend);
+        let report: Vec<String> = mismatches.iter().map(|m| m.to_string()).collect();
+        assert!(mismatches.is_empty(), "\n{}", report.join("\n\n"))
+    }
+
+    #[test]
+    fn reference_conforms() {
+        assert_conforms(&Reference)
+    }
+
+    #[test]
+    fn interpreter_conforms() {
+        assert_conforms(&Interpreter)
+    }
+
+    #[test]
+    fn optimised_interpreter_conforms() {
+        assert_conforms(&Optimised)
+    }
+
+    /// The instruction's opcode and modes, as they'd be written.
+    fn head(instruction: &Instruction) -> i32 {
+        let (opcode, parameters): (i32, Vec<Parameter>) = match *instruction {
+            Instruction::Add(a, b, c) => (1, vec![a, b, c]),
+            Instruction::Multiply(a, b, c) => (2, vec![a, b, c]),
+            Instruction::Input(a) => (3, vec![a]),
+            Instruction::Output(a) => (4, vec![a]),
+            Instruction::JumpIfTrue(a, b) => (5, vec![a, b]),
+            Instruction::JumpIfFalse(a, b) => (6, vec![a, b]),
+            Instruction::LessThan(a, b, c) => (7, vec![a, b, c]),
+            Instruction::Equals(a, b, c) => (8, vec![a, b, c]),
+            Instruction::AdjustRelativeBase(a) => (9, vec![a]),
+            Instruction::Halt => (99, vec![]),
+        };
+        parameters
+            .iter()
+            .zip(&[100, 1_000, 10_000])
+            .map(|(parameter, place)| {
+                place
+                    * match parameter.mode {
+                        ParameterMode::Position => 0,
+                        ParameterMode::Immediate => 1,
+                        ParameterMode::Relative => 2,
+                    }
+            })
+            .sum::<i32>()
+            + opcode
+    }
+
+    #[test]
+    fn covers_every_opcode_and_mode() {
+        let mut run = BTreeSet::new();
+        for case in suite() {
+            let mut machine = Machine::with(case.program.clone(), Tracing::new(Standard));
+            for &value in &case.input {
+                machine.push_input(value);
+            }
+            let _ = machine.run();
+            run.extend(machine.executor().trace.iter().map(|(_, i)| head(i)));
+        }
+
+        // Parameters that are written to can't be immediate.
+        let mut expected = BTreeSet::new();
+        for &opcode in &[1, 2, 7, 8] {
+            for modes in 0..27 {
+                if modes / 9 != 1 {
+                    expected.insert(
+                        opcode + 100 * (modes % 3 + 10 * (modes / 3 % 3 + 10 * (modes / 9))),
+                    );
+                }
+            }
+        }
+        for &opcode in &[5, 6] {
+            for modes in 0..9 {
+                expected.insert(opcode + 100 * (modes % 3 + 10 * (modes / 3)));
+            }
+        }
+        expected.extend(&[3, 203, 4, 104, 204, 9, 109, 209, 99]);
+        let missing: Vec<_> = expected.difference(&run).collect();
+        assert!(missing.is_empty(), "never ran {:?}", missing)
+    }
+
+    #[test]
+    fn reads_cases() {
+        let case = Case::parse(
+            "test.int",
+            "# inputs: 4\n# outputs: 4\n# memory: 3, 5, 4, 5, 99\n# memory: 4\n3,5,4,5,99,0",
+        )
+        .unwrap();
+        assert_eq!(case.input, vec![4]);
+        assert_eq!(case.expected.status, Status::Halted);
+        assert_eq!(case.expected.memory, Some(vec![3, 5, 4, 5, 99, 4]));
+        match Case::parse("test.int", "# status: exploded\n99") {
+            Err(SuiteError::UnknownStatus { status, .. }) => assert_eq!(status, "exploded"),
+            other => panic!("expected an unknown status, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,395 @@
+use super::cfg::{Block, Exit, Graph, Target};
+use super::instruction::{Instruction, Parameter};
+use super::ParameterMode;
+use std::collections::BTreeSet;
+use std::fmt::Write;
+
+/// A loop being written out, so jumps to either end of it can become
+/// `continue` and `break`.
+#[derive(Clone, Copy)]
+struct Loop {
+    header: usize,
+    exit: Option<usize>,
+}
+
+enum Line {
+    Label(usize),
+    Code(usize, String),
+}
+
+/// Turns a control-flow graph into C-like pseudocode. Forward branches
+/// become `if` and `if`/`else`, backward ones become `loop`, and whatever
+/// doesn't fit either shape is left as a `goto`.
+struct Decompiler<'a> {
+    blocks: Vec<&'a Block>,
+    loops: Vec<Loop>,
+    lines: Vec<Line>,
+    labels: BTreeSet<usize>,
+}
+
+impl<'a> Decompiler<'a> {
+    fn index(&self, address: usize) -> Option<usize> {
+        self.blocks.iter().position(|block| block.start == address)
+    }
+
+    fn start(&self, index: usize) -> Option<usize> {
+        self.blocks.get(index).map(|block| block.start)
+    }
+
+    fn emit(&mut self, depth: usize, code: String) {
+        self.lines.push(Line::Code(depth, code));
+    }
+
+    /// The last block in `from..to` that jumps back to the block at `from`.
+    fn latch(&self, from: usize, to: usize) -> Option<usize> {
+        let header = self.blocks[from].start;
+        (from..to).rev().find(|&i| match self.blocks[i].exit {
+            Exit::Jump(Target::Known(target)) => target == header,
+            Exit::Branch {
+                target: Target::Known(target),
+                ..
+            } => target == header,
+            _ => false,
+        })
+    }
+
+    /// Whether block `k` is somewhere a region `..to` carrying on to `follow`
+    /// can skip ahead to without a `goto`.
+    fn within(&self, k: usize, to: usize, follow: Option<usize>) -> bool {
+        k < to || (k == to && self.start(k) == follow)
+    }
+
+    /// What to write for control passing to `target`, when it would otherwise
+    /// carry on to `follow`.
+    fn transfer(&mut self, target: usize, follow: Option<usize>) -> Option<String> {
+        if Some(target) == follow {
+            return None;
+        }
+        if let Some(innermost) = self.loops.last() {
+            if innermost.header == target {
+                return Some("continue;".to_string());
+            }
+            if innermost.exit == Some(target) {
+                return Some("break;".to_string());
+            }
+        }
+        if self.index(target).is_none() {
+            // Jumping off the end of the program stops it.
+            return Some("halt;".to_string());
+        }
+        self.labels.insert(target);
+        Some(format!("goto L{};", target))
+    }
+
+    /// Writes out blocks `from..to`, which carry on to `follow` afterwards.
+    fn region(&mut self, from: usize, to: usize, follow: Option<usize>, depth: usize) {
+        let mut i = from;
+        while i < to {
+            let in_loop = self.loops.last().map(|l| l.header) == Some(self.blocks[i].start);
+            if !in_loop {
+                if let Some(latch) = self.latch(i, to) {
+                    let header = self.blocks[i].start;
+                    let exit = if latch + 1 < to {
+                        self.start(latch + 1)
+                    } else {
+                        follow
+                    };
+                    self.loops.push(Loop { header, exit });
+                    self.emit(depth, "loop {".to_string());
+                    self.region(i, latch + 1, Some(header), depth + 1);
+                    self.emit(depth, "}".to_string());
+                    self.loops.pop();
+                    i = latch + 1;
+                    continue;
+                }
+            }
+
+            let block = self.blocks[i];
+            let fall = if i + 1 < to {
+                self.start(i + 1)
+            } else {
+                follow
+            };
+            self.lines.push(Line::Label(block.start));
+            for (_, instruction) in &block.instructions {
+                if let Some(statement) = statement(instruction) {
+                    self.emit(depth, statement);
+                }
+            }
+
+            match block.exit {
+                Exit::Halt => self.emit(depth, "halt;".to_string()),
+                Exit::Invalid(ptr) => self.emit(depth, format!("invalid({});", ptr)),
+                Exit::Fall(next) | Exit::Jump(Target::Known(next)) => {
+                    if let Some(transfer) = self.transfer(next, fall) {
+                        self.emit(depth, transfer);
+                    }
+                }
+                Exit::Jump(Target::Computed(target)) => {
+                    self.emit(depth, format!("goto *{};", operand(target)))
+                }
+                Exit::Branch {
+                    when,
+                    condition,
+                    target: Target::Known(target),
+                    next,
+                } => {
+                    if let Some(j) = self
+                        .index(target)
+                        .filter(|&j| j > i + 1 && self.within(j, to, follow))
+                    {
+                        i = self.conditional(i, j, to, follow, (when, condition), depth);
+                        continue;
+                    }
+
+                    let taken = self.transfer(target, fall);
+                    let not_taken = self.transfer(next, fall);
+                    match (taken, not_taken) {
+                        (None, None) => {}
+                        (Some(taken), None) => self.guard(depth, test(condition, when), taken),
+                        (None, Some(not_taken)) => {
+                            self.guard(depth, test(condition, !when), not_taken)
+                        }
+                        (Some(taken), Some(not_taken)) => {
+                            self.guard(depth, test(condition, when), taken);
+                            self.emit(depth, not_taken);
+                        }
+                    }
+                }
+                Exit::Branch {
+                    when,
+                    condition,
+                    target: Target::Computed(target),
+                    next,
+                } => {
+                    self.guard(
+                        depth,
+                        test(condition, when),
+                        format!("goto *{};", operand(target)),
+                    );
+                    if let Some(transfer) = self.transfer(next, fall) {
+                        self.emit(depth, transfer);
+                    }
+                }
+            }
+            i += 1;
+        }
+    }
+
+    /// Writes out a branch at block `i` which skips forward to block `j`,
+    /// returning the block to carry on from.
+    fn conditional(
+        &mut self,
+        i: usize,
+        j: usize,
+        to: usize,
+        follow: Option<usize>,
+        (when, condition): (bool, Parameter),
+        depth: usize,
+    ) -> usize {
+        let target = self.blocks[j].start;
+        self.emit(depth, format!("if ({}) {{", test(condition, !when)));
+
+        // The block before the target jumping further forward is the end of
+        // the then branch, with an else branch following.
+        let join = match self.blocks[j - 1].exit {
+            Exit::Jump(Target::Known(join)) if j - 1 > i => self
+                .index(join)
+                .filter(|&k| k > j && self.within(k, to, follow))
+                .map(|k| (join, k)),
+            _ => None,
+        };
+
+        match join {
+            Some((join, k)) => {
+                self.region(i + 1, j, Some(join), depth + 1);
+                self.emit(depth, "} else {".to_string());
+                self.region(j, k, Some(join), depth + 1);
+                self.emit(depth, "}".to_string());
+                k
+            }
+            None => {
+                self.region(i + 1, j, Some(target), depth + 1);
+                self.emit(depth, "}".to_string());
+                j
+            }
+        }
+    }
+
+    fn guard(&mut self, depth: usize, test: String, body: String) {
+        self.emit(depth, format!("if ({}) {{", test));
+        self.emit(depth + 1, body);
+        self.emit(depth, "}".to_string());
+    }
+
+    fn render(&self) -> String {
+        let mut text = String::new();
+        for line in &self.lines {
+            match line {
+                Line::Label(address) if self.labels.contains(address) => {
+                    writeln!(text, "L{}:", address).unwrap()
+                }
+                Line::Label(_) => {}
+                Line::Code(depth, code) => {
+                    writeln!(text, "{}{}", "    ".repeat(*depth), code).unwrap()
+                }
+            }
+        }
+        text
+    }
+}
+
+/// Memory cells are named after their address, so `v225` is whatever is at
+/// address 225, and `rb[+3]` is three past the relative base.
+fn operand(operand: Parameter) -> String {
+    let value = operand.value;
+    match operand.mode {
+        ParameterMode::Immediate => value.to_string(),
+        ParameterMode::Position => format!("v{}", value),
+        ParameterMode::Relative => format!("rb[{:+}]", value),
+    }
+}
+
+fn test(condition: Parameter, non_zero: bool) -> String {
+    format!(
+        "{} {} 0",
+        operand(condition),
+        if non_zero { "!=" } else { "==" }
+    )
+}
+
+fn statement(instruction: &Instruction) -> Option<String> {
+    let statement = match *instruction {
+        Instruction::Add(
+            a,
+            Parameter {
+                value,
+                mode: ParameterMode::Immediate,
+            },
+            c,
+        ) if value < 0 => format!("{} = {} - {};", operand(c), operand(a), -(value as i64)),
+        Instruction::Add(a, b, c) => {
+            format!("{} = {} + {};", operand(c), operand(a), operand(b))
+        }
+        Instruction::Multiply(a, b, c) => {
+            format!("{} = {} * {};", operand(c), operand(a), operand(b))
+        }
+        Instruction::Input(a) => format!("{} = input();", operand(a)),
+        Instruction::Output(a) => format!("output({});", operand(a)),
+        Instruction::LessThan(a, b, c) => {
+            format!("{} = {} < {};", operand(c), operand(a), operand(b))
+        }
+        Instruction::Equals(a, b, c) => {
+            format!("{} = {} == {};", operand(c), operand(a), operand(b))
+        }
+        Instruction::AdjustRelativeBase(a) => format!("rb += {};", operand(a)),
+        // Jumps that are never taken.
+        _ => return None,
+    };
+
+    Some(statement)
+}
+
+pub fn decompile(program: &[i32]) -> String {
+    let graph = Graph::build(program);
+    let mut decompiler = Decompiler {
+        blocks: graph.blocks.values().collect(),
+        loops: vec![],
+        lines: vec![],
+        labels: BTreeSet::new(),
+    };
+    let count = decompiler.blocks.len();
+    decompiler.region(0, count, None, 0);
+    decompiler.render()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::day5;
+
+    #[test]
+    fn straight_line_code() {
+        let program = [3, 9, 1001, 9, -1, 9, 4, 9, 99, 0];
+        assert_eq!(
+            decompile(&program),
+            "v9 = input();\n\
+             v9 = v9 - 1;\n\
+             output(v9);\n\
+             halt;\n"
+        )
+    }
+
+    #[test]
+    fn if_else() {
+        let mut program = vec![3, 20, 1005, 20, 10, 104, 0, 1105, 1, 12, 104, 1, 99];
+        program.resize(21, 0);
+        assert_eq!(
+            decompile(&program),
+            "v20 = input();\n\
+             if (v20 == 0) {\n    \
+                 output(0);\n\
+             } else {\n    \
+                 output(1);\n\
+             }\n\
+             halt;\n"
+        )
+    }
+
+    #[test]
+    fn loops() {
+        let mut program = vec![3, 20, 4, 20, 1001, 20, -1, 20, 1005, 20, 2, 99];
+        program.resize(21, 0);
+        assert_eq!(
+            decompile(&program),
+            "v20 = input();\n\
+             loop {\n    \
+                 output(v20);\n    \
+                 v20 = v20 - 1;\n    \
+                 if (v20 == 0) {\n        \
+                     break;\n    \
+                 }\n\
+             }\n\
+             halt;\n"
+        )
+    }
+
+    #[test]
+    fn computed_jumps_are_left_as_gotos() {
+        let program = [3, 6, 6, 6, 6, 99, 0];
+        assert_eq!(
+            decompile(&program),
+            "v6 = input();\n\
+             if (v6 == 0) {\n    \
+                 goto *v6;\n\
+             }\n\
+             halt;\n"
+        )
+    }
+
+    #[test]
+    fn jumps_out_of_the_program_halt() {
+        let program = [3, 9, 1005, 9, 1000, 104, 1, 99, 0, 0];
+        assert_eq!(
+            decompile(&program),
+            "v9 = input();\n\
+             if (v9 != 0) {\n    \
+                 halt;\n\
+             }\n\
+             output(1);\n\
+             halt;\n"
+        )
+    }
+
+    #[test]
+    fn day5_decompiles() {
+        let mut program = day5::converter(include_str!("../../input/2019/day5.txt").trim());
+        // The program rewrites its third instruction based on the input.
+        assert!(decompile(&program).contains("invalid(6);"));
+
+        program[6] = 1101;
+        let pseudocode = decompile(&program);
+        assert!(pseudocode.starts_with("v225 = input();\n"));
+        assert!(pseudocode.trim_end().ends_with("halt;"))
+    }
+}
@@ -0,0 +1,438 @@
+use super::cfg::Graph;
+use super::instruction::Instruction;
+use super::ParameterMode;
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::mem;
+use std::ops::Range;
+
+/// A word of a program as the diff sees it.
+#[derive(Clone, Debug, PartialEq)]
+enum Item {
+    Instruction(usize, Instruction),
+    Data(usize, i32),
+}
+
+impl Item {
+    fn address(&self) -> usize {
+        match *self {
+            Item::Instruction(address, _) | Item::Data(address, _) => address,
+        }
+    }
+
+    fn end(&self) -> usize {
+        match self {
+            Item::Instruction(address, instruction) => address + instruction.size(),
+            Item::Data(address, _) => address + 1,
+        }
+    }
+
+    /// Whether two items can be lined up: instructions with the same opcode
+    /// and modes, whatever their parameters, or any two data words.
+    fn matches(&self, other: &Item) -> bool {
+        match (self, other) {
+            (Item::Instruction(_, a), Item::Instruction(_, b)) => {
+                a.opcode() == b.opcode()
+                    && (a.parameters().iter())
+                        .zip(b.parameters())
+                        .all(|(p, q)| p.mode == q.mode)
+            }
+            (Item::Data(..), Item::Data(..)) => true,
+            _ => false,
+        }
+    }
+}
+
+/// Splits a program into instructions and data. Where every jump can be
+/// followed, only reachable instructions count as code; otherwise anything
+/// that decodes is taken to be an instruction.
+fn items(program: &[i32]) -> Vec<Item> {
+    let graph = Graph::build(program);
+    let mut code: BTreeMap<usize, Instruction> = BTreeMap::new();
+    if graph.complete() {
+        code.extend(graph.instructions(program));
+    }
+
+    let mut items = vec![];
+    let mut ptr = 0;
+    while ptr < program.len() {
+        let instruction = if graph.complete() {
+            code.remove(&ptr)
+        } else {
+            Instruction::decode(program, ptr).ok()
+        };
+        let item = match instruction {
+            Some(instruction) => Item::Instruction(ptr, instruction),
+            None => Item::Data(ptr, program[ptr]),
+        };
+        ptr = item.end();
+        items.push(item);
+    }
+    items
+}
+
+/// The indices of the items in `a` and `b` which line up, in order, found
+/// with Hirschberg's algorithm so that it only needs linear space.
+fn align(a: &[Item], b: &[Item], offset: (usize, usize), pairs: &mut Vec<(usize, usize)>) {
+    if a.is_empty() || b.is_empty() {
+        return;
+    }
+    if a.len() == 1 {
+        if let Some(j) = b.iter().position(|item| a[0].matches(item)) {
+            pairs.push((offset.0, offset.1 + j));
+        }
+        return;
+    }
+
+    let middle = a.len() / 2;
+    let (top, bottom) = a.split_at(middle);
+    let forward = lengths(top.len(), b.len(), |i, j| top[i].matches(&b[j]));
+    let backward = lengths(bottom.len(), b.len(), |i, j| {
+        bottom[bottom.len() - 1 - i].matches(&b[b.len() - 1 - j])
+    });
+    let split = (0..=b.len())
+        .max_by_key(|&j| (forward[j] + backward[b.len() - j], Reverse(j)))
+        .unwrap();
+
+    align(top, &b[..split], offset, pairs);
+    align(
+        bottom,
+        &b[split..],
+        (offset.0 + middle, offset.1 + split),
+        pairs,
+    );
+}
+
+/// The last row of the usual longest common subsequence table: how many
+/// items line up between all `n` of one sequence and the first `j` of
+/// another `m` long, for each `j`.
+fn lengths<F: Fn(usize, usize) -> bool>(n: usize, m: usize, matches: F) -> Vec<usize> {
+    let mut previous = vec![0; m + 1];
+    let mut current = vec![0; m + 1];
+    for i in 0..n {
+        for j in 0..m {
+            current[j + 1] = if matches(i, j) {
+                previous[j] + 1
+            } else {
+                previous[j + 1].max(current[j])
+            };
+        }
+        mem::swap(&mut previous, &mut current);
+    }
+    previous
+}
+
+/// One way two programs differ.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Change {
+    /// The same instruction in both with a different parameter: a constant,
+    /// or an address if it isn't immediate.
+    Constant {
+        left: usize,
+        right: usize,
+        values: (i32, i32),
+    },
+    /// The same instruction in both, reading or writing words that line up
+    /// with each other but have moved.
+    Relocation {
+        left: usize,
+        right: usize,
+        values: (i32, i32),
+    },
+    /// Instructions or data in one program with nothing to line up with in
+    /// the other. Either range may be empty.
+    Code {
+        left: Range<usize>,
+        right: Range<usize>,
+    },
+    /// A data word that's there in both, holding different values.
+    Data {
+        left: usize,
+        right: usize,
+        values: (i32, i32),
+    },
+}
+
+/// The differences between two programs, lined up by instruction so that
+/// code which has moved still matches.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Diff {
+    pub changes: Vec<Change>,
+}
+
+impl Diff {
+    /// Lines up the two programs' instructions with a longest common
+    /// subsequence, then compares what lined up. Whatever the two start and
+    /// end with in common is lined up first, leaving only the middle to
+    /// search.
+    pub fn new(left: &[i32], right: &[i32]) -> Diff {
+        let (a, b) = (items(left), items(right));
+        let prefix = (a.iter()).zip(&b).take_while(|(x, y)| x.matches(y)).count();
+        let suffix = (a[prefix..].iter().rev())
+            .zip(b[prefix..].iter().rev())
+            .take_while(|(x, y)| x.matches(y))
+            .count();
+
+        let mut pairs: Vec<(usize, usize)> = (0..prefix).map(|i| (i, i)).collect();
+        align(
+            &a[prefix..a.len() - suffix],
+            &b[prefix..b.len() - suffix],
+            (prefix, prefix),
+            &mut pairs,
+        );
+        pairs.extend((1..=suffix).rev().map(|n| (a.len() - n, b.len() - n)));
+
+        // Where each word of the left program ended up in the right, for the
+        // items that line up.
+        let mut moved = BTreeMap::new();
+        for &(i, j) in &pairs {
+            for n in 0..a[i].end() - a[i].address() {
+                moved.insert(a[i].address() + n, b[j].address() + n);
+            }
+        }
+
+        let address = |items: &[Item], n: usize, program: &[i32]| {
+            items.get(n).map_or(program.len(), Item::address)
+        };
+        let mut changes = vec![];
+        let (mut i, mut j) = (0, 0);
+        for &(next_i, next_j) in pairs.iter().chain(Some(&(a.len(), b.len()))) {
+            if next_i > i || next_j > j {
+                changes.push(Change::Code {
+                    left: address(&a, i, left)..address(&a, next_i, left),
+                    right: address(&b, j, right)..address(&b, next_j, right),
+                });
+            }
+            if next_i == a.len() {
+                break;
+            }
+
+            match (&a[next_i], &b[next_j]) {
+                (Item::Instruction(l, x), Item::Instruction(r, y)) => {
+                    let parameters = x.parameters().into_iter().zip(y.parameters());
+                    for (n, (p, q)) in parameters.enumerate() {
+                        if p.value == q.value {
+                            continue;
+                        }
+                        let (left, right, values) = (l + 1 + n, r + 1 + n, (p.value, q.value));
+                        let relocated = p.mode == ParameterMode::Position
+                            && p.value >= 0
+                            && q.value >= 0
+                            && moved.get(&(p.value as usize)) == Some(&(q.value as usize));
+                        changes.push(if relocated {
+                            Change::Relocation {
+                                left,
+                                right,
+                                values,
+                            }
+                        } else {
+                            Change::Constant {
+                                left,
+                                right,
+                                values,
+                            }
+                        });
+                    }
+                }
+                (&Item::Data(l, p), &Item::Data(r, q)) if p != q => changes.push(Change::Data {
+                    left: l,
+                    right: r,
+                    values: (p, q),
+                }),
+                _ => {}
+            }
+            i = next_i + 1;
+            j = next_j + 1;
+        }
+
+        Diff { changes }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// The data words which hold different values, as addresses in each
+    /// program.
+    pub fn varying_data(&self) -> Vec<(usize, usize)> {
+        self.changes
+            .iter()
+            .filter_map(|change| match *change {
+                Change::Data { left, right, .. } => Some((left, right)),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+/// One change per line, with addresses in the left program first.
+impl fmt::Display for Diff {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let range = |range: &Range<usize>| {
+            if range.is_empty() {
+                format!("nothing at {}", range.start)
+            } else {
+                format!("{}..{}", range.start, range.end)
+            }
+        };
+        for change in &self.changes {
+            match change {
+                Change::Constant {
+                    left,
+                    right,
+                    values,
+                } => writeln!(
+                    f,
+                    "constant {} / {}: {} vs {}",
+                    left, right, values.0, values.1
+                )?,
+                Change::Relocation {
+                    left,
+                    right,
+                    values,
+                } => writeln!(
+                    f,
+                    "moved    {} / {}: {} vs {}",
+                    left, right, values.0, values.1
+                )?,
+                Change::Code { left, right } => {
+                    writeln!(f, "code     {} vs {}", range(left), range(right))?
+                }
+                Change::Data {
+                    left,
+                    right,
+                    values,
+                } => writeln!(
+                    f,
+                    "data     {} / {}: {} vs {}",
+                    left, right, values.0, values.1
+                )?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{day2, day5};
+
+    #[test]
+    fn identical_programs_do_not_differ() {
+        let program = day5::converter(include_str!("../../input/2019/day5.txt"));
+        assert!(Diff::new(&program, &program).is_empty())
+    }
+
+    #[test]
+    fn tells_constants_from_code_and_data() {
+        // Outputs the sum of two data words, then a constant.
+        let left = [1, 9, 10, 11, 4, 11, 104, 5, 99, 3, 4, 0];
+        // The same with an extra output at the start, a different constant
+        // and a different data word. The addresses of the data words move
+        // along with them.
+        let right = [104, 0, 1, 11, 12, 13, 4, 13, 104, 6, 99, 3, 7, 0];
+        let diff = Diff::new(&left, &right);
+        assert_eq!(
+            diff.changes,
+            vec![
+                Change::Code {
+                    left: 0..0,
+                    right: 0..2
+                },
+                Change::Relocation {
+                    left: 1,
+                    right: 3,
+                    values: (9, 11)
+                },
+                Change::Relocation {
+                    left: 2,
+                    right: 4,
+                    values: (10, 12)
+                },
+                Change::Relocation {
+                    left: 3,
+                    right: 5,
+                    values: (11, 13)
+                },
+                Change::Relocation {
+                    left: 5,
+                    right: 7,
+                    values: (11, 13)
+                },
+                Change::Constant {
+                    left: 7,
+                    right: 9,
+                    values: (5, 6)
+                },
+                Change::Data {
+                    left: 10,
+                    right: 12,
+                    values: (4, 7)
+                },
+            ]
+        );
+        assert_eq!(diff.varying_data(), vec![(10, 12)]);
+        assert_eq!(
+            diff.to_string().lines().next(),
+            Some("code     nothing at 0 vs 0..2")
+        )
+    }
+
+    #[test]
+    fn finds_patched_puzzle_input() {
+        let left = day2::converter(include_str!("../../input/2019/day2.txt").trim());
+        let mut right = left.clone();
+        right[1] = 12;
+        right[2] = 2;
+        let last = right.len() - 1;
+        right[last] += 1;
+        let diff = Diff::new(&left, &right);
+        let data: Vec<usize> = diff.varying_data().iter().map(|&(l, _)| l).collect();
+        assert!(data.contains(&last), "{}", diff);
+        assert!(diff
+            .changes
+            .iter()
+            .all(|change| !matches!(change, Change::Code { .. })))
+    }
+
+    #[test]
+    fn lines_up_as_much_as_a_full_table() {
+        // Strings of outputs in two modes, which never line up with each
+        // other, and adds.
+        let program = |seed: u32, length: usize| {
+            let mut state = seed;
+            let mut program = vec![];
+            for _ in 0..length {
+                state = state.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+                program.extend(match (state >> 16) % 3 {
+                    0 => vec![104, 1],
+                    1 => vec![4, 1],
+                    _ => vec![1101, 1, 1, 1],
+                });
+            }
+            program.push(99);
+            program
+        };
+        for seed in 0..20 {
+            let (a, b) = (items(&program(seed, 30)), items(&program(seed + 100, 25)));
+            let mut pairs = vec![];
+            align(&a, &b, (0, 0), &mut pairs);
+            assert!(pairs.windows(2).all(|w| w[0].0 < w[1].0 && w[0].1 < w[1].1));
+            assert!(pairs.iter().all(|&(i, j)| a[i].matches(&b[j])));
+
+            let mut common = vec![vec![0; b.len() + 1]; a.len() + 1];
+            for i in 0..a.len() {
+                for j in 0..b.len() {
+                    common[i + 1][j + 1] = if a[i].matches(&b[j]) {
+                        common[i][j] + 1
+                    } else {
+                        common[i][j + 1].max(common[i + 1][j])
+                    };
+                }
+            }
+            assert_eq!(pairs.len(), common[a.len()][b.len()]);
+        }
+    }
+}
@@ -0,0 +1,158 @@
+use super::instruction::{Instruction, Parameter};
+use super::space::AddressSpace;
+use super::{IntCodeError, ParameterMode, State};
+
+/// Everything about a running program that isn't in memory.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Registers {
+    pub ptr: usize,
+    pub relative_base: i32,
+}
+
+impl Registers {
+    /// The address a parameter refers to.
+    pub fn address(&self, parameter: Parameter) -> Result<usize, IntCodeError> {
+        let address = match parameter.mode {
+            ParameterMode::Relative => parameter
+                .value
+                .checked_add(self.relative_base)
+                .ok_or(IntCodeError::Overflow { pc: self.ptr })?,
+            _ => parameter.value,
+        };
+        if address < 0 {
+            return Err(IntCodeError::NegativeAddressError { address });
+        }
+        Ok(address as usize)
+    }
+
+    pub fn read<M: AddressSpace + ?Sized>(
+        &self,
+        memory: &mut M,
+        parameter: Parameter,
+    ) -> Result<i32, IntCodeError> {
+        match parameter.mode {
+            ParameterMode::Immediate => Ok(parameter.value),
+            _ => memory.read(self.address(parameter)?),
+        }
+    }
+}
+
+/// Where input comes from and output goes.
+pub trait Io {
+    /// The next input, or `None` if there isn't any yet.
+    fn input(&mut self) -> Option<i32>;
+
+    fn output(&mut self, value: i32);
+}
+
+/// Carries out decoded instructions.
+pub trait Executor {
+    /// Runs `instruction`, which was decoded at `registers.ptr`, and moves the
+    /// pointer on. Returns `State::AwaitingInput`, leaving everything as it
+    /// was, if the instruction needs input and `io` has none.
+    fn execute<M: AddressSpace + ?Sized>(
+        &mut self,
+        registers: &mut Registers,
+        memory: &mut M,
+        instruction: Instruction,
+        io: &mut dyn Io,
+    ) -> Result<State, IntCodeError>;
+}
+
+/// The instructions as the puzzles describe them.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Standard;
+
+impl Executor for Standard {
+    fn execute<M: AddressSpace + ?Sized>(
+        &mut self,
+        registers: &mut Registers,
+        memory: &mut M,
+        instruction: Instruction,
+        io: &mut dyn Io,
+    ) -> Result<State, IntCodeError> {
+        let next = registers.ptr + instruction.size();
+        match instruction {
+            Instruction::Add(a, b, to)
+            | Instruction::Multiply(a, b, to)
+            | Instruction::LessThan(a, b, to)
+            | Instruction::Equals(a, b, to) => {
+                let a = registers.read(memory, a)?;
+                let b = registers.read(memory, b)?;
+                let value = match instruction {
+                    Instruction::Add(..) => a.checked_add(b),
+                    Instruction::Multiply(..) => a.checked_mul(b),
+                    Instruction::LessThan(..) => Some((a < b) as i32),
+                    _ => Some((a == b) as i32),
+                };
+                let value = value.ok_or(IntCodeError::Overflow { pc: registers.ptr })?;
+                memory.write(registers.address(to)?, value)?;
+            }
+            Instruction::Input(to) => {
+                let address = registers.address(to)?;
+                match io.input() {
+                    Some(value) => memory.write(address, value)?,
+                    None => return Ok(State::AwaitingInput),
+                }
+            }
+            Instruction::Output(value) => io.output(registers.read(memory, value)?),
+            Instruction::JumpIfTrue(condition, target)
+            | Instruction::JumpIfFalse(condition, target) => {
+                let condition = registers.read(memory, condition)?;
+                let target = registers.read(memory, target)?;
+                let jump_if = matches!(instruction, Instruction::JumpIfTrue(..));
+                if (condition != 0) == jump_if {
+                    if target < 0 {
+                        return Err(IntCodeError::NegativeAddressError { address: target });
+                    }
+                    registers.ptr = target as usize;
+                    return Ok(State::Running);
+                }
+            }
+            Instruction::AdjustRelativeBase(by) => {
+                let by = registers.read(memory, by)?;
+                let base = registers.relative_base.checked_add(by);
+                registers.relative_base =
+                    base.ok_or(IntCodeError::Overflow { pc: registers.ptr })?;
+            }
+            Instruction::Halt => return Ok(State::Halted),
+        }
+
+        registers.ptr = next;
+        Ok(State::Running)
+    }
+}
+
+/// Wraps another executor, keeping every instruction it runs along with the
+/// address it ran at.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Tracing<E> {
+    pub executor: E,
+    pub trace: Vec<(usize, Instruction)>,
+}
+
+impl<E: Executor> Tracing<E> {
+    pub fn new(executor: E) -> Tracing<E> {
+        Tracing {
+            executor,
+            trace: vec![],
+        }
+    }
+}
+
+impl<E: Executor> Executor for Tracing<E> {
+    fn execute<M: AddressSpace + ?Sized>(
+        &mut self,
+        registers: &mut Registers,
+        memory: &mut M,
+        instruction: Instruction,
+        io: &mut dyn Io,
+    ) -> Result<State, IntCodeError> {
+        let ptr = registers.ptr;
+        let state = self.executor.execute(registers, memory, instruction, io)?;
+        if state != State::AwaitingInput {
+            self.trace.push((ptr, instruction));
+        }
+        Ok(state)
+    }
+}
@@ -0,0 +1,443 @@
+use super::optimise::Optimiser;
+use super::reference::{self, Failure, Outcome, Status};
+use super::{IntCodeError, Machine, State};
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
+
+/// A small xorshift generator, so runs can be repeated from a seed without
+/// pulling in a dependency.
+#[derive(Clone, Debug)]
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Rng {
+        Rng(seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1)
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    pub fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+
+    pub fn between(&mut self, low: i32, high: i32) -> i32 {
+        low + self.below((high - low + 1) as usize) as i32
+    }
+}
+
+/// Makes a random program out of `instructions` valid instructions, a halt,
+/// and a few cells of data, along with some input for it. Every parameter
+/// points somewhere inside the program, and every jump lands on an
+/// instruction, though writes can still change all of that as it runs.
+pub fn generate(rng: &mut Rng, instructions: usize) -> (Vec<i32>, Vec<i32>) {
+    const OPCODES: [i32; 11] = [1, 1, 2, 3, 4, 4, 5, 6, 7, 8, 9];
+    const DATA: usize = 8;
+
+    let opcodes: Vec<i32> = (0..instructions)
+        .map(|_| OPCODES[rng.below(OPCODES.len())])
+        .chain(Some(99))
+        .collect();
+    let mut starts = vec![];
+    let mut length = 0;
+    for &opcode in &opcodes {
+        starts.push(length as i32);
+        length += match opcode {
+            1 | 2 | 7 | 8 => 4,
+            5 | 6 => 3,
+            3 | 4 | 9 => 2,
+            _ => 1,
+        };
+    }
+    let length = length + DATA;
+
+    let mut program = vec![];
+    for &opcode in &opcodes {
+        let (reads, writes) = match opcode {
+            1 | 2 | 7 | 8 => (2, 1),
+            3 => (0, 1),
+            4 | 9 => (1, 0),
+            5 | 6 => (2, 0),
+            _ => (0, 0),
+        };
+        let mut modes = vec![];
+        let mut parameters = vec![];
+        for n in 0..reads + writes {
+            let jump_target = (opcode == 5 || opcode == 6) && n == 1;
+            let mode = match rng.below(10) {
+                _ if jump_target => 1,
+                0..=3 if n < reads => 1,
+                4 => 2,
+                _ => 0,
+            };
+            parameters.push(match mode {
+                _ if jump_target => starts[rng.below(starts.len())],
+                0 => rng.below(length) as i32,
+                1 if opcode == 9 => rng.between(-2, 5),
+                1 => rng.between(-5, 20),
+                _ => rng.between(-3, 8),
+            });
+            modes.push(mode);
+        }
+
+        let head = modes
+            .iter()
+            .zip(&[100, 1_000, 10_000])
+            .fold(opcode, |head, (mode, place)| head + mode * place);
+        program.push(head);
+        program.extend(parameters);
+    }
+    program.extend((0..DATA).map(|_| rng.between(-5, 20)));
+
+    let input = (0..rng.below(4)).map(|_| rng.between(-5, 20)).collect();
+    (program, input)
+}
+
+/// Something that can run a program so it can be compared to the reference.
+pub trait Backend {
+    fn name(&self) -> &str;
+
+    fn run(&self, program: &[i32], input: &[i32], limit: usize) -> Outcome;
+
+    /// Whether it runs exactly the same instructions as the reference, so
+    /// that stopping at a step limit leaves both in the same place.
+    fn step_exact(&self) -> bool {
+        true
+    }
+}
+
+fn failure(error: IntCodeError) -> Failure {
+    match error {
+        IntCodeError::UnknownOpcodeError { .. }
+        | IntCodeError::UnrecognisedParameterMode { .. } => Failure::InvalidInstruction,
+        IntCodeError::UnexpectedEndOfInputError {} => Failure::Truncated,
+        IntCodeError::NegativeAddressError { .. } => Failure::NegativeAddress,
+        IntCodeError::Overflow { .. } => Failure::Overflow,
+        _ => Failure::Other,
+    }
+}
+
+fn run_machine(program: &[i32], input: &[i32], limit: usize) -> (Status, Machine) {
+    let mut machine = Machine::new(program);
+    for &value in input {
+        machine.push_input(value);
+    }
+
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        for _ in 0..limit {
+            match machine.step() {
+                Ok(State::Running) => continue,
+                Ok(State::Halted) => return Status::Halted,
+                Ok(State::AwaitingInput) => return Status::AwaitingInput,
+                Err(error) => return Status::Failed(failure(error)),
+            }
+        }
+        Status::StepLimit
+    }));
+
+    (result.unwrap_or(Status::Failed(Failure::Panicked)), machine)
+}
+
+/// The `Machine` used by every day's puzzle.
+pub struct Interpreter;
+
+impl Backend for Interpreter {
+    fn name(&self) -> &str {
+        "interpreter"
+    }
+
+    fn run(&self, program: &[i32], input: &[i32], limit: usize) -> Outcome {
+        let (status, mut machine) = run_machine(program, input, limit);
+        Outcome {
+            status,
+            output: machine.take_output(),
+            memory: Some(machine.memory().to_vec()),
+        }
+    }
+}
+
+/// The `Machine` running whatever the optimiser makes of the program. The
+/// optimiser rewrites code, so memory isn't compared.
+pub struct Optimised;
+
+impl Backend for Optimised {
+    fn name(&self) -> &str {
+        "optimised"
+    }
+
+    fn run(&self, program: &[i32], input: &[i32], limit: usize) -> Outcome {
+        let optimised = Optimiser::new().optimise(program);
+        let (status, mut machine) = run_machine(&optimised.program, input, limit);
+        Outcome {
+            status,
+            output: machine.take_output(),
+            memory: None,
+        }
+    }
+
+    fn step_exact(&self) -> bool {
+        false
+    }
+}
+
+/// The reference interpreter itself, for checking it against expectations
+/// written down elsewhere.
+pub struct Reference;
+
+impl Backend for Reference {
+    fn name(&self) -> &str {
+        "reference"
+    }
+
+    fn run(&self, program: &[i32], input: &[i32], limit: usize) -> Outcome {
+        reference::run(program, input, limit)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Divergence {
+    pub backend: String,
+    pub program: Vec<i32>,
+    pub input: Vec<i32>,
+    pub expected: Outcome,
+    pub actual: Outcome,
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let join = |values: &[i32]| {
+            values
+                .iter()
+                .map(|v| v.to_string())
+                .collect::<Vec<_>>()
+                .join(",")
+        };
+        writeln!(f, "{} disagrees with the reference", self.backend)?;
+        writeln!(f, "program: {}", join(&self.program))?;
+        writeln!(f, "input: {}", join(&self.input))?;
+        writeln!(f, "expected: {:?}", self.expected)?;
+        write!(f, "actual: {:?}", self.actual)
+    }
+}
+
+/// Runs random programs on several backends and the reference interpreter,
+/// and shrinks any program they disagree about.
+pub struct Fuzzer {
+    backends: Vec<Box<dyn Backend>>,
+    limit: usize,
+    instructions: usize,
+}
+
+impl Default for Fuzzer {
+    fn default() -> Fuzzer {
+        Fuzzer {
+            backends: vec![Box::new(Interpreter), Box::new(Optimised)],
+            limit: 500,
+            instructions: 12,
+        }
+    }
+}
+
+impl Fuzzer {
+    pub fn new() -> Fuzzer {
+        Fuzzer::default()
+    }
+
+    pub fn backend(mut self, backend: Box<dyn Backend>) -> Fuzzer {
+        self.backends.push(backend);
+        self
+    }
+
+    pub fn limit(mut self, limit: usize) -> Fuzzer {
+        self.limit = limit;
+        self
+    }
+
+    pub fn instructions(mut self, instructions: usize) -> Fuzzer {
+        self.instructions = instructions;
+        self
+    }
+
+    /// The first backend to disagree with the reference about this program.
+    /// Programs which need more memory than the reference allows aren't
+    /// compared, as the backends would go on to allocate it.
+    pub fn check(&self, program: &[i32], input: &[i32]) -> Option<Divergence> {
+        let expected = reference::run(program, input, self.limit);
+        if expected.status == Status::MemoryLimit {
+            return None;
+        }
+        for backend in &self.backends {
+            if expected.status == Status::StepLimit && !backend.step_exact() {
+                continue;
+            }
+            let actual = backend.run(program, input, self.limit);
+            if !expected.agrees(&actual, program.len()) {
+                return Some(Divergence {
+                    backend: backend.name().to_string(),
+                    program: program.to_vec(),
+                    input: input.to_vec(),
+                    expected,
+                    actual,
+                });
+            }
+        }
+
+        None
+    }
+
+    /// Tries `cases` programs generated from `seed`, returning the first
+    /// disagreement, shrunk.
+    pub fn run(&self, seed: u64, cases: usize) -> Result<(), Box<Divergence>> {
+        let mut rng = Rng::new(seed);
+        for _ in 0..cases {
+            let instructions = 1 + rng.below(self.instructions);
+            let (program, input) = generate(&mut rng, instructions);
+            if let Some(divergence) = self.check(&program, &input) {
+                return Err(Box::new(self.shrink(divergence)));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Makes a disagreement as small as possible by repeatedly dropping input,
+    /// cutting the program short, dropping words from it and simplifying
+    /// them, keeping any change that leaves the same backend disagreeing.
+    pub fn shrink(&self, mut divergence: Divergence) -> Divergence {
+        loop {
+            let smaller = candidates(&divergence.program, &divergence.input)
+                .into_iter()
+                .filter_map(|(program, input)| self.check(&program, &input))
+                .find(|found| found.backend == divergence.backend);
+            match smaller {
+                Some(smaller) => divergence = smaller,
+                None => return divergence,
+            }
+        }
+    }
+}
+
+/// Every program and input one step simpler than these.
+fn candidates(program: &[i32], input: &[i32]) -> Vec<(Vec<i32>, Vec<i32>)> {
+    let mut candidates = vec![];
+    for i in 0..input.len() {
+        let mut input = input.to_vec();
+        input.remove(i);
+        candidates.push((program.to_vec(), input));
+    }
+    for end in 0..program.len() {
+        candidates.push((program[..end].to_vec(), input.to_vec()));
+    }
+    // Whole instructions at a time, as well as single words.
+    for &width in &[4, 3, 2, 1] {
+        for i in (0..program.len().saturating_sub(width - 1)).rev() {
+            let mut program = program.to_vec();
+            program.drain(i..i + width);
+            candidates.push((program, input.to_vec()));
+        }
+    }
+    for i in 0..program.len() {
+        for &simpler in &[0, program[i] / 2] {
+            if simpler != program[i] {
+                let mut program = program.to_vec();
+                program[i] = simpler;
+                candidates.push((program, input.to_vec()));
+            }
+        }
+    }
+
+    candidates
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::intcode::instruction::Instruction;
+
+    /// An interpreter that loses its last output when there's more than one.
+    struct Forgetful;
+
+    impl Backend for Forgetful {
+        fn name(&self) -> &str {
+            "forgetful"
+        }
+
+        fn run(&self, program: &[i32], input: &[i32], limit: usize) -> Outcome {
+            let mut outcome = Interpreter.run(program, input, limit);
+            if outcome.output.len() > 1 {
+                outcome.output.pop();
+            }
+            outcome
+        }
+    }
+
+    /// An interpreter that carries on as if overflowing arithmetic halted.
+    struct Wrapping;
+
+    impl Backend for Wrapping {
+        fn name(&self) -> &str {
+            "wrapping"
+        }
+
+        fn run(&self, program: &[i32], input: &[i32], limit: usize) -> Outcome {
+            let mut outcome = Interpreter.run(program, input, limit);
+            if outcome.status == Status::Failed(Failure::Overflow) {
+                outcome.status = Status::Halted;
+            }
+            outcome
+        }
+    }
+
+    #[test]
+    fn generated_programs_are_well_formed() {
+        let mut rng = Rng::new(7);
+        for _ in 0..100 {
+            let (program, _) = generate(&mut rng, 10);
+            let mut ptr = 0;
+            for _ in 0..10 {
+                ptr += Instruction::decode(&program[..], ptr).unwrap().size();
+            }
+            assert_eq!(program[ptr], 99);
+        }
+    }
+
+    #[test]
+    fn backends_agree_with_the_reference() {
+        if let Err(divergence) = Fuzzer::new().run(2019, 500) {
+            panic!("{}", divergence)
+        }
+    }
+
+    #[test]
+    fn disagreements_are_shrunk() {
+        let divergence = Fuzzer::new()
+            .backend(Box::new(Forgetful))
+            .run(1, 1_000)
+            .unwrap_err();
+        assert_eq!(divergence.backend, "forgetful");
+        assert!(divergence.program.len() <= 6, "{}", divergence);
+        assert!(Fuzzer::new()
+            .backend(Box::new(Forgetful))
+            .check(&divergence.program, &divergence.input)
+            .is_some())
+    }
+
+    #[test]
+    fn overflow_is_compared() {
+        for program in &[
+            vec![1101, i32::MAX, 1, 0, 99],
+            vec![1102, i32::MIN, -1, 0, 99],
+            vec![109, i32::MAX, 109, 1, 99],
+        ] {
+            assert!(Fuzzer::new().check(program, &[]).is_none());
+            let divergence = Fuzzer::new()
+                .backend(Box::new(Wrapping))
+                .check(program, &[]);
+            assert_eq!(divergence.unwrap().backend, "wrapping")
+        }
+    }
+}
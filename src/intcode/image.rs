@@ -0,0 +1,277 @@
+use super::program::{self, LoadError};
+use failure::Fail;
+
+/// Every image starts with these bytes, followed by the format version.
+pub const MAGIC: &[u8; 4] = b"ICIM";
+pub const VERSION: u8 = 1;
+
+/// Set in the flags byte when runs of zeros are stored as a count.
+const ZERO_RUNS: u8 = 1;
+
+#[derive(Debug, Fail, PartialEq)]
+pub enum ImageError {
+    #[fail(display = "Not an Intcode image")]
+    BadMagic {},
+
+    #[fail(
+        display = "Image is version {}, but only version 1 can be read",
+        version
+    )]
+    UnsupportedVersion { version: u8 },
+
+    #[fail(display = "Unknown flags: {:#04x}", flags)]
+    UnknownFlags { flags: u8 },
+
+    #[fail(display = "Image ends in the middle of a value")]
+    Truncated {},
+
+    #[fail(display = "Value at byte {} doesn't fit in a cell", offset)]
+    Overflow { offset: usize },
+
+    #[fail(display = "Image says it has {} cells, but holds {}", expected, actual)]
+    LengthMismatch { expected: usize, actual: usize },
+}
+
+fn zigzag(value: i32) -> u32 {
+    ((value << 1) ^ (value >> 31)) as u32
+}
+
+fn unzigzag(value: u32) -> i32 {
+    (value >> 1) as i32 ^ -((value & 1) as i32)
+}
+
+fn write_varint(bytes: &mut Vec<u8>, mut value: u32) {
+    while value >= 0x80 {
+        bytes.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    bytes.push(value as u8);
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn byte(&mut self) -> Result<u8, ImageError> {
+        let byte = *self
+            .bytes
+            .get(self.offset)
+            .ok_or(ImageError::Truncated {})?;
+        self.offset += 1;
+        Ok(byte)
+    }
+
+    fn varint(&mut self) -> Result<u32, ImageError> {
+        let start = self.offset;
+        let mut value: u32 = 0;
+        for shift in (0..35).step_by(7) {
+            let byte = self.byte()?;
+            let bits = u32::from(byte & 0x7f);
+            if shift == 28 && bits > 0xf {
+                return Err(ImageError::Overflow { offset: start });
+            }
+            value |= bits << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(ImageError::Overflow { offset: start })
+    }
+
+    fn done(&self) -> bool {
+        self.offset == self.bytes.len()
+    }
+}
+
+/// Encodes memory as a binary image: the magic bytes, version, a flags
+/// byte and the number of cells, then each cell zigzag encoded as a varint
+/// so small negative numbers stay small. With `compress`, each zero is
+/// followed by how many zeros there are in its run, which keeps memory that
+/// has grown far past the program cheap to store.
+pub fn encode(memory: &[i32], compress: bool) -> Vec<u8> {
+    let mut bytes = MAGIC.to_vec();
+    bytes.push(VERSION);
+    bytes.push(if compress { ZERO_RUNS } else { 0 });
+    write_varint(&mut bytes, memory.len() as u32);
+
+    let mut i = 0;
+    while i < memory.len() {
+        write_varint(&mut bytes, zigzag(memory[i]));
+        if compress && memory[i] == 0 {
+            let run = memory[i..].iter().take_while(|&&cell| cell == 0).count();
+            write_varint(&mut bytes, run as u32);
+            i += run;
+        } else {
+            i += 1;
+        }
+    }
+
+    bytes
+}
+
+pub fn decode(bytes: &[u8]) -> Result<Vec<i32>, ImageError> {
+    if bytes.len() < MAGIC.len() || &bytes[..MAGIC.len()] != MAGIC {
+        return Err(ImageError::BadMagic {});
+    }
+    let mut reader = Reader {
+        bytes,
+        offset: MAGIC.len(),
+    };
+    let version = reader.byte()?;
+    if version != VERSION {
+        return Err(ImageError::UnsupportedVersion { version });
+    }
+    let flags = reader.byte()?;
+    if flags & !ZERO_RUNS != 0 {
+        return Err(ImageError::UnknownFlags { flags });
+    }
+    let expected = reader.varint()? as usize;
+
+    // The header can't be trusted, but every cell that isn't in a run of
+    // zeros takes at least a byte.
+    let mut memory = Vec::with_capacity(expected.min(bytes.len()));
+    while !reader.done() && memory.len() <= expected {
+        let value = unzigzag(reader.varint()?);
+        if flags & ZERO_RUNS != 0 && value == 0 {
+            let run = reader.varint()? as usize;
+            if run > expected - memory.len() {
+                return Err(ImageError::LengthMismatch {
+                    expected,
+                    actual: memory.len().saturating_add(run),
+                });
+            }
+            memory.resize(memory.len() + run, 0);
+        } else {
+            memory.push(value);
+        }
+    }
+    if memory.len() != expected || !reader.done() {
+        return Err(ImageError::LengthMismatch {
+            expected,
+            actual: memory.len(),
+        });
+    }
+
+    Ok(memory)
+}
+
+/// Converts the usual comma-separated text into an image.
+pub fn from_text(text: &str, compress: bool) -> Result<Vec<u8>, LoadError> {
+    program::parse(text).map(|memory| encode(&memory, compress))
+}
+
+/// Converts an image back into comma-separated text.
+pub fn to_text(bytes: &[u8]) -> Result<String, ImageError> {
+    let memory = decode(bytes)?;
+    Ok(memory
+        .iter()
+        .map(|cell| cell.to_string())
+        .collect::<Vec<_>>()
+        .join(","))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::intcode::Machine;
+
+    const DAY5: &str = include_str!("../../input/2019/day5.txt");
+
+    #[test]
+    fn zigzag_round_trips_the_extremes() {
+        for &value in &[0, 1, -1, 63, -64, i32::MAX, i32::MIN] {
+            assert_eq!(unzigzag(zigzag(value)), value);
+            let mut bytes = encode(&[value], false);
+            bytes.drain(..7);
+            let mut reader = Reader {
+                bytes: &bytes,
+                offset: 0,
+            };
+            assert_eq!(reader.varint().map(unzigzag), Ok(value))
+        }
+    }
+
+    #[test]
+    fn text_round_trips() {
+        for &compress in &[false, true] {
+            let image = from_text(DAY5, compress).unwrap();
+            assert_eq!(to_text(&image).unwrap(), DAY5.trim())
+        }
+    }
+
+    #[test]
+    fn grown_memory_round_trips_and_compresses() {
+        let quine = [
+            109, 1, 204, -1, 1001, 100, 1, 100, 1008, 100, 16, 101, 1006, 101, 0, 99,
+        ];
+        let mut machine = Machine::new(&quine);
+        machine.run().unwrap();
+        let memory = machine.memory();
+        assert_eq!(memory.len(), 102);
+
+        let plain = encode(memory, false);
+        let compressed = encode(memory, true);
+        assert!(compressed.len() < plain.len() / 2);
+        assert_eq!(decode(&plain).unwrap(), memory);
+        assert_eq!(decode(&compressed).unwrap(), memory);
+        assert_eq!(decode(&encode(&[0; 5], true)).unwrap(), vec![0; 5])
+    }
+
+    #[test]
+    fn rejects_broken_images() {
+        let image = encode(&[1, 0, 0, 3, 4], true);
+        assert_eq!(decode(b"1,0,0,3,99"), Err(ImageError::BadMagic {}));
+
+        let mut future = image.clone();
+        future[4] = 2;
+        assert_eq!(
+            decode(&future),
+            Err(ImageError::UnsupportedVersion { version: 2 })
+        );
+
+        assert_eq!(
+            decode(&image[..image.len() - 1]),
+            Err(ImageError::LengthMismatch {
+                expected: 5,
+                actual: 4
+            })
+        );
+
+        let mut cut = encode(&[1000], false);
+        cut.pop();
+        assert_eq!(decode(&cut), Err(ImageError::Truncated {}));
+
+        let mut huge = encode(&[], false);
+        huge[6] = 1;
+        huge.extend(&[0xff, 0xff, 0xff, 0xff, 0x7f]);
+        assert_eq!(decode(&huge), Err(ImageError::Overflow { offset: 7 }))
+    }
+
+    #[test]
+    fn lengths_in_the_header_are_not_trusted() {
+        // Claims u32::MAX cells but holds one.
+        let mut image = MAGIC.to_vec();
+        image.extend(&[VERSION, 0, 0xff, 0xff, 0xff, 0xff, 0x0f, 2]);
+        assert_eq!(
+            decode(&image),
+            Err(ImageError::LengthMismatch {
+                expected: u32::MAX as usize,
+                actual: 1
+            })
+        );
+
+        // A run of a million zeros in a three cell image.
+        let mut image = encode(&[1, 0, 0], true);
+        image.truncate(image.len() - 1);
+        image.extend(&[0xc0, 0x84, 0x3d]);
+        assert_eq!(
+            decode(&image),
+            Err(ImageError::LengthMismatch {
+                expected: 3,
+                actual: 1_000_001
+            })
+        )
+    }
+}
@@ -0,0 +1,343 @@
+use super::space::AddressSpace;
+use super::{get_modes, IntCodeError, ParameterMode};
+use std::fmt;
+
+/// A parameter as written in the program, before its mode is applied.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Parameter {
+    pub value: i32,
+    pub mode: ParameterMode,
+}
+
+impl Parameter {
+    pub fn immediate(value: i32) -> Parameter {
+        Parameter {
+            value,
+            mode: ParameterMode::Immediate,
+        }
+    }
+
+    pub fn position(address: i32) -> Parameter {
+        Parameter {
+            value: address,
+            mode: ParameterMode::Position,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Instruction {
+    Add(Parameter, Parameter, Parameter),
+    Multiply(Parameter, Parameter, Parameter),
+    Input(Parameter),
+    Output(Parameter),
+    JumpIfTrue(Parameter, Parameter),
+    JumpIfFalse(Parameter, Parameter),
+    LessThan(Parameter, Parameter, Parameter),
+    Equals(Parameter, Parameter, Parameter),
+    AdjustRelativeBase(Parameter),
+    Halt,
+}
+
+/// How many parameters follow an opcode.
+fn arity(opcode: i32) -> Option<usize> {
+    match opcode {
+        1 | 2 | 7 | 8 => Some(3),
+        5 | 6 => Some(2),
+        3 | 4 | 9 => Some(1),
+        99 => Some(0),
+        _ => None,
+    }
+}
+
+/// Anything instructions can be decoded from: a machine's memory, or a
+/// program that isn't running.
+pub trait Code {
+    /// The word at `address`, or `None` past the end.
+    fn word(&self, address: usize) -> Option<i32>;
+}
+
+impl<M: AddressSpace + ?Sized> Code for M {
+    fn word(&self, address: usize) -> Option<i32> {
+        self.peek(address)
+    }
+}
+
+impl Code for [i32] {
+    fn word(&self, address: usize) -> Option<i32> {
+        self.get(address).copied()
+    }
+}
+
+impl Instruction {
+    /// Splits the first word of an instruction into its opcode and the modes
+    /// of its parameters, and works out how many parameters follow.
+    pub fn head(word: i32) -> Result<(i32, [ParameterMode; 3], usize), IntCodeError> {
+        let opcode = word % 100;
+        let count = arity(opcode).ok_or(IntCodeError::UnknownOpcodeError { opcode })?;
+        let (first, second, third) = get_modes(word)?;
+        Ok((opcode, [first, second, third], count))
+    }
+
+    /// Reads the instruction at `ptr`.
+    pub fn decode<C: Code + ?Sized>(code: &C, ptr: usize) -> Result<Instruction, IntCodeError> {
+        let word = code
+            .word(ptr)
+            .ok_or(IntCodeError::UnexpectedEndOfInputError {})?;
+        let (opcode, modes, count) = Instruction::head(word)?;
+        let parameters = (modes.iter().take(count).enumerate())
+            .map(|(n, &mode)| {
+                let value = code
+                    .word(ptr + 1 + n)
+                    .ok_or(IntCodeError::UnexpectedEndOfInputError {})?;
+                Ok(Parameter { value, mode })
+            })
+            .collect::<Result<Vec<_>, IntCodeError>>()?;
+        Ok(Instruction::new(opcode, &parameters).unwrap())
+    }
+
+    /// Puts an instruction together from its opcode and parameters, if there
+    /// are the right number of them.
+    pub fn new(opcode: i32, parameters: &[Parameter]) -> Option<Instruction> {
+        let p = |n: usize| parameters[n];
+        if arity(opcode) != Some(parameters.len()) {
+            return None;
+        }
+        Some(match opcode {
+            1 => Instruction::Add(p(0), p(1), p(2)),
+            2 => Instruction::Multiply(p(0), p(1), p(2)),
+            3 => Instruction::Input(p(0)),
+            4 => Instruction::Output(p(0)),
+            5 => Instruction::JumpIfTrue(p(0), p(1)),
+            6 => Instruction::JumpIfFalse(p(0), p(1)),
+            7 => Instruction::LessThan(p(0), p(1), p(2)),
+            8 => Instruction::Equals(p(0), p(1), p(2)),
+            9 => Instruction::AdjustRelativeBase(p(0)),
+            _ => Instruction::Halt,
+        })
+    }
+
+    pub fn opcode(&self) -> i32 {
+        match self {
+            Instruction::Add(..) => 1,
+            Instruction::Multiply(..) => 2,
+            Instruction::Input(_) => 3,
+            Instruction::Output(_) => 4,
+            Instruction::JumpIfTrue(..) => 5,
+            Instruction::JumpIfFalse(..) => 6,
+            Instruction::LessThan(..) => 7,
+            Instruction::Equals(..) => 8,
+            Instruction::AdjustRelativeBase(_) => 9,
+            Instruction::Halt => 99,
+        }
+    }
+
+    pub fn parameters(&self) -> Vec<Parameter> {
+        match *self {
+            Instruction::Add(a, b, c)
+            | Instruction::Multiply(a, b, c)
+            | Instruction::LessThan(a, b, c)
+            | Instruction::Equals(a, b, c) => vec![a, b, c],
+            Instruction::JumpIfTrue(a, b) | Instruction::JumpIfFalse(a, b) => vec![a, b],
+            Instruction::Input(a) | Instruction::Output(a) | Instruction::AdjustRelativeBase(a) => {
+                vec![a]
+            }
+            Instruction::Halt => vec![],
+        }
+    }
+
+    /// The same instruction with each parameter replaced by `f(n, parameter)`.
+    pub fn map_parameters<F>(&self, mut f: F) -> Instruction
+    where
+        F: FnMut(usize, Parameter) -> Parameter,
+    {
+        let parameters: Vec<Parameter> = self
+            .parameters()
+            .into_iter()
+            .enumerate()
+            .map(|(n, parameter)| f(n, parameter))
+            .collect();
+        Instruction::new(self.opcode(), &parameters).unwrap()
+    }
+
+    /// The parameters whose values the instruction uses.
+    pub fn sources(&self) -> Vec<Parameter> {
+        match *self {
+            Instruction::Add(a, b, _)
+            | Instruction::Multiply(a, b, _)
+            | Instruction::LessThan(a, b, _)
+            | Instruction::Equals(a, b, _)
+            | Instruction::JumpIfTrue(a, b)
+            | Instruction::JumpIfFalse(a, b) => vec![a, b],
+            Instruction::Output(a) | Instruction::AdjustRelativeBase(a) => vec![a],
+            Instruction::Input(_) | Instruction::Halt => vec![],
+        }
+    }
+
+    /// The parameter the instruction writes its result to, if it has one.
+    pub fn destination(&self) -> Option<Parameter> {
+        match *self {
+            Instruction::Add(_, _, c)
+            | Instruction::Multiply(_, _, c)
+            | Instruction::LessThan(_, _, c)
+            | Instruction::Equals(_, _, c) => Some(c),
+            Instruction::Input(a) => Some(a),
+            _ => None,
+        }
+    }
+
+    /// The addresses the instruction reads from and writes to, leaving out
+    /// relative mode, whose addresses depend on the relative base.
+    pub fn accesses(&self) -> (Vec<usize>, Option<usize>) {
+        let address = |parameter: Parameter| match parameter.mode {
+            ParameterMode::Position if parameter.value >= 0 => Some(parameter.value as usize),
+            _ => None,
+        };
+        let reads = self.sources().into_iter().filter_map(address).collect();
+        (reads, self.destination().and_then(address))
+    }
+
+    /// The words the instruction is made of.
+    pub fn encode(&self) -> Vec<i32> {
+        let parameters = self.parameters();
+        let modes: i32 = parameters
+            .iter()
+            .zip(&[100, 1_000, 10_000])
+            .map(|(parameter, &place)| match parameter.mode {
+                ParameterMode::Position => 0,
+                ParameterMode::Immediate => place,
+                ParameterMode::Relative => 2 * place,
+            })
+            .sum();
+        let mut words = vec![self.opcode() + modes];
+        words.extend(parameters.iter().map(|parameter| parameter.value));
+        words
+    }
+
+    /// How many words the instruction takes up.
+    pub fn size(&self) -> usize {
+        match self {
+            Instruction::Add(..)
+            | Instruction::Multiply(..)
+            | Instruction::LessThan(..)
+            | Instruction::Equals(..) => 4,
+            Instruction::JumpIfTrue(..) | Instruction::JumpIfFalse(..) => 3,
+            Instruction::Input(_) | Instruction::Output(_) | Instruction::AdjustRelativeBase(_) => {
+                2
+            }
+            Instruction::Halt => 1,
+        }
+    }
+}
+
+/// Positions in brackets, immediates bare, and relative addresses as an
+/// offset from `rb`.
+impl fmt::Display for Parameter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.mode {
+            ParameterMode::Position => write!(f, "[{}]", self.value),
+            ParameterMode::Immediate => write!(f, "{}", self.value),
+            ParameterMode::Relative => write!(f, "[rb{:+}]", self.value),
+        }
+    }
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (mnemonic, parameters) = match *self {
+            Instruction::Add(a, b, c) => ("add", vec![a, b, c]),
+            Instruction::Multiply(a, b, c) => ("mul", vec![a, b, c]),
+            Instruction::Input(a) => ("in", vec![a]),
+            Instruction::Output(a) => ("out", vec![a]),
+            Instruction::JumpIfTrue(a, b) => ("jt", vec![a, b]),
+            Instruction::JumpIfFalse(a, b) => ("jf", vec![a, b]),
+            Instruction::LessThan(a, b, c) => ("lt", vec![a, b, c]),
+            Instruction::Equals(a, b, c) => ("eq", vec![a, b, c]),
+            Instruction::AdjustRelativeBase(a) => ("arb", vec![a]),
+            Instruction::Halt => ("halt", vec![]),
+        };
+        write!(f, "{}", mnemonic)?;
+        for (n, parameter) in parameters.iter().enumerate() {
+            write!(f, "{}{}", if n == 0 { " " } else { ", " }, parameter)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn decodes_parameters_and_modes() {
+        let memory = vec![1002, 4, 3, 4, 33];
+        assert_eq!(
+            Instruction::decode(&memory, 0).unwrap(),
+            Instruction::Multiply(
+                Parameter {
+                    value: 4,
+                    mode: ParameterMode::Position
+                },
+                Parameter {
+                    value: 3,
+                    mode: ParameterMode::Immediate
+                },
+                Parameter {
+                    value: 4,
+                    mode: ParameterMode::Position
+                },
+            )
+        );
+        assert_eq!(
+            Instruction::decode(&vec![204, -1], 0).unwrap(),
+            Instruction::Output(Parameter {
+                value: -1,
+                mode: ParameterMode::Relative
+            })
+        );
+        assert_eq!(Instruction::decode(&vec![99], 0).unwrap().size(), 1)
+    }
+
+    #[test]
+    fn rejects_what_it_cannot_decode() {
+        match Instruction::decode(&vec![1, 0, 0], 0) {
+            Err(IntCodeError::UnexpectedEndOfInputError {}) => {}
+            other => panic!("expected a truncated instruction, got {:?}", other),
+        }
+        match Instruction::decode(&vec![42], 0) {
+            Err(IntCodeError::UnknownOpcodeError { opcode }) => assert_eq!(opcode, 42),
+            other => panic!("expected an unknown opcode, got {:?}", other),
+        }
+        match Instruction::decode(&vec![301], 0) {
+            Err(IntCodeError::UnrecognisedParameterMode { val }) => assert_eq!(val, 3),
+            other => panic!("expected a bad mode, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn encodes_back_to_the_same_words() {
+        let program = [1002, 4, 3, 4, 21107, -1, 2, 0, 1105, 1, 0, 204, 7, 99];
+        let mut ptr = 0;
+        while ptr < program.len() {
+            let instruction = Instruction::decode(&program[..], ptr).unwrap();
+            assert_eq!(
+                instruction.encode(),
+                &program[ptr..ptr + instruction.size()]
+            );
+            ptr += instruction.size();
+        }
+    }
+
+    #[test]
+    fn displays_as_assembly() {
+        let memory = vec![1002, 4, 3, 4, 22201, -1, 2, 0, 99];
+        let text: Vec<String> = [0, 4, 8]
+            .iter()
+            .map(|&ptr| Instruction::decode(&memory, ptr).unwrap().to_string())
+            .collect();
+        assert_eq!(
+            text,
+            vec!["mul [4], 3, [4]", "add [rb-1], [rb+2], [rb+0]", "halt"]
+        )
+    }
+}
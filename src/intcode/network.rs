@@ -0,0 +1,274 @@
+use super::fuzz::Rng;
+use super::{IntCodeError, Machine, State};
+use failure::Fail;
+
+#[derive(Debug, Fail)]
+pub enum NetworkError {
+    #[fail(display = "Machine {}: {}", machine, error)]
+    Machine {
+        machine: usize,
+        #[cause]
+        error: IntCodeError,
+    },
+}
+
+/// Which machine runs next.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Policy {
+    /// Each machine that can run takes one step in turn.
+    RoundRobin,
+    /// Each machine in turn runs until it halts or waits for input.
+    UntilBlocked,
+    /// A machine that can run is picked at random for every step, the same
+    /// way each time for the same seed.
+    Random(u64),
+}
+
+/// How a network stopped.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Outcome {
+    Halted,
+    /// Every machine still going is waiting for input with none on its way.
+    Deadlock {
+        waiting: Vec<usize>,
+    },
+    /// The network ran for as many steps as it was allowed.
+    StepLimit,
+}
+
+/// Machines whose output is passed to each other's input, run one step at a
+/// time on a single thread. Output goes to every machine it's connected to as
+/// soon as it's made, and is also kept for looking at afterwards.
+#[derive(Clone, Debug, Default)]
+pub struct Network {
+    machines: Vec<Machine>,
+    /// Where each machine's output goes.
+    routes: Vec<Vec<usize>>,
+    outputs: Vec<Vec<i32>>,
+    /// How each machine last stopped, or `Running` if it can go on.
+    states: Vec<State>,
+    steps: usize,
+}
+
+impl Network {
+    pub fn new() -> Network {
+        Network::default()
+    }
+
+    /// Adds a machine, returning its index.
+    pub fn add(&mut self, machine: Machine) -> usize {
+        self.machines.push(machine);
+        self.routes.push(vec![]);
+        self.outputs.push(vec![]);
+        self.states.push(State::Running);
+        self.machines.len() - 1
+    }
+
+    /// Sends everything `from` outputs to `to`.
+    pub fn connect(&mut self, from: usize, to: usize) {
+        self.routes[from].push(to);
+    }
+
+    pub fn push_input(&mut self, machine: usize, value: i32) {
+        self.machines[machine].push_input(value);
+        if self.states[machine] == State::AwaitingInput {
+            self.states[machine] = State::Running;
+        }
+    }
+
+    pub fn machine(&self, machine: usize) -> &Machine {
+        &self.machines[machine]
+    }
+
+    /// Everything `machine` has output, wherever it went.
+    pub fn output(&self, machine: usize) -> &[i32] {
+        &self.outputs[machine]
+    }
+
+    /// How many instructions have run across every machine.
+    pub fn steps(&self) -> usize {
+        self.steps
+    }
+
+    fn step(&mut self, machine: usize) -> Result<State, NetworkError> {
+        let state = self.machines[machine]
+            .step()
+            .map_err(|error| NetworkError::Machine { machine, error })?;
+        self.steps += 1;
+        self.states[machine] = state;
+        for value in self.machines[machine].take_output() {
+            self.outputs[machine].push(value);
+            for i in 0..self.routes[machine].len() {
+                self.push_input(self.routes[machine][i], value);
+            }
+        }
+        Ok(state)
+    }
+
+    /// Runs until every machine has halted, the network deadlocks, or
+    /// `limit` more instructions have run.
+    pub fn run(&mut self, policy: Policy, limit: usize) -> Result<Outcome, NetworkError> {
+        let limit = self.steps + limit;
+        let mut rng = match policy {
+            Policy::Random(seed) => Some(Rng::new(seed)),
+            _ => None,
+        };
+        // Where round robin and running until blocked carry on from.
+        let mut next = 0;
+        loop {
+            let runnable: Vec<usize> = (0..self.machines.len())
+                .filter(|&i| self.states[i] == State::Running)
+                .collect();
+            if runnable.is_empty() {
+                let waiting: Vec<usize> = (0..self.machines.len())
+                    .filter(|&i| self.states[i] == State::AwaitingInput)
+                    .collect();
+                return Ok(if waiting.is_empty() {
+                    Outcome::Halted
+                } else {
+                    Outcome::Deadlock { waiting }
+                });
+            }
+            if self.steps >= limit {
+                return Ok(Outcome::StepLimit);
+            }
+
+            let machine = match &mut rng {
+                Some(rng) => runnable[rng.below(runnable.len())],
+                None => *runnable
+                    .iter()
+                    .find(|&&i| i >= next)
+                    .unwrap_or(&runnable[0]),
+            };
+            next = machine + 1;
+            if policy == Policy::UntilBlocked {
+                while self.steps < limit && self.step(machine)? == State::Running {}
+            } else {
+                self.step(machine)?;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Reads values forever, writing each one back plus one.
+    const INCREMENT: [i32; 12] = [3, 11, 1001, 11, 1, 11, 4, 11, 1105, 1, 0, 0];
+
+    /// The feedback loop example from day 7, which gives 139629729 with
+    /// phases 9, 8, 7, 6 and 5.
+    const AMPLIFIER: [i32; 29] = [
+        3, 26, 1001, 26, -4, 26, 3, 27, 1002, 27, 2, 27, 1, 27, 26, 27, 4, 27, 1001, 28, -1, 28,
+        1005, 28, 6, 99, 0, 0, 5,
+    ];
+
+    const POLICIES: [Policy; 5] = [
+        Policy::RoundRobin,
+        Policy::UntilBlocked,
+        Policy::Random(1),
+        Policy::Random(2),
+        Policy::Random(3),
+    ];
+
+    fn amplifiers() -> Network {
+        let mut network = Network::new();
+        for phase in &[9, 8, 7, 6, 5] {
+            let machine = network.add(Machine::new(&AMPLIFIER));
+            network.push_input(machine, *phase);
+        }
+        for i in 0..5 {
+            network.connect(i, (i + 1) % 5);
+        }
+        network.push_input(0, 0);
+        network
+    }
+
+    #[test]
+    fn policies_agree_on_a_feedback_loop() {
+        for &policy in &POLICIES {
+            let mut network = amplifiers();
+            assert_eq!(network.run(policy, 100_000).unwrap(), Outcome::Halted);
+            assert_eq!(network.output(4).last(), Some(&139_629_729), "{:?}", policy)
+        }
+    }
+
+    #[test]
+    fn policies_agree_on_a_chain() {
+        for &policy in &POLICIES {
+            let mut network = Network::new();
+            for i in 0..5 {
+                network.add(Machine::new(&INCREMENT));
+                if i > 0 {
+                    network.connect(i - 1, i);
+                }
+            }
+            for value in 0..3 {
+                network.push_input(0, value);
+            }
+            // Once the values have passed through, every machine waits for
+            // more.
+            assert_eq!(
+                network.run(policy, 100_000).unwrap(),
+                Outcome::Deadlock {
+                    waiting: vec![0, 1, 2, 3, 4]
+                }
+            );
+            assert_eq!(network.output(4), &[5, 6, 7], "{:?}", policy)
+        }
+    }
+
+    #[test]
+    fn detects_deadlock() {
+        // Each waits for the other before sending anything.
+        let mut network = Network::new();
+        let a = network.add(Machine::new(&INCREMENT));
+        let b = network.add(Machine::new(&INCREMENT));
+        let c = network.add(Machine::new(&[104, 1, 99]));
+        network.connect(a, b);
+        network.connect(b, a);
+        assert_eq!(
+            network.run(Policy::RoundRobin, 1_000).unwrap(),
+            Outcome::Deadlock {
+                waiting: vec![a, b]
+            }
+        );
+        assert_eq!(network.output(c), &[1]);
+        // Sending one of them something gets them going again, forever.
+        network.push_input(a, 0);
+        assert_eq!(
+            network.run(Policy::RoundRobin, 1_000).unwrap(),
+            Outcome::StepLimit
+        );
+        assert_eq!(network.steps(), 4 + 1_000)
+    }
+
+    #[test]
+    fn random_order_repeats_for_a_seed() {
+        // Stopped part way, how far each machine has got depends on the order
+        // they ran in.
+        let progress = |seed| {
+            let mut network = amplifiers();
+            network.run(Policy::Random(seed), 40).unwrap();
+            network
+                .machines
+                .iter()
+                .map(Machine::steps)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(progress(7), progress(7));
+        assert_ne!(progress(7), progress(8))
+    }
+
+    #[test]
+    fn reports_which_machine_failed() {
+        let mut network = Network::new();
+        network.add(Machine::new(&INCREMENT));
+        network.add(Machine::new(&[42]));
+        match network.run(Policy::UntilBlocked, 100) {
+            Err(NetworkError::Machine { machine, .. }) => assert_eq!(machine, 1),
+            other => panic!("expected machine 1 to fail, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,652 @@
+use super::instruction::{Instruction, Parameter};
+use super::ParameterMode;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// Where control can go after the instruction at each address.
+type Successors = BTreeMap<usize, Vec<usize>>;
+
+/// An unconditional jump with room for one more word after it, used to
+/// stand in for a removed instruction without moving anything else.
+fn jump(target: usize) -> Instruction {
+    Instruction::JumpIfTrue(Parameter::immediate(1), Parameter::immediate(target as i32))
+}
+
+/// What the optimiser learned about a program: every instruction that can
+/// run, which addresses are ever written, which instructions' results are
+/// never used, and which addresses are read by the rest.
+struct Analysis {
+    instructions: BTreeMap<usize, Instruction>,
+    variable: HashSet<usize>,
+    volatile: HashSet<usize>,
+    dead: HashSet<usize>,
+    read: HashSet<usize>,
+}
+
+impl Analysis {
+    /// Gives up on anything it can't see all the way through: relative mode,
+    /// jumps to computed addresses, writes to patched addresses, and code
+    /// which is rewritten before it runs. Writing over an instruction which
+    /// can't run again is fine, as is patching an operand which isn't a jump
+    /// target or a destination, though then the operand could be anything.
+    fn of(
+        program: &[i32],
+        volatile: &HashSet<usize>,
+        observed: &HashSet<usize>,
+    ) -> Option<Analysis> {
+        let mut variable = volatile.clone();
+        let (instructions, successors) = loop {
+            let (instructions, successors) = Analysis::reachable(program, &variable)?;
+            let mut written = volatile.clone();
+            for instruction in instructions.values() {
+                written.extend(instruction.accesses().1);
+            }
+            if written == variable {
+                break (instructions, successors);
+            }
+            variable = written;
+        };
+
+        let mut later = HashMap::new();
+        for (&pc, instruction) in &instructions {
+            let words = pc..pc + instruction.size();
+            if volatile.contains(&pc) {
+                return None;
+            }
+            // A destination is always the last parameter.
+            if instruction.destination().is_some() && volatile.contains(&(words.end - 1)) {
+                return None;
+            }
+            let write = match instruction.accesses().1 {
+                Some(write) => write,
+                None => continue,
+            };
+            let runs_later = later
+                .entry(pc)
+                .or_insert_with(|| Analysis::later(&successors, pc));
+            for (&ptr, other) in instructions.range(write.saturating_sub(3)..=write) {
+                if write < ptr + other.size() && runs_later.contains(&ptr) {
+                    return None;
+                }
+            }
+        }
+
+        let mut analysis = Analysis {
+            instructions,
+            variable,
+            volatile: volatile.clone(),
+            dead: HashSet::new(),
+            read: observed.clone(),
+        };
+        analysis.liveness(program, &successors, observed);
+        Some(analysis)
+    }
+
+    /// Every instruction, with where control can go after it, or `None` if
+    /// the program can't be followed.
+    fn reachable(
+        program: &[i32],
+        variable: &HashSet<usize>,
+    ) -> Option<(BTreeMap<usize, Instruction>, Successors)> {
+        let mut instructions = BTreeMap::new();
+        let mut successors = BTreeMap::new();
+        let mut pending = vec![0];
+        while let Some(ptr) = pending.pop() {
+            if ptr >= program.len() || instructions.contains_key(&ptr) {
+                continue;
+            }
+            let instruction = Instruction::decode(program, ptr).ok()?;
+            if let Instruction::AdjustRelativeBase(_) = instruction {
+                return None;
+            }
+            let parameters = instruction.parameters();
+            if parameters.iter().any(|p| p.mode == ParameterMode::Relative) {
+                return None;
+            }
+
+            let next = ptr + instruction.size();
+            let after = match instruction {
+                Instruction::Halt => vec![],
+                Instruction::JumpIfTrue(..) | Instruction::JumpIfFalse(..) => {
+                    let value = |n| constant(program, variable, ptr, &instruction, n);
+                    let target = value(1)?;
+                    if target < 0 {
+                        return None;
+                    }
+                    match value(0) {
+                        Some(condition) if (condition != 0) == (instruction.opcode() == 5) => {
+                            vec![target as usize]
+                        }
+                        Some(_) => vec![next],
+                        None => vec![target as usize, next],
+                    }
+                }
+                _ => vec![next],
+            };
+            pending.extend(&after);
+            successors.insert(ptr, after);
+            instructions.insert(ptr, instruction);
+        }
+
+        Some((instructions, successors))
+    }
+
+    /// The instructions which can run at some point after the one at `pc`.
+    fn later(successors: &Successors, pc: usize) -> HashSet<usize> {
+        let mut later = HashSet::new();
+        let mut pending = successors[&pc].clone();
+        while let Some(ptr) = pending.pop() {
+            if let Some(next) = successors.get(&ptr) {
+                if later.insert(ptr) {
+                    pending.extend(next);
+                }
+            }
+        }
+        later
+    }
+
+    /// The addresses an instruction reads from. An operand which was patched
+    /// before the program ran could point anywhere.
+    fn reads(&self, program: &[i32], pc: usize, instruction: &Instruction) -> HashSet<usize> {
+        let mut reads = HashSet::new();
+        let sources = instruction.sources().len();
+        for (n, parameter) in instruction
+            .parameters()
+            .into_iter()
+            .enumerate()
+            .take(sources)
+        {
+            if parameter.mode != ParameterMode::Position {
+                continue;
+            }
+            if self.variable.contains(&(pc + 1 + n)) {
+                reads.extend(0..program.len());
+                reads.extend(&self.variable);
+            } else if parameter.value >= 0 {
+                reads.insert(parameter.value as usize);
+            }
+        }
+        reads
+    }
+
+    /// Works backwards from the end of the program, where only `observed`
+    /// matters, to find the adds, multiplies and comparisons whose results
+    /// are overwritten or never read, and what the others read.
+    fn liveness(&mut self, program: &[i32], successors: &Successors, observed: &HashSet<usize>) {
+        let reads: BTreeMap<usize, HashSet<usize>> = (self.instructions.iter())
+            .map(|(&pc, instruction)| (pc, self.reads(program, pc, instruction)))
+            .collect();
+        let mut live: HashMap<usize, HashSet<usize>> = HashMap::new();
+        let live_after = |live: &HashMap<usize, HashSet<usize>>, pc: usize| {
+            let mut after = HashSet::new();
+            if successors[&pc].is_empty() {
+                after.extend(observed);
+            }
+            for next in &successors[&pc] {
+                match live.get(next) {
+                    Some(before) => after.extend(before),
+                    None if *next >= program.len() => after.extend(observed),
+                    None => {}
+                }
+            }
+            after
+        };
+
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for (&pc, instruction) in self.instructions.iter().rev() {
+                let mut before = live_after(&live, pc);
+                let unused =
+                    matches!(result(instruction), Some(address) if !before.contains(&address));
+                if !unused {
+                    if let Some(write) = instruction.accesses().1 {
+                        before.remove(&write);
+                    }
+                    before.extend(&reads[&pc]);
+                }
+                if live.get(&pc) != Some(&before) {
+                    live.insert(pc, before);
+                    changed = true;
+                }
+            }
+        }
+
+        for (&pc, instruction) in &self.instructions {
+            let after = live_after(&live, pc);
+            if matches!(result(instruction), Some(address) if !after.contains(&address)) {
+                self.dead.insert(pc);
+            } else {
+                self.read.extend(&reads[&pc]);
+            }
+        }
+    }
+
+    fn constant(
+        &self,
+        program: &[i32],
+        pc: usize,
+        instruction: &Instruction,
+        n: usize,
+    ) -> Option<i32> {
+        constant(program, &self.variable, pc, instruction, n)
+    }
+
+    /// Whether the instruction at `pc` can be replaced: nothing patches it,
+    /// and nothing still needed reads any word the replacement changes.
+    fn rewritable(&self, program: &[i32], pc: usize, replacement: &Instruction) -> bool {
+        let size = self.instructions[&pc].size();
+        let words = replacement.encode();
+        !(pc..pc + size).any(|word| self.volatile.contains(&word))
+            && (words.iter().enumerate())
+                .all(|(n, &word)| word == program[pc + n] || !self.read.contains(&(pc + n)))
+    }
+}
+
+/// Where an add, multiply or comparison stores its result. Unlike input,
+/// these can be dropped if nothing uses the result.
+fn result(instruction: &Instruction) -> Option<usize> {
+    match instruction {
+        Instruction::Input(_) => None,
+        _ => instruction.accesses().1,
+    }
+}
+
+/// The value of the `n`th parameter of the instruction at `pc`, if it's the
+/// same every time the instruction runs.
+fn constant(
+    program: &[i32],
+    variable: &HashSet<usize>,
+    pc: usize,
+    instruction: &Instruction,
+    n: usize,
+) -> Option<i32> {
+    if variable.contains(&(pc + 1 + n)) {
+        return None;
+    }
+    let parameter = instruction.parameters()[n];
+    match parameter.mode {
+        ParameterMode::Immediate => Some(parameter.value),
+        ParameterMode::Position if parameter.value >= 0 => {
+            let address = parameter.value as usize;
+            if variable.contains(&address) {
+                None
+            } else {
+                program.get(address).copied()
+            }
+        }
+        _ => None,
+    }
+}
+
+/// The rewritten program, which is always the same length as the original so
+/// that every address still means the same thing.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Optimised {
+    pub program: Vec<i32>,
+    pub folded: usize,
+    pub removed: usize,
+    pub threaded: usize,
+}
+
+/// A peephole optimiser which folds constant operands into immediates,
+/// removes writes nothing reads, and threads jumps through other jumps.
+///
+/// Memory patched before the program runs has to be declared `volatile`, and
+/// anything inspected after it halts `observed`, or the optimiser will assume
+/// they're constant or unused.
+#[derive(Default)]
+pub struct Optimiser {
+    volatile: HashSet<usize>,
+    observed: HashSet<usize>,
+}
+
+impl Optimiser {
+    pub fn new() -> Optimiser {
+        Optimiser::default()
+    }
+
+    pub fn volatile(mut self, addresses: &[usize]) -> Optimiser {
+        self.volatile.extend(addresses);
+        self
+    }
+
+    pub fn observed(mut self, addresses: &[usize]) -> Optimiser {
+        self.observed.extend(addresses);
+        self
+    }
+
+    pub fn optimise(&self, program: &[i32]) -> Optimised {
+        let mut optimised = Optimised {
+            program: program.to_vec(),
+            folded: 0,
+            removed: 0,
+            threaded: 0,
+        };
+        let analysis = match Analysis::of(program, &self.volatile, &self.observed) {
+            Some(analysis) => analysis,
+            None => return optimised,
+        };
+
+        let mut rewritten = BTreeMap::new();
+        for (&ptr, instruction) in &analysis.instructions {
+            let replacement = match instruction {
+                Instruction::Add(..)
+                | Instruction::Multiply(..)
+                | Instruction::LessThan(..)
+                | Instruction::Equals(..) => self.fold(program, &analysis, ptr, instruction),
+                Instruction::Output(_) => analysis
+                    .constant(program, ptr, instruction, 0)
+                    .map(|value| (Rewrite::Folded, output(value))),
+                Instruction::JumpIfTrue(..) | Instruction::JumpIfFalse(..) => analysis
+                    .constant(program, ptr, instruction, 0)
+                    .map(|condition| {
+                        let target = analysis.constant(program, ptr, instruction, 1).unwrap();
+                        if (condition != 0) == (instruction.opcode() == 5) {
+                            jump(target as usize)
+                        } else {
+                            jump(ptr + instruction.size())
+                        }
+                    })
+                    .map(|jump| (Rewrite::Threaded, jump)),
+                _ => None,
+            };
+
+            if let Some((rewrite, replacement)) = replacement {
+                if replacement != *instruction && analysis.rewritable(program, ptr, &replacement) {
+                    match rewrite {
+                        Rewrite::Folded => optimised.folded += 1,
+                        Rewrite::Removed => optimised.removed += 1,
+                        Rewrite::Threaded => optimised.threaded += 1,
+                    }
+                    rewritten.insert(ptr, replacement);
+                }
+            }
+        }
+
+        optimised.threaded += thread_jumps(program, &analysis, &mut rewritten);
+        for (ptr, instruction) in rewritten {
+            let words = instruction.encode();
+            optimised.program[ptr..ptr + words.len()].copy_from_slice(&words);
+        }
+
+        optimised
+    }
+
+    fn fold(
+        &self,
+        program: &[i32],
+        analysis: &Analysis,
+        ptr: usize,
+        instruction: &Instruction,
+    ) -> Option<(Rewrite, Instruction)> {
+        let address = instruction.parameters()[2].value as usize;
+        let noun = analysis.constant(program, ptr, instruction, 0);
+        let verb = analysis.constant(program, ptr, instruction, 1);
+        let value = match (noun, verb) {
+            (Some(noun), Some(verb)) => match instruction {
+                Instruction::Add(..) => noun.checked_add(verb),
+                Instruction::Multiply(..) => noun.checked_mul(verb),
+                Instruction::LessThan(..) => Some((noun < verb) as i32),
+                _ => Some((noun == verb) as i32),
+            },
+            _ => None,
+        };
+
+        // An instruction whose result isn't used can go, as long as it can't
+        // fail by overflowing or reading a negative address.
+        let infallible = match instruction {
+            Instruction::Add(..) | Instruction::Multiply(..) => value.is_some(),
+            _ => (instruction.sources().iter().enumerate()).all(|(n, parameter)| {
+                parameter.mode == ParameterMode::Immediate
+                    || (parameter.value >= 0 && !analysis.variable.contains(&(ptr + 1 + n)))
+            }),
+        };
+        if analysis.dead.contains(&ptr) && infallible {
+            return Some((Rewrite::Removed, jump(ptr + instruction.size())));
+        }
+
+        let folded = match value {
+            Some(value) => Instruction::Add(
+                Parameter::immediate(value),
+                Parameter::immediate(0),
+                Parameter::position(address as i32),
+            ),
+            // Whichever operands are constant can still become immediates.
+            None => instruction.map_parameters(|n, parameter| match [noun, verb].get(n) {
+                Some(&Some(constant)) => Parameter::immediate(constant),
+                _ => parameter,
+            }),
+        };
+
+        Some((Rewrite::Folded, folded))
+    }
+}
+
+enum Rewrite {
+    Folded,
+    Removed,
+    Threaded,
+}
+
+fn output(value: i32) -> Instruction {
+    Instruction::Output(Parameter::immediate(value))
+}
+
+/// Where an instruction always jumps to, if it's an unconditional jump.
+fn unconditional(instruction: &Instruction) -> Option<usize> {
+    let (when, condition, target) = match *instruction {
+        Instruction::JumpIfTrue(condition, target) => (true, condition, target),
+        Instruction::JumpIfFalse(condition, target) => (false, condition, target),
+        _ => return None,
+    };
+    if condition.mode == ParameterMode::Immediate
+        && (condition.value != 0) == when
+        && target.mode == ParameterMode::Immediate
+    {
+        Some(target.value as usize)
+    } else {
+        None
+    }
+}
+
+/// Points every jump which lands on an unconditional jump straight at that
+/// jump's destination.
+fn thread_jumps(
+    program: &[i32],
+    analysis: &Analysis,
+    rewritten: &mut BTreeMap<usize, Instruction>,
+) -> usize {
+    // Jumps which are patched or written over aren't always what they seem.
+    let current = |rewritten: &BTreeMap<usize, Instruction>, ptr: usize| {
+        let instruction = rewritten
+            .get(&ptr)
+            .or_else(|| analysis.instructions.get(&ptr))
+            .copied()?;
+        let size = analysis.instructions[&ptr].size();
+        if (ptr..ptr + size).any(|word| analysis.variable.contains(&word)) {
+            None
+        } else {
+            Some(instruction)
+        }
+    };
+
+    let mut threaded = 0;
+    for &ptr in analysis.instructions.keys() {
+        let instruction = match current(rewritten, ptr) {
+            Some(instruction) => instruction,
+            None => continue,
+        };
+        let target = match instruction {
+            Instruction::JumpIfTrue(_, target) | Instruction::JumpIfFalse(_, target) => target,
+            _ => continue,
+        };
+        if target.mode != ParameterMode::Immediate {
+            continue;
+        }
+
+        let mut target = target.value as usize;
+        let mut hops = 0;
+        while let Some(next) = current(rewritten, target).as_ref().and_then(unconditional) {
+            if next == target || hops == analysis.instructions.len() {
+                break;
+            }
+            target = next;
+            hops += 1;
+        }
+
+        if hops > 0 {
+            let threaded_jump = instruction.map_parameters(|n, parameter| match n {
+                1 => Parameter::immediate(target as i32),
+                _ => parameter,
+            });
+            if !analysis.rewritable(program, ptr, &threaded_jump) {
+                continue;
+            }
+            rewritten.insert(ptr, threaded_jump);
+            threaded += 1;
+        }
+    }
+
+    threaded
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::day2;
+    use crate::day5;
+    use crate::intcode::Machine;
+
+    fn run(program: &[i32], patches: &[(usize, i32)], input: &[i32]) -> (Vec<i32>, Vec<i32>) {
+        let mut machine = Machine::new(program);
+        for &(address, value) in patches {
+            machine.poke(address, value);
+        }
+        for &value in input {
+            machine.push_input(value);
+        }
+        machine.run().unwrap();
+        (machine.take_output(), machine.memory().to_vec())
+    }
+
+    #[test]
+    fn folds_constant_operands() {
+        // Outputs 3 * 4 + 5, with the 5 read from a cell nothing writes to.
+        let program = [1102, 3, 4, 13, 1, 13, 14, 13, 4, 13, 99, 0, 0, 0, 5];
+        let optimised = Optimiser::new().optimise(&program);
+        assert_eq!(optimised.folded, 2);
+        assert_eq!(&optimised.program[..8], &[1101, 12, 0, 13, 1001, 13, 5, 13]);
+        assert_eq!(run(&optimised.program, &[], &[]).0, vec![17])
+    }
+
+    #[test]
+    fn removes_writes_nothing_reads() {
+        let program = [1101, 1, 2, 9, 104, 7, 99, 0, 0, 0];
+        let optimised = Optimiser::new().optimise(&program);
+        assert_eq!(optimised.removed, 1);
+        assert_eq!(&optimised.program[..3], &[1105, 1, 4]);
+        assert_eq!(run(&optimised.program, &[], &[]).0, vec![7])
+    }
+
+    #[test]
+    fn observed_writes_are_kept() {
+        let program = [1101, 1, 2, 5, 99, 0];
+        let optimised = Optimiser::new().observed(&[5]).optimise(&program);
+        assert_eq!(optimised.removed, 0);
+        assert_eq!(run(&optimised.program, &[], &[]).1[5], 3)
+    }
+
+    #[test]
+    fn threads_constant_jumps() {
+        // A jump which is never taken, then a chain of two unconditional jumps.
+        let program = [1106, 1, 99, 1105, 1, 9, 4, 12, 99, 1105, 1, 6, 42];
+        let optimised = Optimiser::new().optimise(&program);
+        assert_eq!(&optimised.program[..3], &[1105, 1, 6]);
+        assert_eq!(optimised.threaded, 3);
+        assert_eq!(optimised.folded, 1);
+        assert_eq!(run(&optimised.program, &[], &[]).0, vec![42])
+    }
+
+    #[test]
+    fn leaves_self_modifying_code_alone() {
+        // Points its output instruction at the 42.
+        let program = [1101, 8, 0, 5, 4, 0, 99, 0, 42];
+        assert_eq!(
+            Optimiser::new().optimise(&program).program,
+            program.to_vec()
+        )
+    }
+
+    #[test]
+    fn removes_writes_overwritten_before_they_are_read() {
+        let program = [1101, 1, 2, 11, 1101, 3, 4, 11, 4, 11, 99, 0];
+        let optimised = Optimiser::new().optimise(&program);
+        assert_eq!(optimised.removed, 1);
+        assert_eq!(&optimised.program[..3], &[1105, 1, 4]);
+        assert_eq!(run(&optimised.program, &[], &[]).0, vec![7])
+    }
+
+    #[test]
+    fn folds_instructions_which_overwrite_themselves() {
+        // Writes 41 + 1 over its own destination, then outputs it.
+        let program = [1001, 9, 1, 3, 4, 3, 99, 0, 0, 41];
+        let optimised = Optimiser::new().optimise(&program);
+        assert_eq!(optimised.folded, 1);
+        assert_eq!(&optimised.program[..4], &[1101, 42, 0, 3]);
+        assert_eq!(run(&optimised.program, &[], &[]).0, vec![42])
+    }
+
+    #[test]
+    fn refuses_to_rewrite_instructions_written_later() {
+        // Points the output at 4 from the 7 at 11 to the 9 at 12 before it
+        // runs, so the output can't be folded into `104, 7`.
+        let program = [1101, 12, 0, 5, 4, 11, 99, 0, 0, 0, 0, 7, 9];
+        let optimised = Optimiser::new().optimise(&program);
+        assert_eq!(optimised.program, program.to_vec());
+        assert_eq!(run(&optimised.program, &[], &[]).0, vec![9])
+    }
+
+    #[test]
+    fn refuses_to_rewrite_words_read_as_data() {
+        // Outputs the 13 at 1, so the add can't be folded, though the output
+        // itself can.
+        let program = [1001, 13, 1, 14, 4, 1, 4, 14, 99, 0, 0, 0, 0, 5, 0];
+        let optimised = Optimiser::new().optimise(&program);
+        assert_eq!(&optimised.program[..6], &[1001, 13, 1, 14, 104, 13]);
+        assert_eq!(run(&optimised.program, &[], &[]).0, vec![13, 6])
+    }
+
+    #[test]
+    fn day2_is_optimised() {
+        let program = day2::converter(include_str!("../../input/2019/day2.txt").trim());
+        let optimised = Optimiser::new()
+            .volatile(&[1, 2])
+            .observed(&[0])
+            .optimise(&program);
+        assert!(optimised.folded > 0);
+        assert_ne!(optimised.program, program);
+        for noun in 0..=99 {
+            for verb in 0..=99 {
+                let patches = [(1, noun), (2, verb)];
+                assert_eq!(
+                    run(&program, &patches, &[]).1[0],
+                    run(&optimised.program, &patches, &[]).1[0]
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn day5_is_left_alone() {
+        // Adds its first input into the opcode at address 6 before running it.
+        let program = day5::converter(include_str!("../../input/2019/day5.txt").trim());
+        let optimised = Optimiser::new().optimise(&program);
+        assert_eq!(optimised.program, program);
+        for &input in &[1, 5] {
+            assert_eq!(
+                run(&program, &[], &[input]).0,
+                run(&optimised.program, &[], &[input]).0
+            );
+        }
+    }
+}
@@ -0,0 +1,280 @@
+use super::executor::{Executor, Io, Registers};
+use super::instruction::Instruction;
+use super::space::AddressSpace;
+use super::{IntCodeError, State};
+use std::fmt::Write;
+
+/// How often one address was used.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Counts {
+    pub reads: usize,
+    pub writes: usize,
+    /// How many instructions were run with a word at this address, so every
+    /// word of an instruction is counted, not just its opcode.
+    pub executes: usize,
+}
+
+/// Counts for every address a program has used. Addresses which were
+/// executed are taken to be code, and the rest data.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Profile {
+    counts: Vec<Counts>,
+}
+
+/// Shades for data, from least to most used.
+const DATA: &[u8] = b".:-=+*#%@";
+
+/// Shades for code, from least to most run.
+const CODE: &[u8] = b"123456789";
+
+impl Profile {
+    pub fn counts(&self) -> &[Counts] {
+        &self.counts
+    }
+
+    pub fn get(&self, address: usize) -> Counts {
+        self.counts.get(address).copied().unwrap_or_default()
+    }
+
+    fn entry(&mut self, address: usize) -> &mut Counts {
+        if address >= self.counts.len() {
+            self.counts.resize(address + 1, Counts::default());
+        }
+        &mut self.counts[address]
+    }
+
+    /// The most any code cell was run, and the most any data cell was used.
+    fn busiest(&self) -> (usize, usize) {
+        self.counts.iter().fold((0, 0), |(code, data), counts| {
+            if counts.executes > 0 {
+                (code.max(counts.executes), data)
+            } else {
+                (code, data.max(counts.reads + counts.writes))
+            }
+        })
+    }
+
+    /// Whether a cell is code, and how heavily it was used on a scale of 1
+    /// to `levels`, relative to the busiest cell of the same sort. Counts go
+    /// up in powers, so this is logarithmic. `None` if it wasn't used at all.
+    fn shade(
+        &self,
+        address: usize,
+        levels: usize,
+        busiest: (usize, usize),
+    ) -> Option<(bool, usize)> {
+        let counts = self.get(address);
+        let (code, used, most) = if counts.executes > 0 {
+            (true, counts.executes, busiest.0)
+        } else {
+            (false, counts.reads + counts.writes, busiest.1)
+        };
+        if used == 0 {
+            return None;
+        }
+        let scale = (used as f64 + 1.0).ln() / (most as f64 + 1.0).ln();
+        let level = (scale * levels as f64).ceil() as usize;
+        Some((code, level.max(1).min(levels)))
+    }
+
+    /// Draws memory `width` cells to a row, each row labelled with its first
+    /// address. Code is shaded with digits by how often it ran and data with
+    /// symbols by how often it was read or written. Unused cells are blank.
+    /// Panics if `width` is zero.
+    pub fn heatmap(&self, width: usize) -> String {
+        assert!(width > 0, "a heatmap needs at least one cell to a row");
+        let mut text = String::new();
+        writeln!(
+            text,
+            "code {} by executions, data {} by reads and writes",
+            String::from_utf8_lossy(CODE),
+            String::from_utf8_lossy(DATA)
+        )
+        .unwrap();
+        let busiest = self.busiest();
+        for start in (0..self.counts.len()).step_by(width) {
+            let row: String = (start..(start + width).min(self.counts.len()))
+                .map(|address| match self.shade(address, CODE.len(), busiest) {
+                    Some((true, level)) => CODE[level - 1] as char,
+                    Some((false, level)) => DATA[level - 1] as char,
+                    None => ' ',
+                })
+                .collect();
+            writeln!(text, "{:>6} {}", start, row.trim_end()).unwrap();
+        }
+        text
+    }
+
+    /// Draws memory as an SVG grid `width` cells across, code in blue and
+    /// data in orange, darker the more it was used. Each cell's counts show
+    /// when hovering over it. Panics if `width` is zero.
+    pub fn svg(&self, width: usize) -> String {
+        assert!(width > 0, "a grid needs at least one cell to a row");
+        const CELL: usize = 12;
+        const LEVELS: usize = 8;
+        let rows = self.counts.len().div_ceil(width);
+        let busiest = self.busiest();
+        let mut svg = String::new();
+        writeln!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}">"#,
+            width * CELL,
+            rows * CELL
+        )
+        .unwrap();
+        for (address, counts) in self.counts.iter().enumerate() {
+            let fill = match self.shade(address, LEVELS, busiest) {
+                Some((code, level)) => format!(
+                    "hsl({}, 80%, {}%)",
+                    if code { 215 } else { 30 },
+                    90 - 60 * level / LEVELS
+                ),
+                None => "#eee".to_string(),
+            };
+            writeln!(
+                svg,
+                r#"<rect x="{}" y="{}" width="{}" height="{}" fill="{}"><title>{}: {} reads, {} writes, {} executes</title></rect>"#,
+                address % width * CELL,
+                address / width * CELL,
+                CELL - 1,
+                CELL - 1,
+                fill,
+                address,
+                counts.reads,
+                counts.writes,
+                counts.executes
+            )
+            .unwrap();
+        }
+        svg.push_str("</svg>\n");
+        svg
+    }
+}
+
+/// An address space which counts reads and writes into a profile.
+struct Counting<'a, M: ?Sized> {
+    memory: &'a mut M,
+    profile: &'a mut Profile,
+}
+
+impl<'a, M: AddressSpace + ?Sized> AddressSpace for Counting<'a, M> {
+    fn len(&self) -> usize {
+        self.memory.len()
+    }
+
+    fn peek(&self, address: usize) -> Option<i32> {
+        self.memory.peek(address)
+    }
+
+    fn read(&mut self, address: usize) -> Result<i32, IntCodeError> {
+        let value = self.memory.read(address)?;
+        self.profile.entry(address).reads += 1;
+        Ok(value)
+    }
+
+    fn write(&mut self, address: usize, value: i32) -> Result<(), IntCodeError> {
+        self.memory.write(address, value)?;
+        self.profile.entry(address).writes += 1;
+        Ok(())
+    }
+}
+
+/// Wraps another executor, counting how often each address is read, written
+/// and run.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Profiling<E> {
+    pub executor: E,
+    pub profile: Profile,
+}
+
+impl<E: Executor> Profiling<E> {
+    pub fn new(executor: E) -> Profiling<E> {
+        Profiling {
+            executor,
+            profile: Profile::default(),
+        }
+    }
+}
+
+impl<E: Executor> Executor for Profiling<E> {
+    fn execute<M: AddressSpace + ?Sized>(
+        &mut self,
+        registers: &mut Registers,
+        memory: &mut M,
+        instruction: Instruction,
+        io: &mut dyn Io,
+    ) -> Result<State, IntCodeError> {
+        let ptr = registers.ptr;
+        let mut counting = Counting {
+            memory,
+            profile: &mut self.profile,
+        };
+        let state = self
+            .executor
+            .execute(registers, &mut counting, instruction, io)?;
+        if state != State::AwaitingInput {
+            for address in ptr..ptr + instruction.size() {
+                self.profile.entry(address).executes += 1;
+            }
+        }
+        Ok(state)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::intcode::executor::Standard;
+    use crate::intcode::Machine;
+
+    /// Counts down from 3 in the last cell.
+    const COUNTDOWN: [i32; 10] = [1001, 9, -1, 9, 1005, 9, 0, 99, 0, 3];
+
+    fn profile(program: &[i32]) -> Profile {
+        let mut machine = Machine::with(program.to_vec(), Profiling::new(Standard));
+        machine.run().unwrap();
+        machine.executor().profile.clone()
+    }
+
+    #[test]
+    fn counts_reads_writes_and_executes() {
+        let profile = profile(&COUNTDOWN);
+        let executes: Vec<usize> = profile.counts().iter().map(|c| c.executes).collect();
+        assert_eq!(executes, vec![3, 3, 3, 3, 3, 3, 3, 1, 0, 0]);
+        assert_eq!(
+            profile.get(9),
+            Counts {
+                reads: 6,
+                writes: 3,
+                executes: 0
+            }
+        );
+        assert_eq!(profile.get(8), Counts::default())
+    }
+
+    #[test]
+    fn draws_a_heatmap() {
+        let heatmap = profile(&COUNTDOWN).heatmap(5);
+        let rows: Vec<&str> = heatmap.lines().skip(1).collect();
+        assert_eq!(rows, vec!["     0 99999", "     5 995 @"])
+    }
+
+    #[test]
+    fn draws_an_svg_grid() {
+        let svg = profile(&COUNTDOWN).svg(5);
+        assert!(
+            svg.starts_with(r#"<svg xmlns="http://www.w3.org/2000/svg" width="60" height="24">"#)
+        );
+        assert_eq!(svg.matches("<rect").count(), 10);
+        assert!(svg.contains(
+            r#"<rect x="48" y="12" width="11" height="11" fill="hsl(30, 80%, 30%)"><title>9: 6 reads, 3 writes, 0 executes</title></rect>"#
+        ));
+        assert!(svg.contains(r##"fill="#eee"><title>8: 0 reads"##))
+    }
+
+    #[test]
+    #[should_panic(expected = "at least one cell")]
+    fn rejects_a_zero_width() {
+        profile(&COUNTDOWN).heatmap(0);
+    }
+}
@@ -0,0 +1,245 @@
+use super::protection::{Kind, Region};
+use failure::Fail;
+
+#[derive(Debug, Fail, PartialEq)]
+pub enum LoadError {
+    #[fail(display = "Value {} is not an integer: {:?}", index, token)]
+    InvalidValue { index: usize, token: String },
+
+    #[fail(display = "Line {}: not a list of integers: {}", line, text)]
+    InvalidHeader { line: usize, text: String },
+
+    #[fail(display = "Program is empty")]
+    Empty {},
+}
+
+/// What a program says about itself in the comments before its first value.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Metadata {
+    pub name: Option<String>,
+    /// Input to run the program with.
+    pub inputs: Vec<i32>,
+    /// Output it should give for that input.
+    pub outputs: Vec<i32>,
+    /// What memory should hold afterwards. Can be split over several lines.
+    pub memory: Option<Vec<i32>>,
+    /// How it should stop, if not by halting.
+    pub status: Option<String>,
+    /// Which parts of memory are code and which are data, for
+    /// `Machine::protect`.
+    pub regions: Vec<Region>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Program {
+    pub metadata: Metadata,
+    pub memory: Vec<i32>,
+}
+
+fn integers(text: &str, line: usize) -> Result<Vec<i32>, LoadError> {
+    text.split(',')
+        .map(str::trim)
+        .filter(|token| !token.is_empty())
+        .map(|token| token.parse())
+        .collect::<Result<_, _>>()
+        .map_err(|_| LoadError::InvalidHeader {
+            line,
+            text: text.to_string(),
+        })
+}
+
+/// Reads ranges like `0..10, 20..30`.
+fn regions(text: &str, kind: Kind, line: usize) -> Result<Vec<Region>, LoadError> {
+    let invalid = || LoadError::InvalidHeader {
+        line,
+        text: text.to_string(),
+    };
+    text.split(',')
+        .map(str::trim)
+        .filter(|token| !token.is_empty())
+        .map(|token| {
+            let mut ends = token.splitn(2, "..");
+            let start = ends.next().unwrap().trim().parse().map_err(|_| invalid())?;
+            let end = ends
+                .next()
+                .ok_or_else(invalid)?
+                .trim()
+                .parse()
+                .map_err(|_| invalid())?;
+            Ok(Region {
+                range: start..end,
+                kind,
+            })
+        })
+        .collect()
+}
+
+impl Program {
+    /// Reads comma-separated values, which may be spread over several lines
+    /// and surrounded by whitespace, with a trailing comma allowed. A `#`
+    /// starts a comment running to the end of the line. Comments before the
+    /// first value can give metadata, including `code`, `read-only` and
+    /// `read-write` lists of ranges like `0..10`:
+    ///
+    /// ```text
+    /// # name: Thermal radiator diagnostic
+    /// # inputs: 5
+    /// # outputs: 7704130
+    /// # status: halted
+    /// 3,225,1,225,6,6,1100,1,238,225,104,0,
+    /// ```
+    pub fn parse(text: &str) -> Result<Program, LoadError> {
+        let mut metadata = Metadata::default();
+        let mut code = String::new();
+        for (number, line) in text.lines().enumerate() {
+            let (values, comment) = match line.find('#') {
+                Some(hash) => (&line[..hash], Some(&line[hash + 1..])),
+                None => (line, None),
+            };
+            let header = code.trim().is_empty() && values.trim().is_empty();
+            if let (true, Some(comment)) = (header, comment) {
+                let mut field = comment.splitn(2, ':');
+                let key = field.next().unwrap().trim();
+                let value = field.next().map(str::trim);
+                match (key, value) {
+                    ("name", Some(name)) => metadata.name = Some(name.to_string()),
+                    ("inputs", Some(inputs)) => metadata.inputs = integers(inputs, number + 1)?,
+                    ("outputs", Some(outputs)) => metadata.outputs = integers(outputs, number + 1)?,
+                    ("memory", Some(memory)) => metadata
+                        .memory
+                        .get_or_insert_with(Vec::new)
+                        .extend(integers(memory, number + 1)?),
+                    ("status", Some(status)) => metadata.status = Some(status.to_string()),
+                    ("code", Some(ranges)) => {
+                        metadata
+                            .regions
+                            .extend(regions(ranges, Kind::Code, number + 1)?)
+                    }
+                    ("read-only", Some(ranges)) => {
+                        metadata
+                            .regions
+                            .extend(regions(ranges, Kind::ReadOnly, number + 1)?)
+                    }
+                    ("read-write", Some(ranges)) => {
+                        metadata
+                            .regions
+                            .extend(regions(ranges, Kind::ReadWrite, number + 1)?)
+                    }
+                    // Any other comment.
+                    _ => {}
+                }
+            }
+            code.push_str(values);
+            code.push('\n');
+        }
+
+        let mut tokens: Vec<&str> = code.split(',').map(str::trim).collect();
+        if tokens.len() > 1 && tokens.last() == Some(&"") {
+            tokens.pop();
+        }
+        if tokens == [""] {
+            return Err(LoadError::Empty {});
+        }
+        let memory = tokens
+            .iter()
+            .enumerate()
+            .map(|(index, token)| {
+                token.parse().map_err(|_| LoadError::InvalidValue {
+                    index,
+                    token: token.to_string(),
+                })
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Program { metadata, memory })
+    }
+}
+
+/// Reads a program's memory, ignoring any metadata.
+pub fn parse(text: &str) -> Result<Vec<i32>, LoadError> {
+    Program::parse(text).map(|program| program.memory)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn tolerates_whitespace_and_comments() {
+        let text = "  1, 0,\n0 ,3 # add\n,99,\n\n";
+        assert_eq!(parse(text), Ok(vec![1, 0, 0, 3, 99]))
+    }
+
+    #[test]
+    fn reports_bad_tokens() {
+        assert_eq!(
+            parse("1,0,0,x3,99"),
+            Err(LoadError::InvalidValue {
+                index: 3,
+                token: "x3".to_string()
+            })
+        );
+        assert_eq!(
+            parse("1,,2"),
+            Err(LoadError::InvalidValue {
+                index: 1,
+                token: "".to_string()
+            })
+        );
+        assert_eq!(parse(" # nothing\n"), Err(LoadError::Empty {}))
+    }
+
+    #[test]
+    fn reads_metadata_from_the_header() {
+        let program = Program::parse(
+            "# name: Echo\n# A program which outputs its input.\n\
+             # inputs: 7, 8\n# outputs: 7, 8\n\
+             3,9,4,9,1105,1,0,99 # outputs: 1\n",
+        )
+        .unwrap();
+        assert_eq!(
+            program.metadata,
+            Metadata {
+                name: Some("Echo".to_string()),
+                inputs: vec![7, 8],
+                outputs: vec![7, 8],
+                ..Metadata::default()
+            }
+        );
+        assert_eq!(program.memory, vec![3, 9, 4, 9, 1105, 1, 0, 99]);
+        assert_eq!(
+            Program::parse("# inputs: one\n99"),
+            Err(LoadError::InvalidHeader {
+                line: 1,
+                text: "one".to_string()
+            })
+        )
+    }
+
+    #[test]
+    fn reads_regions() {
+        let program =
+            Program::parse("# code: 0..2, 4..5\n# read-write: 2..4\n1105,1,4,0,99").unwrap();
+        let kinds: Vec<(usize, usize, Kind)> = program
+            .metadata
+            .regions
+            .iter()
+            .map(|region| (region.range.start, region.range.end, region.kind))
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                (0, 2, Kind::Code),
+                (4, 5, Kind::Code),
+                (2, 4, Kind::ReadWrite)
+            ]
+        );
+        assert_eq!(
+            Program::parse("# read-only: 3\n99"),
+            Err(LoadError::InvalidHeader {
+                line: 1,
+                text: "3".to_string()
+            })
+        )
+    }
+}
@@ -0,0 +1,243 @@
+use super::cfg::Graph;
+use super::instruction::Parameter;
+use super::{watch, IntCodeError, ParameterMode};
+use std::collections::HashSet;
+use std::ops::Range;
+
+/// What a region of memory holds, and so what the program may do with it.
+/// Any address can be read.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Kind {
+    /// Can be run but not written.
+    Code,
+    ReadOnly,
+    ReadWrite,
+}
+
+/// Something a program did to an address that it might not be allowed to.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Access {
+    Write,
+    Execute,
+}
+
+impl Kind {
+    pub fn allows(self, access: Access) -> bool {
+        match access {
+            Access::Write => self == Kind::ReadWrite,
+            Access::Execute => self == Kind::Code,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Region {
+    pub range: Range<usize>,
+    pub kind: Kind,
+}
+
+/// Which parts of memory are which. Where regions overlap the last one
+/// counts, and addresses outside every region can be used for anything.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Protection {
+    regions: Vec<Region>,
+}
+
+impl Protection {
+    pub fn new(regions: Vec<Region>) -> Protection {
+        Protection { regions }
+    }
+
+    pub fn regions(&self) -> &[Region] {
+        &self.regions
+    }
+
+    pub fn kind(&self, address: usize) -> Option<Kind> {
+        self.regions
+            .iter()
+            .rev()
+            .find(|region| region.range.contains(&address))
+            .map(|region| region.kind)
+    }
+
+    /// Fails with `IntCodeError::ProtectionFault` if the instruction at `pc`
+    /// isn't allowed to do `access` to `address`.
+    pub fn check(&self, pc: usize, address: usize, access: Access) -> Result<(), IntCodeError> {
+        match self.kind(address) {
+            Some(kind) if !kind.allows(access) => Err(IntCodeError::ProtectionFault {
+                pc,
+                address,
+                kind: access,
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Works out regions from the control-flow graph. Reachable instructions
+    /// are code, apart from any the program rewrites, which are left
+    /// unprotected, and the rest of the program is data. Data is read-only
+    /// where nothing could write to it: where no instruction writes in
+    /// position mode, and there are no writes in relative mode or through
+    /// rewritten instructions. Nothing is protected if any jump goes
+    /// somewhere that can't be worked out without running the program.
+    pub fn infer(program: &[i32]) -> Protection {
+        let graph = Graph::build(program);
+        let modified: HashSet<usize> = watch::analyse(program)
+            .iter()
+            .map(|modification| modification.address)
+            .collect();
+
+        // Code the analysis can't see could write anywhere.
+        if !graph.complete() {
+            return Protection::default();
+        }
+
+        let mut regions = vec![];
+        let mut code = HashSet::new();
+        let mut written = HashSet::new();
+        let mut unknown_writes = !modified.is_empty();
+        for (ptr, instruction) in graph.instructions(program) {
+            let range = ptr..ptr + instruction.size();
+            code.extend(range.clone());
+            if !range.clone().any(|address| modified.contains(&address)) {
+                regions.push(Region {
+                    range,
+                    kind: Kind::Code,
+                });
+            }
+            match instruction.destination() {
+                Some(Parameter {
+                    value: address,
+                    mode: ParameterMode::Position,
+                }) if address >= 0 => {
+                    written.insert(address as usize);
+                }
+                Some(_) => unknown_writes = true,
+                None => {}
+            }
+        }
+
+        for address in (0..program.len()).filter(|address| !code.contains(address)) {
+            let kind = if unknown_writes || written.contains(&address) {
+                Kind::ReadWrite
+            } else {
+                Kind::ReadOnly
+            };
+            regions.push(Region {
+                range: address..address + 1,
+                kind,
+            });
+        }
+
+        // Join neighbours of the same kind back up.
+        regions.sort_by_key(|region| region.range.start);
+        let mut joined: Vec<Region> = vec![];
+        for region in regions {
+            match joined.last_mut() {
+                Some(last) if last.range.end == region.range.start && last.kind == region.kind => {
+                    last.range.end = region.range.end
+                }
+                _ => joined.push(region),
+            }
+        }
+        Protection { regions: joined }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::intcode::program::Program;
+    use crate::intcode::{Machine, State};
+    use crate::{day2, day5};
+
+    /// Doubles its input, with the 2 kept as a constant after the code and
+    /// the answer written after that.
+    const DOUBLE: [i32; 12] = [3, 11, 2, 11, 10, 11, 4, 11, 99, 0, 2, 0];
+
+    #[test]
+    fn infers_code_and_data() {
+        let protection = Protection::infer(&DOUBLE);
+        assert_eq!(
+            protection.regions(),
+            &[
+                Region {
+                    range: 0..9,
+                    kind: Kind::Code
+                },
+                Region {
+                    range: 9..11,
+                    kind: Kind::ReadOnly
+                },
+                Region {
+                    range: 11..12,
+                    kind: Kind::ReadWrite
+                },
+            ]
+        );
+        let mut machine = Machine::new(&DOUBLE);
+        machine.protect(protection);
+        machine.push_input(21);
+        assert_eq!(machine.run().unwrap(), State::Halted);
+        assert_eq!(machine.take_output(), vec![42])
+    }
+
+    #[test]
+    fn puzzle_inputs_run_under_inferred_protection() {
+        let day2 = day2::converter(include_str!("../../input/2019/day2.txt").trim());
+        let mut machine = Machine::new(&day2);
+        machine.protect(Protection::infer(&day2));
+        machine.poke(1, 12);
+        machine.poke(2, 2);
+        assert_eq!(machine.run().unwrap(), State::Halted);
+        let mut unprotected = Machine::new(&day2);
+        unprotected.poke(1, 12);
+        unprotected.poke(2, 2);
+        unprotected.run().unwrap();
+        assert_eq!(machine.memory(), unprotected.memory());
+
+        // Day 5 rewrites an instruction with its input, so where it goes from
+        // there isn't known.
+        let day5 = day5::converter(include_str!("../../input/2019/day5.txt"));
+        assert!(Protection::infer(&day5).regions().is_empty())
+    }
+
+    #[test]
+    fn faults_on_writes_to_code() {
+        // Writes over its own halt through the relative base.
+        let program = [109, 7, 21101, 1, 1, -1, 99, 0];
+        let mut machine = Machine::new(&program);
+        machine.protect(Protection::infer(&program));
+        match machine.run() {
+            Err(IntCodeError::ProtectionFault { pc, address, kind }) => {
+                assert_eq!((pc, address, kind), (2, 6, Access::Write))
+            }
+            other => panic!("expected a protection fault, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn faults_on_running_data() {
+        let program = Program::parse("# code: 0..3\n# read-write: 3..4\n1105,1,3,99").unwrap();
+        let mut machine = Machine::new(&program.memory);
+        machine.protect(Protection::new(program.metadata.regions));
+        match machine.run() {
+            Err(IntCodeError::ProtectionFault { pc, address, kind }) => {
+                assert_eq!((pc, address, kind), (3, 3, Access::Execute))
+            }
+            other => panic!("expected a protection fault, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn leaves_rewritten_code_alone() {
+        // Turns the add at 4 into a halt before reaching it.
+        let program = [1101, 0, 99, 4, 1, 9, 9, 9, 99, 0];
+        let protection = Protection::infer(&program);
+        assert_eq!(protection.kind(0), Some(Kind::Code));
+        assert_eq!(protection.kind(4), None);
+        let mut machine = Machine::new(&program);
+        machine.protect(protection);
+        assert_eq!(machine.run().unwrap(), State::Halted)
+    }
+}
@@ -0,0 +1,248 @@
+/// Why a run stopped early.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Failure {
+    /// An unknown opcode or parameter mode.
+    InvalidInstruction,
+    /// An instruction ran off the end of memory.
+    Truncated,
+    NegativeAddress,
+    Overflow,
+    Panicked,
+    Other,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Status {
+    Halted,
+    AwaitingInput,
+    /// Still running after the number of instructions it was allowed.
+    StepLimit,
+    /// Wanted more than `MEMORY` cells.
+    MemoryLimit,
+    Failed(Failure),
+}
+
+impl From<Failure> for Status {
+    fn from(failure: Failure) -> Status {
+        Status::Failed(failure)
+    }
+}
+
+/// The most memory a run can grow to, so that a program which computes a
+/// huge address stops rather than exhausting the host.
+pub const MEMORY: usize = 1 << 20;
+
+/// Everything observable about a run. Backends which can't say what memory
+/// ended up as leave it out.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Outcome {
+    pub status: Status,
+    pub output: Vec<i32>,
+    pub memory: Option<Vec<i32>>,
+}
+
+impl Outcome {
+    /// Whether two runs look the same. Memory is only compared when both have
+    /// it, and zeros past the end of the program don't count, since whether
+    /// memory grows to cover a read is an implementation detail.
+    pub fn agrees(&self, other: &Outcome, length: usize) -> bool {
+        let trimmed = |memory: &Vec<i32>| {
+            let mut memory = memory.clone();
+            while memory.len() > length && memory.last() == Some(&0) {
+                memory.pop();
+            }
+            memory
+        };
+        let memory = match (&self.memory, &other.memory) {
+            (Some(a), Some(b)) => trimmed(a) == trimmed(b),
+            _ => true,
+        };
+        self.status == other.status && self.output == other.output && memory
+    }
+}
+
+/// A plain Intcode interpreter, written without reference to `Machine` so
+/// the two can be checked against each other. It runs for at most `limit`
+/// instructions.
+pub fn run(program: &[i32], input: &[i32], limit: usize) -> Outcome {
+    let mut computer = Computer {
+        memory: program.to_vec(),
+        pc: 0,
+        base: 0,
+        input: input.to_vec(),
+        output: vec![],
+    };
+    computer.input.reverse();
+
+    let mut steps = 0;
+    let status = loop {
+        if steps == limit {
+            break Status::StepLimit;
+        }
+        if computer.pc as usize >= computer.memory.len() {
+            break Status::Halted;
+        }
+        match computer.step() {
+            Ok(None) => steps += 1,
+            Ok(Some(status)) => break status,
+            Err(status) => break status,
+        }
+    };
+
+    Outcome {
+        status,
+        output: computer.output,
+        memory: Some(computer.memory),
+    }
+}
+
+struct Computer {
+    memory: Vec<i32>,
+    pc: i64,
+    base: i32,
+    /// Backwards, so the next value is popped off the end.
+    input: Vec<i32>,
+    output: Vec<i32>,
+}
+
+impl Computer {
+    fn word(&self, offset: usize) -> i32 {
+        self.memory[self.pc as usize + offset]
+    }
+
+    /// The address parameter `n` refers to, growing memory to include it.
+    fn address(&mut self, n: usize, mode: i32) -> Result<usize, Status> {
+        let parameter = self.word(n + 1);
+        let address = if mode == 2 {
+            parameter.checked_add(self.base).ok_or(Failure::Overflow)?
+        } else {
+            parameter
+        };
+        if address < 0 {
+            return Err(Failure::NegativeAddress.into());
+        }
+        let address = address as usize;
+        if address >= MEMORY {
+            return Err(Status::MemoryLimit);
+        }
+        if address >= self.memory.len() {
+            self.memory.resize(address + 1, 0);
+        }
+        Ok(address)
+    }
+
+    fn read(&mut self, n: usize, mode: i32) -> Result<i32, Status> {
+        if mode == 1 {
+            return Ok(self.word(n + 1));
+        }
+        let address = self.address(n, mode)?;
+        Ok(self.memory[address])
+    }
+
+    /// Runs one instruction, returning a status if the program stopped
+    /// without failing.
+    fn step(&mut self) -> Result<Option<Status>, Status> {
+        let head = self.word(0);
+        let opcode = head % 100;
+        let modes = [(head / 100) % 10, (head / 1_000) % 10, (head / 10_000) % 10];
+        if modes.iter().any(|mode| !(0..=2).contains(mode)) {
+            return Err(Failure::InvalidInstruction.into());
+        }
+        let parameters = match opcode {
+            1 | 2 | 7 | 8 => 3,
+            5 | 6 => 2,
+            3 | 4 | 9 => 1,
+            99 => return Ok(Some(Status::Halted)),
+            _ => return Err(Failure::InvalidInstruction.into()),
+        };
+        if self.pc as usize + parameters >= self.memory.len() {
+            return Err(Failure::Truncated.into());
+        }
+        if opcode == 3 && self.input.is_empty() {
+            return Ok(Some(Status::AwaitingInput));
+        }
+
+        let next = self.pc + parameters as i64 + 1;
+        match opcode {
+            1 | 2 | 7 | 8 => {
+                let a = self.read(0, modes[0])?;
+                let b = self.read(1, modes[1])?;
+                let target = self.address(2, modes[2])?;
+                self.memory[target] = match opcode {
+                    1 => a.checked_add(b).ok_or(Failure::Overflow)?,
+                    2 => a.checked_mul(b).ok_or(Failure::Overflow)?,
+                    7 => (a < b) as i32,
+                    _ => (a == b) as i32,
+                };
+                self.pc = next;
+            }
+            3 => {
+                let target = self.address(0, modes[0])?;
+                self.memory[target] = self.input.pop().unwrap();
+                self.pc = next;
+            }
+            4 => {
+                let value = self.read(0, modes[0])?;
+                self.output.push(value);
+                self.pc = next;
+            }
+            5 | 6 => {
+                let condition = self.read(0, modes[0])?;
+                let destination = self.read(1, modes[1])?;
+                self.pc = if (condition != 0) == (opcode == 5) {
+                    if destination < 0 {
+                        return Err(Failure::NegativeAddress.into());
+                    }
+                    destination as i64
+                } else {
+                    next
+                };
+            }
+            _ => {
+                let adjustment = self.read(0, modes[0])?;
+                self.base = self.base.checked_add(adjustment).ok_or(Failure::Overflow)?;
+                self.pc = next;
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::day5;
+
+    #[test]
+    fn runs_day5_diagnostics() {
+        let program = day5::converter(include_str!("../../input/2019/day5.txt").trim());
+        let outcome = run(&program, &[1], 10_000);
+        assert_eq!(outcome.status, Status::Halted);
+        assert!(outcome.output[..outcome.output.len() - 1]
+            .iter()
+            .all(|&o| o == 0))
+    }
+
+    #[test]
+    fn relative_mode_quine() {
+        let quine = vec![
+            109, 1, 204, -1, 1001, 100, 1, 100, 1008, 100, 16, 101, 1006, 101, 0, 99,
+        ];
+        let outcome = run(&quine, &[], 10_000);
+        assert_eq!(outcome.output, quine)
+    }
+
+    #[test]
+    fn stops_at_the_step_limit() {
+        let outcome = run(&[1105, 1, 0], &[], 10);
+        assert_eq!(outcome.status, Status::StepLimit)
+    }
+
+    #[test]
+    fn stops_at_the_memory_limit() {
+        let outcome = run(&[1101, 1, 2, MEMORY as i32, 99], &[], 10);
+        assert_eq!(outcome.status, Status::MemoryLimit);
+        assert_eq!(outcome.memory.unwrap().len(), 5)
+    }
+}
@@ -0,0 +1,210 @@
+use super::{IntCodeError, Machine, State};
+use failure::Fail;
+use std::fmt;
+
+#[derive(Debug, Fail)]
+pub enum ReplayError {
+    #[fail(display = "Line {}: not a replay event: {}", line, text)]
+    InvalidEvent { line: usize, text: String },
+
+    #[fail(
+        display = "Event {} differs: expected {:?}, got {:?}",
+        index, expected, actual
+    )]
+    Mismatch {
+        index: usize,
+        expected: Option<Event>,
+        actual: Option<Event>,
+    },
+
+    #[fail(display = "{}", _0)]
+    IntCode(#[cause] IntCodeError),
+}
+
+impl From<IntCodeError> for ReplayError {
+    fn from(error: IntCodeError) -> ReplayError {
+        ReplayError::IntCode(error)
+    }
+}
+
+/// A value going into or out of a machine, along with how many instructions
+/// had run before the one which read or wrote it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Event {
+    Input { step: usize, value: i32 },
+    Output { step: usize, value: i32 },
+}
+
+/// Everything a machine read and wrote, in order.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Recording {
+    pub events: Vec<Event>,
+}
+
+impl Recording {
+    pub fn inputs(&self) -> impl Iterator<Item = i32> + '_ {
+        self.events.iter().filter_map(|event| match event {
+            Event::Input { value, .. } => Some(*value),
+            _ => None,
+        })
+    }
+
+    /// Reads the format written by `Display`: one event per line, `in` or
+    /// `out` followed by the step and the value. Blank lines and lines
+    /// starting with `#` are skipped.
+    pub fn parse(text: &str) -> Result<Recording, ReplayError> {
+        let mut events = vec![];
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let invalid = || ReplayError::InvalidEvent {
+                line: number + 1,
+                text: line.to_string(),
+            };
+
+            let words: Vec<&str> = line.split_whitespace().collect();
+            let (step, value) = match words[1..] {
+                [step, value] => (
+                    step.parse().map_err(|_| invalid())?,
+                    value.parse().map_err(|_| invalid())?,
+                ),
+                _ => return Err(invalid()),
+            };
+            events.push(match words[0] {
+                "in" => Event::Input { step, value },
+                "out" => Event::Output { step, value },
+                _ => return Err(invalid()),
+            });
+        }
+
+        Ok(Recording { events })
+    }
+}
+
+impl fmt::Display for Recording {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for event in &self.events {
+            match event {
+                Event::Input { step, value } => writeln!(f, "in {} {}", step, value)?,
+                Event::Output { step, value } => writeln!(f, "out {} {}", step, value)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Runs `program` again with the inputs from `recording`, checking that it
+/// reads and writes the same values at the same points. Stops when the
+/// program halts or wants more input than was recorded.
+pub fn replay(program: &[i32], recording: &Recording) -> Result<(), ReplayError> {
+    let mut machine = Machine::new(program);
+    machine.record();
+    for value in recording.inputs() {
+        machine.push_input(value);
+    }
+
+    let mut checked = 0;
+    loop {
+        let state = machine.step()?;
+        let events = &machine.recording().unwrap().events;
+        for (index, &actual) in events.iter().enumerate().skip(checked) {
+            let expected = recording.events.get(index).copied();
+            if expected != Some(actual) {
+                return Err(ReplayError::Mismatch {
+                    index,
+                    expected,
+                    actual: Some(actual),
+                });
+            }
+        }
+        checked = events.len();
+        if state != State::Running {
+            break;
+        }
+    }
+
+    match recording.events.get(checked) {
+        Some(&expected) => Err(ReplayError::Mismatch {
+            index: checked,
+            expected: Some(expected),
+            actual: None,
+        }),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::day5;
+
+    /// Adds up its input until it reads a zero, outputting the running total.
+    const TOTAL: [i32; 17] = [
+        3, 15, 1006, 15, 14, 1, 15, 16, 16, 4, 16, 1105, 1, 0, 99, 0, 0,
+    ];
+
+    fn session(program: &[i32], inputs: &[i32]) -> Recording {
+        let mut machine = Machine::new(program);
+        machine.record();
+        for &input in inputs {
+            machine.run().unwrap();
+            machine.push_input(input);
+        }
+        machine.run().unwrap();
+        machine.recording().unwrap().clone()
+    }
+
+    #[test]
+    fn records_inputs_and_outputs_with_step_counts() {
+        let recording = session(&TOTAL, &[2, 3]);
+        assert_eq!(
+            recording.events,
+            vec![
+                Event::Input { step: 0, value: 2 },
+                Event::Output { step: 3, value: 2 },
+                Event::Input { step: 5, value: 3 },
+                Event::Output { step: 8, value: 5 },
+            ]
+        );
+        assert!(replay(&TOTAL, &recording).is_ok())
+    }
+
+    #[test]
+    fn replays_from_text() {
+        let program = day5::converter(include_str!("../../input/2019/day5.txt"));
+        let text = session(&program, &[5]).to_string();
+        assert!(text.starts_with("in 0 5\nout "));
+        assert!(replay(&program, &Recording::parse(&text).unwrap()).is_ok())
+    }
+
+    #[test]
+    fn reports_the_first_difference() {
+        let program = day5::converter(include_str!("../../input/2019/day5.txt"));
+        let mut recording = session(&program, &[1]);
+        let last = recording.events.len() - 1;
+        if let Event::Output { value, .. } = &mut recording.events[last] {
+            *value += 1;
+        }
+        match replay(&program, &recording) {
+            Err(ReplayError::Mismatch { index, .. }) => assert_eq!(index, last),
+            other => panic!("expected a mismatch, got {:?}", other),
+        }
+
+        let mut longer = session(&program, &[1]);
+        longer.events.push(Event::Output { step: 0, value: 0 });
+        match replay(&program, &longer) {
+            Err(ReplayError::Mismatch { actual: None, .. }) => {}
+            other => panic!("expected a missing event, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn rejects_malformed_files() {
+        match Recording::parse("# header\nin 0 1\nsideways 3 4\n") {
+            Err(ReplayError::InvalidEvent { line, .. }) => assert_eq!(line, 3),
+            other => panic!("expected an invalid event, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,252 @@
+use super::{IntCodeError, Machine, State};
+use std::ops::RangeInclusive;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc};
+use std::thread;
+
+const DEFAULT_THREADS: usize = 4;
+
+/// One run to try: values written into the program before it starts, and the
+/// input it's given.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Trial {
+    pub patches: Vec<(usize, i32)>,
+    pub input: Vec<i32>,
+}
+
+impl Trial {
+    pub fn patch(patches: &[(usize, i32)]) -> Trial {
+        Trial {
+            patches: patches.to_vec(),
+            input: vec![],
+        }
+    }
+
+    pub fn input(input: &[i32]) -> Trial {
+        Trial {
+            patches: vec![],
+            input: input.to_vec(),
+        }
+    }
+}
+
+/// Every way of patching `addresses` with values from `values`, with the last
+/// address changing fastest.
+pub fn patch_sweep(addresses: &[usize], values: RangeInclusive<i32>) -> Vec<Trial> {
+    let mut trials = vec![Trial::default()];
+    for &address in addresses {
+        trials = trials
+            .iter()
+            .flat_map(|trial| {
+                values.clone().map(move |value| {
+                    let mut trial = trial.clone();
+                    trial.patches.push((address, value));
+                    trial
+                })
+            })
+            .collect();
+    }
+
+    trials
+}
+
+/// How a trial finished.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Outcome {
+    pub state: State,
+    pub memory: Vec<i32>,
+    pub output: Vec<i32>,
+}
+
+type Report = (usize, Option<Outcome>);
+
+/// Runs many variations of a program across a pool of threads. Whatever the
+/// number of threads, matches come back in the order the trials were given,
+/// and `first` always finds the earliest one. Plenty of variations are
+/// expected to crash, so a trial which fails just doesn't match.
+pub struct Search {
+    program: Arc<Vec<i32>>,
+    threads: usize,
+}
+
+impl Search {
+    pub fn new(program: &[i32]) -> Search {
+        Search {
+            program: Arc::new(program.to_vec()),
+            threads: DEFAULT_THREADS,
+        }
+    }
+
+    pub fn threads(mut self, threads: usize) -> Search {
+        self.threads = threads.max(1);
+        self
+    }
+
+    pub fn first<P>(&self, trials: Vec<Trial>, predicate: P) -> Option<(Trial, Outcome)>
+    where
+        P: Fn(&Outcome) -> bool + Send + Sync + 'static,
+    {
+        self.sweep(trials, predicate, true).into_iter().next()
+    }
+
+    pub fn all<P>(&self, trials: Vec<Trial>, predicate: P) -> Vec<(Trial, Outcome)>
+    where
+        P: Fn(&Outcome) -> bool + Send + Sync + 'static,
+    {
+        self.sweep(trials, predicate, false)
+    }
+
+    /// Hands trials out to workers in order. When only the first match is
+    /// wanted, workers give up on anything after the earliest trial known to
+    /// match, but everything before it still runs, so the result doesn't
+    /// depend on which thread got there first.
+    fn sweep<P>(&self, trials: Vec<Trial>, predicate: P, first_only: bool) -> Vec<(Trial, Outcome)>
+    where
+        P: Fn(&Outcome) -> bool + Send + Sync + 'static,
+    {
+        let trials = Arc::new(trials);
+        let predicate = Arc::new(predicate);
+        let next = Arc::new(AtomicUsize::new(0));
+        let stop = Arc::new(AtomicUsize::new(usize::MAX));
+        let (sender, receiver) = mpsc::channel::<Report>();
+
+        let workers: Vec<_> = (0..self.threads)
+            .map(|_| {
+                let program = Arc::clone(&self.program);
+                let trials = Arc::clone(&trials);
+                let predicate = Arc::clone(&predicate);
+                let next = Arc::clone(&next);
+                let stop = Arc::clone(&stop);
+                let sender = sender.clone();
+                thread::spawn(move || {
+                    let mut machine = Machine::new(&program);
+                    loop {
+                        let index = next.fetch_add(1, Ordering::SeqCst);
+                        if index >= trials.len() || index > stop.load(Ordering::SeqCst) {
+                            return;
+                        }
+
+                        let result = attempt(&mut machine, &program, &trials[index])
+                            .ok()
+                            .filter(|outcome| predicate(outcome));
+                        if first_only && result.is_some() {
+                            stop.fetch_min(index, Ordering::SeqCst);
+                        }
+                        if sender.send((index, result)).is_err() {
+                            return;
+                        }
+                    }
+                })
+            })
+            .collect();
+        drop(sender);
+
+        let mut reports: Vec<Report> = receiver.iter().collect();
+        for worker in workers {
+            worker.join().expect("search worker panicked");
+        }
+        reports.sort_by_key(|&(index, _)| index);
+
+        let mut matches = vec![];
+        for (index, result) in reports {
+            if let Some(outcome) = result {
+                matches.push((trials[index].clone(), outcome));
+                if first_only {
+                    break;
+                }
+            }
+        }
+
+        matches
+    }
+}
+
+fn attempt(machine: &mut Machine, program: &[i32], trial: &Trial) -> Result<Outcome, IntCodeError> {
+    machine.reset(program);
+    for &(address, value) in &trial.patches {
+        machine.poke(address, value);
+    }
+    for &value in &trial.input {
+        machine.push_input(value);
+    }
+    let state = machine.run()?;
+
+    Ok(Outcome {
+        state,
+        memory: machine.memory().to_vec(),
+        output: machine.take_output(),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // Adds the values at 5 and 6 into 0.
+    const SUM: [i32; 7] = [1, 5, 6, 0, 99, 0, 0];
+
+    #[test]
+    fn patch_sweep_changes_last_address_fastest() {
+        let trials = patch_sweep(&[1, 2], 0..=1);
+        let patches: Vec<_> = trials.into_iter().map(|t| t.patches).collect();
+        assert_eq!(
+            patches,
+            vec![
+                vec![(1, 0), (2, 0)],
+                vec![(1, 0), (2, 1)],
+                vec![(1, 1), (2, 0)],
+                vec![(1, 1), (2, 1)],
+            ]
+        )
+    }
+
+    #[test]
+    fn first_match_is_the_same_for_any_number_of_threads() {
+        for &threads in &[1, 2, 3, 8] {
+            let (trial, outcome) = Search::new(&SUM)
+                .threads(threads)
+                .first(patch_sweep(&[5, 6], 0..=4), |o| o.memory[0] == 5)
+                .unwrap();
+            assert_eq!(trial.patches, vec![(5, 1), (6, 4)]);
+            assert_eq!(outcome.state, State::Halted);
+        }
+    }
+
+    #[test]
+    fn all_matches_come_back_in_order() {
+        for &threads in &[1, 5] {
+            let matches = Search::new(&SUM)
+                .threads(threads)
+                .all(patch_sweep(&[5, 6], 0..=4), |o| o.memory[0] == 5);
+            let nouns: Vec<i32> = matches.iter().map(|(t, _)| t.patches[0].1).collect();
+            assert_eq!(nouns, vec![1, 2, 3, 4]);
+        }
+    }
+
+    #[test]
+    fn trials_can_supply_input() {
+        let echo = [3, 0, 4, 0, 99];
+        let trials = (0..10).map(|n| Trial::input(&[n])).collect();
+        let (trial, _) = Search::new(&echo)
+            .first(trials, |o| o.output == vec![7])
+            .unwrap();
+        assert_eq!(trial.input, vec![7])
+    }
+
+    #[test]
+    fn trials_which_fail_do_not_match() {
+        let trials: Vec<Trial> = [50, 0, 1, 2]
+            .iter()
+            .map(|&op| Trial::patch(&[(0, op)]))
+            .collect();
+        for &threads in &[1, 3] {
+            let search = Search::new(&SUM).threads(threads);
+            let (trial, _) = search.first(trials.clone(), |_| true).unwrap();
+            assert_eq!(trial.patches, vec![(0, 1)]);
+            let ops: Vec<i32> = (search.all(trials.clone(), |_| true).iter())
+                .map(|(t, _)| t.patches[0].1)
+                .collect();
+            assert_eq!(ops, vec![1, 2]);
+        }
+    }
+}
@@ -0,0 +1,516 @@
+use super::executor::Registers;
+use super::instruction::Instruction;
+use super::program::{LoadError, Program};
+use super::{IntCodeError, Machine, State};
+use failure::Fail;
+use std::fmt::Write;
+use std::fs;
+
+#[derive(Debug, Fail)]
+pub enum ShellError {
+    #[fail(display = "Unknown command: {} (try help)", command)]
+    UnknownCommand { command: String },
+
+    #[fail(display = "Usage: {}", usage)]
+    Usage { usage: &'static str },
+
+    #[fail(display = "Couldn't access {}: {}", file, message)]
+    Io { file: String, message: String },
+
+    #[fail(display = "{}", _0)]
+    Load(#[cause] LoadError),
+
+    #[fail(display = "Not a snapshot: {}", line)]
+    InvalidSnapshot { line: String },
+
+    #[fail(display = "No command {} in the history", index)]
+    NoHistory { index: usize },
+
+    #[fail(display = "{}", _0)]
+    IntCode(#[cause] IntCodeError),
+}
+
+impl From<IntCodeError> for ShellError {
+    fn from(error: IntCodeError) -> ShellError {
+        ShellError::IntCode(error)
+    }
+}
+
+impl From<LoadError> for ShellError {
+    fn from(error: LoadError) -> ShellError {
+        ShellError::Load(error)
+    }
+}
+
+const HELP: &str = "\
+load FILE          load a program, comments and all
+reset              start the program again
+input VALUE...     queue input
+run                run until the program halts or wants input
+step [COUNT]       run one instruction, or COUNT, showing each
+peek ADDRESS [N]   show N cells, or one
+poke ADDRESS VALUE change a cell
+output             show all output so far
+dis [FROM [TO]]    disassemble, by default the next few instructions
+regs               show the instruction pointer and relative base
+save FILE          write a snapshot of the machine
+restore FILE       go back to a snapshot
+history            list earlier commands; !N runs one again
+quit";
+
+/// How many instructions `dis` shows when not told where to stop.
+const DISASSEMBLY: usize = 8;
+
+/// The most cells `peek` shows at once.
+const PEEK_LIMIT: usize = 1_000;
+
+/// Values separated by commas or spaces.
+fn values(words: &[&str]) -> Option<Vec<i32>> {
+    words
+        .iter()
+        .flat_map(|word| word.split(','))
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(|value| value.parse().ok())
+        .collect()
+}
+
+fn join(values: &[i32]) -> String {
+    values
+        .iter()
+        .map(|value| value.to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// An interactive session with one machine. Each command gives back the
+/// text to show for it.
+pub struct Shell {
+    program: Vec<i32>,
+    machine: Machine,
+    output: Vec<i32>,
+    history: Vec<String>,
+    finished: bool,
+}
+
+impl Default for Shell {
+    fn default() -> Shell {
+        Shell {
+            program: vec![],
+            machine: Machine::new(&[]),
+            output: vec![],
+            history: vec![],
+            finished: false,
+        }
+    }
+}
+
+impl Shell {
+    pub fn new() -> Shell {
+        Shell::default()
+    }
+
+    pub fn machine(&self) -> &Machine {
+        &self.machine
+    }
+
+    /// Whether `quit` has been given.
+    pub fn finished(&self) -> bool {
+        self.finished
+    }
+
+    /// Runs one line. Blank lines and `#` comments do nothing.
+    pub fn execute(&mut self, line: &str) -> Result<String, ShellError> {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            return Ok(String::new());
+        }
+        if let Some(index) = line.strip_prefix('!') {
+            let index: usize = index
+                .parse()
+                .map_err(|_| ShellError::Usage { usage: "!N" })?;
+            let line = index
+                .checked_sub(1)
+                .and_then(|i| self.history.get(i))
+                .ok_or(ShellError::NoHistory { index })?
+                .clone();
+            return self.execute(&line);
+        }
+        self.history.push(line.to_string());
+
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let arguments = &words[1..];
+        let number = |n: usize, usage| {
+            arguments
+                .get(n)
+                .and_then(|word| word.parse::<i64>().ok())
+                .ok_or(ShellError::Usage { usage })
+        };
+        let file = |usage| {
+            arguments
+                .first()
+                .copied()
+                .ok_or(ShellError::Usage { usage })
+        };
+
+        match words[0] {
+            "load" => {
+                let file = file("load FILE")?;
+                let program = Program::parse(&read(file)?)?;
+                self.program = program.memory;
+                self.reset();
+                Ok(format!("Loaded {} words from {}", self.program.len(), file))
+            }
+            "reset" => {
+                self.reset();
+                Ok("Reset".to_string())
+            }
+            "input" => {
+                let values = values(arguments)
+                    .filter(|values| !values.is_empty())
+                    .ok_or(ShellError::Usage {
+                        usage: "input VALUE...",
+                    })?;
+                for &value in &values {
+                    self.machine.push_input(value);
+                }
+                Ok(format!("Queued {}", join(&values)))
+            }
+            "run" => {
+                let state = self.machine.run()?;
+                Ok(self.report(String::new(), state))
+            }
+            "step" => {
+                let count = match arguments.first() {
+                    Some(_) => number(0, "step [COUNT]")?.max(0) as usize,
+                    None => 1,
+                };
+                let mut text = String::new();
+                let mut state = State::Running;
+                for _ in 0..count {
+                    let pc = self.machine.registers().ptr;
+                    if let Ok(instruction) = Instruction::decode(self.machine.address_space(), pc) {
+                        writeln!(text, "{:>6}  {}", pc, instruction).unwrap();
+                    }
+                    state = self.machine.step()?;
+                    if state != State::Running {
+                        break;
+                    }
+                }
+                Ok(self.report(text, state))
+            }
+            "peek" => {
+                let usage = "peek ADDRESS [N]";
+                let address = number(0, usage)?;
+                let count = match arguments.get(1) {
+                    Some(_) => number(1, usage)?.clamp(0, PEEK_LIMIT as i64) as usize,
+                    None => 1,
+                };
+                if address < 0 {
+                    return Err(IntCodeError::NegativeAddressError {
+                        address: address as i32,
+                    }
+                    .into());
+                }
+                let address = address as usize;
+                let end = address
+                    .checked_add(count)
+                    .ok_or(ShellError::Usage { usage })?;
+                let cells: Vec<i32> = (address..end)
+                    .map(|a| self.machine.memory().get(a).copied().unwrap_or(0))
+                    .collect();
+                Ok(format!("{}: {}", address, join(&cells)))
+            }
+            "poke" => {
+                let usage = "poke ADDRESS VALUE";
+                let address = number(0, usage)?;
+                let value = (arguments.get(1))
+                    .and_then(|word| word.parse::<i32>().ok())
+                    .ok_or(ShellError::Usage { usage })?;
+                if address < 0 {
+                    return Err(ShellError::Usage { usage });
+                }
+                self.machine.poke(address as usize, value);
+                Ok(format!("{}: {}", address, value))
+            }
+            "output" => Ok(join(&self.output)),
+            "dis" => {
+                let usage = "dis [FROM [TO]]";
+                let from = match arguments.first() {
+                    Some(_) => number(0, usage)?.max(0) as usize,
+                    None => self.machine.registers().ptr,
+                };
+                let to = match arguments.get(1) {
+                    Some(_) => Some(number(1, usage)?.max(0) as usize),
+                    None => None,
+                };
+                Ok(self.disassemble(from, to))
+            }
+            "regs" => {
+                let registers = self.machine.registers();
+                Ok(format!(
+                    "pc {}  rb {}  steps {}",
+                    registers.ptr,
+                    registers.relative_base,
+                    self.machine.steps()
+                ))
+            }
+            "save" => {
+                let file = file("save FILE")?;
+                fs::write(file, self.snapshot()).map_err(|error| ShellError::Io {
+                    file: file.to_string(),
+                    message: error.to_string(),
+                })?;
+                Ok(format!("Saved to {}", file))
+            }
+            "restore" => {
+                let file = file("restore FILE")?;
+                self.restore(&read(file)?)?;
+                Ok(format!("Restored from {}", file))
+            }
+            "history" => Ok(self
+                .history
+                .iter()
+                .enumerate()
+                .map(|(i, line)| format!("{:>4}  {}", i + 1, line))
+                .collect::<Vec<_>>()
+                .join("\n")),
+            "help" => Ok(HELP.to_string()),
+            "quit" | "exit" => {
+                self.finished = true;
+                Ok(String::new())
+            }
+            command => Err(ShellError::UnknownCommand {
+                command: command.to_string(),
+            }),
+        }
+    }
+
+    fn reset(&mut self) {
+        self.machine = Machine::new(&self.program);
+        self.output.clear();
+    }
+
+    /// Collects any new output, then says how the machine stopped.
+    fn report(&mut self, mut text: String, state: State) -> String {
+        let output = self.machine.take_output();
+        if !output.is_empty() {
+            writeln!(text, "out: {}", join(&output)).unwrap();
+            self.output.extend(output);
+        }
+        text.push_str(match state {
+            State::Running => "running",
+            State::AwaitingInput => "awaiting input",
+            State::Halted => "halted",
+        });
+        text
+    }
+
+    /// Shows each instruction from `from`, up to `to` or for a few
+    /// instructions. Words that aren't instructions are shown as data.
+    fn disassemble(&self, from: usize, to: Option<usize>) -> String {
+        let memory = self.machine.address_space();
+        let pc = self.machine.registers().ptr;
+        let end = to.unwrap_or(memory.len()).min(memory.len());
+        let mut lines = vec![];
+        let mut ptr = from;
+        while ptr < end && (to.is_some() || lines.len() < DISASSEMBLY) {
+            let (size, text) = match Instruction::decode(memory, ptr) {
+                Ok(instruction) => (instruction.size(), instruction.to_string()),
+                Err(_) => (1, format!("data {}", memory[ptr])),
+            };
+            let words = join(&memory[ptr..ptr + size]);
+            let marker = if ptr == pc { ">" } else { " " };
+            lines.push(format!("{} {:>5}  {:<24} {}", marker, ptr, words, text));
+            ptr += size;
+        }
+        lines.join("\n")
+    }
+
+    /// Everything needed to carry on from here later, apart from the step
+    /// count.
+    fn snapshot(&self) -> String {
+        let registers = self.machine.registers();
+        format!(
+            "pc: {}\nrb: {}\ninput: {}\noutput: {}\nmemory: {}\n",
+            registers.ptr,
+            registers.relative_base,
+            join(&self.machine.pending_input()),
+            join(&self.output),
+            join(self.machine.memory())
+        )
+    }
+
+    fn restore(&mut self, snapshot: &str) -> Result<(), ShellError> {
+        let mut registers = Registers::default();
+        let (mut input, mut output, mut memory) = (vec![], vec![], None);
+        for line in snapshot.lines().filter(|line| !line.trim().is_empty()) {
+            let invalid = || ShellError::InvalidSnapshot {
+                line: line.to_string(),
+            };
+            let mut field = line.splitn(2, ':');
+            let key = field.next().unwrap().trim();
+            let value = field.next().ok_or_else(invalid)?.trim();
+            let list = || values(&[value]).ok_or_else(invalid);
+            match key {
+                "pc" => registers.ptr = value.parse().map_err(|_| invalid())?,
+                "rb" => registers.relative_base = value.parse().map_err(|_| invalid())?,
+                "input" => input = list()?,
+                "output" => output = list()?,
+                "memory" => memory = Some(list()?),
+                _ => return Err(invalid()),
+            }
+        }
+        let memory = memory.ok_or(ShellError::InvalidSnapshot {
+            line: "no memory".to_string(),
+        })?;
+
+        self.machine = Machine::new(&memory);
+        self.machine.set_registers(registers);
+        for value in input {
+            self.machine.push_input(value);
+        }
+        self.output = output;
+        Ok(())
+    }
+}
+
+fn read(file: &str) -> Result<String, ShellError> {
+    fs::read_to_string(file).map_err(|error| ShellError::Io {
+        file: file.to_string(),
+        message: error.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Runs each line of `script`, joining what they show.
+    fn script(shell: &mut Shell, script: &str) -> String {
+        script
+            .lines()
+            .map(|line| shell.execute(line).unwrap())
+            .filter(|text| !text.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn temporary(name: &str) -> String {
+        let mut path = std::env::temp_dir();
+        path.push(format!("intcode-shell-{}-{}", std::process::id(), name));
+        path.to_string_lossy().to_string()
+    }
+
+    #[test]
+    fn loads_runs_and_inspects_a_program() {
+        let file = temporary("echo.int");
+        fs::write(&file, "# name: Echo\n3,9,4,9,1105,1,0,99,0,0").unwrap();
+        let mut shell = Shell::new();
+        let text = script(
+            &mut shell,
+            &format!(
+                "load {}\n# comments are ignored\ninput 7, 8\nrun\npeek 9\npoke 9 -2\nstep 2\noutput\nregs",
+                file
+            ),
+        );
+        fs::remove_file(&file).unwrap();
+        let expected = format!(
+            "Loaded 10 words from {}
+Queued 7, 8
+out: 7, 8
+awaiting input
+9: 8
+9: -2
+     0  in [9]
+awaiting input
+7, 8
+pc 0  rb 0  steps 6",
+            file
+        );
+        assert_eq!(text, expected)
+    }
+
+    #[test]
+    fn disassembles() {
+        let mut shell = Shell::new();
+        shell.program = vec![1002, 4, 3, 4, 33, 109, -1];
+        shell.reset();
+        let expected = "\
+>     0  1002, 4, 3, 4            mul [4], 3, [4]
+      4  33                       data 33
+      5  109, -1                  arb -1";
+        assert_eq!(shell.execute("dis").unwrap(), expected);
+        assert_eq!(
+            shell.execute("dis 4 5").unwrap(),
+            "      4  33                       data 33"
+        )
+    }
+
+    #[test]
+    fn saves_and_restores_snapshots() {
+        let file = temporary("snapshot");
+        let mut shell = Shell::new();
+        shell.program = vec![3, 9, 4, 9, 1105, 1, 0, 99, 0, 0];
+        shell.reset();
+        script(
+            &mut shell,
+            &format!("input 1 2\nstep 2\nsave {}\nrun", file),
+        );
+        assert_eq!(shell.execute("output").unwrap(), "1, 2");
+
+        shell.execute(&format!("restore {}", file)).unwrap();
+        fs::remove_file(&file).unwrap();
+        assert_eq!(shell.execute("output").unwrap(), "1");
+        assert_eq!(shell.machine().pending_input(), vec![2]);
+        assert_eq!(shell.machine().registers().ptr, 4);
+        assert_eq!(shell.execute("run").unwrap(), "out: 2\nawaiting input")
+    }
+
+    #[test]
+    fn repeats_commands_from_the_history() {
+        let mut shell = Shell::new();
+        shell.program = vec![104, 1, 1105, 1, 0];
+        shell.reset();
+        script(&mut shell, "step\nregs");
+        assert_eq!(
+            shell.execute("history").unwrap(),
+            "   1  step\n   2  regs\n   3  history"
+        );
+        assert_eq!(shell.execute("!1").unwrap(), "     2  jt 1, 0\nrunning");
+        assert_eq!(shell.execute("!2").unwrap(), "pc 0  rb 0  steps 2");
+        match shell.execute("!9") {
+            Err(ShellError::NoHistory { index }) => assert_eq!(index, 9),
+            other => panic!("expected a missing history entry, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn reports_mistakes() {
+        let mut shell = Shell::new();
+        assert!(matches!(
+            shell.execute("frobnicate"),
+            Err(ShellError::UnknownCommand { .. })
+        ));
+        assert!(matches!(
+            shell.execute("poke 1"),
+            Err(ShellError::Usage { .. })
+        ));
+        assert!(matches!(
+            shell.execute("poke 1 4294967296"),
+            Err(ShellError::Usage { .. })
+        ));
+        assert_eq!(
+            shell.execute("peek 9223372036854775807 2").unwrap(),
+            "9223372036854775807: 0, 0"
+        );
+        let cells = shell.execute("peek 0 1000000000").unwrap();
+        assert_eq!(cells.split(',').count(), PEEK_LIMIT);
+        assert!(matches!(
+            shell.execute("load /nonexistent/program"),
+            Err(ShellError::Io { .. })
+        ));
+        shell.execute("quit").unwrap();
+        assert!(shell.finished())
+    }
+}
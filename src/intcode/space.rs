@@ -0,0 +1,245 @@
+use super::IntCodeError;
+use std::collections::HashMap;
+use std::ops::Range;
+
+/// Somewhere an Intcode program's memory lives. Reading or writing past the
+/// end makes it bigger, as if memory went on forever full of zeros.
+pub trait AddressSpace {
+    /// One past the highest address used so far.
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Looks at a cell without counting as a read or growing anything, for
+    /// decoding instructions. `None` past the end.
+    fn peek(&self, address: usize) -> Option<i32>;
+
+    fn read(&mut self, address: usize) -> Result<i32, IntCodeError>;
+
+    fn write(&mut self, address: usize, value: i32) -> Result<(), IntCodeError>;
+}
+
+impl AddressSpace for Vec<i32> {
+    fn len(&self) -> usize {
+        self.as_slice().len()
+    }
+
+    fn peek(&self, address: usize) -> Option<i32> {
+        self.get(address).copied()
+    }
+
+    fn read(&mut self, address: usize) -> Result<i32, IntCodeError> {
+        if address >= self.as_slice().len() {
+            self.resize(address + 1, 0);
+        }
+        Ok(self[address])
+    }
+
+    fn write(&mut self, address: usize, value: i32) -> Result<(), IntCodeError> {
+        if address >= self.as_slice().len() {
+            self.resize(address + 1, 0);
+        }
+        self[address] = value;
+        Ok(())
+    }
+}
+
+/// Memory which only stores cells that aren't zero, for programs that use a
+/// few addresses a long way apart.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Sparse {
+    cells: HashMap<usize, i32>,
+    len: usize,
+}
+
+impl Sparse {
+    pub fn new(program: &[i32]) -> Sparse {
+        Sparse {
+            cells: program
+                .iter()
+                .enumerate()
+                .filter(|(_, &value)| value != 0)
+                .map(|(address, &value)| (address, value))
+                .collect(),
+            len: program.len(),
+        }
+    }
+
+    /// How many cells are actually stored.
+    pub fn stored(&self) -> usize {
+        self.cells.len()
+    }
+}
+
+impl AddressSpace for Sparse {
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn peek(&self, address: usize) -> Option<i32> {
+        if address < self.len {
+            Some(self.cells.get(&address).copied().unwrap_or(0))
+        } else {
+            None
+        }
+    }
+
+    fn read(&mut self, address: usize) -> Result<i32, IntCodeError> {
+        self.len = self.len.max(address + 1);
+        Ok(self.cells.get(&address).copied().unwrap_or(0))
+    }
+
+    fn write(&mut self, address: usize, value: i32) -> Result<(), IntCodeError> {
+        self.len = self.len.max(address + 1);
+        if value == 0 {
+            self.cells.remove(&address);
+        } else {
+            self.cells.insert(address, value);
+        }
+        Ok(())
+    }
+}
+
+/// Wraps another address space, remembering what every write overwrote so
+/// that it can be undone. Undoing doesn't shrink memory back down.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Journal<M> {
+    memory: M,
+    /// Each write's address and the value it replaced, oldest first.
+    entries: Vec<(usize, i32)>,
+}
+
+impl<M: AddressSpace> Journal<M> {
+    pub fn new(memory: M) -> Journal<M> {
+        Journal {
+            memory,
+            entries: vec![],
+        }
+    }
+
+    pub fn entries(&self) -> &[(usize, i32)] {
+        &self.entries
+    }
+
+    /// Undoes writes until only `count` are left.
+    pub fn rewind(&mut self, count: usize) -> Result<(), IntCodeError> {
+        while self.entries.len() > count {
+            let (address, value) = self.entries.pop().unwrap();
+            self.memory.write(address, value)?;
+        }
+        Ok(())
+    }
+
+    pub fn into_inner(self) -> M {
+        self.memory
+    }
+}
+
+impl<M: AddressSpace> AddressSpace for Journal<M> {
+    fn len(&self) -> usize {
+        self.memory.len()
+    }
+
+    fn peek(&self, address: usize) -> Option<i32> {
+        self.memory.peek(address)
+    }
+
+    fn read(&mut self, address: usize) -> Result<i32, IntCodeError> {
+        self.memory.read(address)
+    }
+
+    fn write(&mut self, address: usize, value: i32) -> Result<(), IntCodeError> {
+        let old = self.memory.read(address)?;
+        self.memory.write(address, value)?;
+        self.entries.push((address, old));
+        Ok(())
+    }
+}
+
+/// Wraps another address space, refusing writes to the addresses in `code`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ReadOnlyCode<M> {
+    memory: M,
+    code: Range<usize>,
+}
+
+impl<M: AddressSpace> ReadOnlyCode<M> {
+    pub fn new(memory: M, code: Range<usize>) -> ReadOnlyCode<M> {
+        ReadOnlyCode { memory, code }
+    }
+
+    pub fn into_inner(self) -> M {
+        self.memory
+    }
+}
+
+impl<M: AddressSpace> AddressSpace for ReadOnlyCode<M> {
+    fn len(&self) -> usize {
+        self.memory.len()
+    }
+
+    fn peek(&self, address: usize) -> Option<i32> {
+        self.memory.peek(address)
+    }
+
+    fn read(&mut self, address: usize) -> Result<i32, IntCodeError> {
+        self.memory.read(address)
+    }
+
+    fn write(&mut self, address: usize, value: i32) -> Result<(), IntCodeError> {
+        if self.code.contains(&address) {
+            return Err(IntCodeError::ReadOnlyWrite { address });
+        }
+        self.memory.write(address, value)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn vectors_grow_when_touched() {
+        let mut memory = vec![1, 2];
+        assert_eq!(memory.peek(5), None);
+        assert_eq!(memory.read(5).unwrap(), 0);
+        assert_eq!(AddressSpace::len(&memory), 6);
+        memory.write(7, 3).unwrap();
+        assert_eq!(memory, vec![1, 2, 0, 0, 0, 0, 0, 3])
+    }
+
+    #[test]
+    fn sparse_memory_only_stores_non_zero_cells() {
+        let mut memory = Sparse::new(&[1, 0, 0, 3, 99]);
+        assert_eq!(memory.stored(), 3);
+        memory.write(1_000_000, 7).unwrap();
+        memory.write(0, 0).unwrap();
+        assert_eq!(memory.len(), 1_000_001);
+        assert_eq!(memory.stored(), 3);
+        assert_eq!(memory.read(1_000_000).unwrap(), 7);
+        assert_eq!(memory.peek(0), Some(0))
+    }
+
+    #[test]
+    fn journals_rewind() {
+        let mut memory = Journal::new(vec![1, 2, 3]);
+        memory.write(0, 10).unwrap();
+        memory.write(1, 20).unwrap();
+        memory.write(0, 100).unwrap();
+        assert_eq!(memory.entries(), &[(0, 1), (1, 2), (0, 10)]);
+        memory.rewind(1).unwrap();
+        assert_eq!(memory.into_inner(), vec![10, 2, 3])
+    }
+
+    #[test]
+    fn read_only_code_refuses_writes() {
+        let mut memory = ReadOnlyCode::new(vec![1, 0, 0, 3, 99, 0], 0..5);
+        assert!(memory.write(5, 1).is_ok());
+        match memory.write(3, 1) {
+            Err(IntCodeError::ReadOnlyWrite { address }) => assert_eq!(address, 3),
+            other => panic!("expected a read-only write, got {:?}", other),
+        }
+    }
+}
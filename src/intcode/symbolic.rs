@@ -0,0 +1,437 @@
+use super::instruction::Instruction;
+use super::{IntCodeError, ParameterMode};
+use failure::Fail;
+use std::collections::{BTreeMap, HashMap};
+use std::ops::RangeInclusive;
+use std::rc::Rc;
+
+#[derive(Debug, Fail)]
+pub enum SymbolicError {
+    #[fail(
+        display = "Instruction at {} depends on a symbolic value to decide what to do",
+        ptr
+    )]
+    SymbolicControlFlow { ptr: usize },
+
+    #[fail(display = "Instruction at {} writes to a symbolic address", ptr)]
+    SymbolicWriteAddress { ptr: usize },
+
+    #[fail(display = "Opcode {} can't be executed symbolically", opcode)]
+    UnsupportedOpcode { opcode: i32 },
+
+    #[fail(
+        display = "Attempted to access address {} outside the program",
+        address
+    )]
+    OutOfBounds { address: i64 },
+
+    #[fail(display = "Result isn't a linear function of the symbols")]
+    NonLinear {},
+
+    #[fail(display = "{}", _0)]
+    IntCode(#[cause] IntCodeError),
+}
+
+impl From<IntCodeError> for SymbolicError {
+    fn from(error: IntCodeError) -> SymbolicError {
+        SymbolicError::IntCode(error)
+    }
+}
+
+/// The value of a memory cell, in terms of the symbols the program started with.
+#[derive(Debug, PartialEq)]
+pub enum Expr {
+    Const(i64),
+    /// The starting value of the `n`th symbolic cell.
+    Symbol(usize),
+    Add(Rc<Expr>, Rc<Expr>),
+    Mul(Rc<Expr>, Rc<Expr>),
+    /// Read through a symbolic address, so it could be anything.
+    Unknown,
+}
+
+impl Expr {
+    pub fn constant(&self) -> Option<i64> {
+        match self {
+            Expr::Const(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// A value too big to work with can't be reasoned about any more than one
+    /// read through a symbolic address.
+    fn constant_or_unknown(value: Option<i64>) -> Rc<Expr> {
+        Rc::new(value.map_or(Expr::Unknown, Expr::Const))
+    }
+
+    fn add(a: Rc<Expr>, b: Rc<Expr>) -> Rc<Expr> {
+        match (&*a, &*b) {
+            (Expr::Const(x), Expr::Const(y)) => Expr::constant_or_unknown(x.checked_add(*y)),
+            (Expr::Const(0), _) => b,
+            (_, Expr::Const(0)) => a,
+            (Expr::Unknown, _) | (_, Expr::Unknown) => Rc::new(Expr::Unknown),
+            _ => Rc::new(Expr::Add(a, b)),
+        }
+    }
+
+    fn mul(a: Rc<Expr>, b: Rc<Expr>) -> Rc<Expr> {
+        match (&*a, &*b) {
+            (Expr::Const(x), Expr::Const(y)) => Expr::constant_or_unknown(x.checked_mul(*y)),
+            (Expr::Const(0), _) | (_, Expr::Const(0)) => Rc::new(Expr::Const(0)),
+            (Expr::Const(1), _) => b,
+            (_, Expr::Const(1)) => a,
+            (Expr::Unknown, _) | (_, Expr::Unknown) => Rc::new(Expr::Unknown),
+            _ => Rc::new(Expr::Mul(a, b)),
+        }
+    }
+}
+
+/// `constant + sum(coefficient * symbol)`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Linear {
+    pub constant: i64,
+    pub coefficients: BTreeMap<usize, i64>,
+}
+
+impl Linear {
+    /// `None` if anything overflows.
+    fn scale(&self, factor: i64) -> Option<Linear> {
+        Some(Linear {
+            constant: self.constant.checked_mul(factor)?,
+            coefficients: self
+                .coefficients
+                .iter()
+                .map(|(&symbol, &c)| Some((symbol, c.checked_mul(factor)?)))
+                .collect::<Option<_>>()?,
+        })
+    }
+
+    /// `None` if anything overflows.
+    fn plus(&self, other: &Linear) -> Option<Linear> {
+        let mut sum = self.clone();
+        sum.constant = sum.constant.checked_add(other.constant)?;
+        for (&symbol, &c) in &other.coefficients {
+            let coefficient = sum.coefficients.entry(symbol).or_insert(0);
+            *coefficient = coefficient.checked_add(c)?;
+        }
+        Some(sum)
+    }
+
+    /// Rewrites an expression as a linear function, if it is one and its
+    /// coefficients fit. Shared subexpressions are only visited once.
+    pub fn from_expr(expr: &Rc<Expr>) -> Option<Linear> {
+        Linear::from_shared(expr, &mut HashMap::new())
+    }
+
+    fn from_shared(
+        expr: &Rc<Expr>,
+        seen: &mut HashMap<*const Expr, Option<Linear>>,
+    ) -> Option<Linear> {
+        let key = Rc::as_ptr(expr);
+        if let Some(linear) = seen.get(&key) {
+            return linear.clone();
+        }
+
+        let linear = match &**expr {
+            Expr::Const(value) => Some(Linear {
+                constant: *value,
+                coefficients: BTreeMap::new(),
+            }),
+            Expr::Symbol(n) => {
+                let mut coefficients = BTreeMap::new();
+                coefficients.insert(*n, 1);
+                Some(Linear {
+                    constant: 0,
+                    coefficients,
+                })
+            }
+            Expr::Add(a, b) => {
+                let a = Linear::from_shared(a, seen)?;
+                let b = Linear::from_shared(b, seen)?;
+                a.plus(&b)
+            }
+            Expr::Mul(a, b) => {
+                let a = Linear::from_shared(a, seen)?;
+                let b = Linear::from_shared(b, seen)?;
+                match (a.is_constant(), b.is_constant()) {
+                    (true, _) => b.scale(a.constant),
+                    (_, true) => a.scale(b.constant),
+                    _ => None,
+                }
+            }
+            Expr::Unknown => None,
+        };
+        seen.insert(key, linear.clone());
+
+        linear
+    }
+
+    fn is_constant(&self) -> bool {
+        self.coefficients.values().all(|&c| c == 0)
+    }
+
+    /// Finds the first values for symbols `0..count`, each within `range`,
+    /// which make this equal `target`. All but the last symbol are tried in
+    /// turn; the last is then solved for directly.
+    pub fn solve(&self, target: i64, count: usize, range: RangeInclusive<i32>) -> Option<Vec<i32>> {
+        let mut values = vec![];
+        let remaining = target.checked_sub(self.constant)?;
+        if self.assign(remaining, count, &range, &mut values) {
+            Some(values)
+        } else {
+            None
+        }
+    }
+
+    fn assign(
+        &self,
+        remaining: i64,
+        count: usize,
+        range: &RangeInclusive<i32>,
+        values: &mut Vec<i32>,
+    ) -> bool {
+        let symbol = values.len();
+        let coefficient = *self.coefficients.get(&symbol).unwrap_or(&0);
+
+        if symbol + 1 == count {
+            let value = if coefficient == 0 {
+                if remaining != 0 {
+                    return false;
+                }
+                *range.start() as i64
+            } else if remaining % coefficient == 0 {
+                remaining / coefficient
+            } else {
+                return false;
+            };
+            if value < *range.start() as i64 || value > *range.end() as i64 {
+                return false;
+            }
+            values.push(value as i32);
+            return true;
+        }
+
+        for value in range.clone() {
+            let rest = match coefficient
+                .checked_mul(value as i64)
+                .and_then(|product| remaining.checked_sub(product))
+            {
+                Some(rest) => rest,
+                None => continue,
+            };
+            values.push(value);
+            if self.assign(rest, count, range, values) {
+                return true;
+            }
+            values.pop();
+        }
+
+        false
+    }
+}
+
+/// Runs a program with some cells holding symbols instead of numbers, so
+/// its results come out as expressions in terms of them.
+pub struct SymbolicMachine {
+    memory: Vec<Rc<Expr>>,
+    ptr: usize,
+}
+
+impl SymbolicMachine {
+    pub fn new(program: &[i32], symbols: &[usize]) -> SymbolicMachine {
+        let mut memory: Vec<Rc<Expr>> = program
+            .iter()
+            .map(|&value| Rc::new(Expr::Const(value as i64)))
+            .collect();
+        for (n, &address) in symbols.iter().enumerate() {
+            memory[address] = Rc::new(Expr::Symbol(n));
+        }
+
+        SymbolicMachine { memory, ptr: 0 }
+    }
+
+    pub fn read(&self, address: usize) -> Rc<Expr> {
+        self.memory[address].clone()
+    }
+
+    fn cell(&self, address: i64) -> Result<Rc<Expr>, SymbolicError> {
+        if address < 0 || address as usize >= self.memory.len() {
+            return Err(SymbolicError::OutOfBounds { address });
+        }
+        Ok(self.memory[address as usize].clone())
+    }
+
+    fn concrete(&self, address: usize) -> Result<i64, SymbolicError> {
+        self.cell(address as i64)?
+            .constant()
+            .ok_or(SymbolicError::SymbolicControlFlow { ptr: self.ptr })
+    }
+
+    fn parameter(&self, offset: usize, mode: ParameterMode) -> Result<Rc<Expr>, SymbolicError> {
+        let raw = self.cell((self.ptr + offset) as i64)?;
+        match mode {
+            ParameterMode::Immediate => Ok(raw),
+            ParameterMode::Position => match raw.constant() {
+                Some(address) => self.cell(address),
+                None => Ok(Rc::new(Expr::Unknown)),
+            },
+            ParameterMode::Relative => Err(SymbolicError::UnsupportedOpcode {
+                opcode: self.concrete(self.ptr)? as i32,
+            }),
+        }
+    }
+
+    fn write(&mut self, offset: usize, value: Rc<Expr>) -> Result<(), SymbolicError> {
+        let address = self
+            .cell((self.ptr + offset) as i64)?
+            .constant()
+            .ok_or(SymbolicError::SymbolicWriteAddress { ptr: self.ptr })?;
+        self.cell(address)?;
+        self.memory[address as usize] = value;
+        Ok(())
+    }
+
+    /// Runs until the program halts. Comparisons and jumps are fine as long
+    /// as they only look at constants.
+    pub fn run(&mut self) -> Result<(), SymbolicError> {
+        while self.ptr < self.memory.len() {
+            let head = self.concrete(self.ptr)? as i32;
+            let (opcode, modes, count) = Instruction::head(head)?;
+
+            match opcode {
+                1 | 2 | 7 | 8 => {
+                    let noun = self.parameter(1, modes[0])?;
+                    let verb = self.parameter(2, modes[1])?;
+                    let value = match (opcode, noun.constant(), verb.constant()) {
+                        (1, _, _) => Expr::add(noun, verb),
+                        (2, _, _) => Expr::mul(noun, verb),
+                        (7, Some(a), Some(b)) => Rc::new(Expr::Const((a < b) as i64)),
+                        (8, Some(a), Some(b)) => Rc::new(Expr::Const((a == b) as i64)),
+                        _ => return Err(SymbolicError::SymbolicControlFlow { ptr: self.ptr }),
+                    };
+                    self.write(3, value)?;
+                    self.ptr += count + 1;
+                }
+                5 | 6 => {
+                    let condition = self.parameter(1, modes[0])?.constant();
+                    let target = self.parameter(2, modes[1])?.constant();
+                    match (condition, target) {
+                        (Some(condition), Some(target)) => {
+                            if (condition != 0) == (opcode == 5) {
+                                if target < 0 {
+                                    return Err(SymbolicError::OutOfBounds { address: target });
+                                }
+                                self.ptr = target as usize;
+                            } else {
+                                self.ptr += count + 1;
+                            }
+                        }
+                        _ => return Err(SymbolicError::SymbolicControlFlow { ptr: self.ptr }),
+                    }
+                }
+                99 => return Ok(()),
+                _ => return Err(SymbolicError::UnsupportedOpcode { opcode }),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Finds values within `range` for the cells at `symbols` which make the
+/// program leave `target` at address 0, without running it for each guess.
+/// `Ok(None)` means no values can work; an error means the program couldn't
+/// be analysed and has to be searched concretely instead.
+pub fn solve(
+    program: &[i32],
+    symbols: &[usize],
+    target: i32,
+    range: RangeInclusive<i32>,
+) -> Result<Option<Vec<i32>>, SymbolicError> {
+    let mut machine = SymbolicMachine::new(program, symbols);
+    machine.run()?;
+    let linear = Linear::from_expr(&machine.read(0)).ok_or(SymbolicError::NonLinear {})?;
+
+    Ok(linear.solve(target as i64, symbols.len(), range))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn builds_linear_expressions() {
+        // [0] = [1] * 3 + [2] + 7, after first reading through [1] and [2] as addresses.
+        let program = [1, 0, 0, 3, 1002, 1, 3, 0, 1, 0, 2, 0, 1001, 0, 7, 0, 99];
+        let mut machine = SymbolicMachine::new(&program, &[1, 2]);
+        machine.run().unwrap();
+
+        let mut coefficients = BTreeMap::new();
+        coefficients.insert(0, 3);
+        coefficients.insert(1, 1);
+        assert_eq!(
+            Linear::from_expr(&machine.read(0)),
+            Some(Linear {
+                constant: 7,
+                coefficients,
+            })
+        );
+        assert_eq!(machine.read(3), Rc::new(Expr::Unknown))
+    }
+
+    #[test]
+    fn solves_for_noun_and_verb() {
+        let program = [1, 0, 0, 3, 1002, 1, 3, 0, 1, 0, 2, 0, 1001, 0, 7, 0, 99];
+        assert_eq!(
+            solve(&program, &[1, 2], 57, 0..=99).unwrap(),
+            Some(vec![0, 50])
+        );
+        assert_eq!(
+            solve(&program, &[1, 2], 400, 0..=99).unwrap(),
+            Some(vec![98, 99])
+        )
+    }
+
+    #[test]
+    fn rejects_products_of_symbols() {
+        let program = [2, 5, 6, 0, 99, 0, 0];
+        match solve(&program, &[5, 6], 6, 0..=99) {
+            Err(SymbolicError::NonLinear {}) => (),
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn rejects_overflowing_coefficients() {
+        // Multiplies the symbol at 13 by two billion three times over.
+        let program = [
+            1002,
+            13,
+            2_000_000_000,
+            0,
+            1002,
+            0,
+            2_000_000_000,
+            0,
+            1002,
+            0,
+            2_000_000_000,
+            0,
+            99,
+            0,
+        ];
+        match solve(&program, &[13], 0, 0..=99) {
+            Err(SymbolicError::NonLinear {}) => (),
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn rejects_jumps_on_symbols() {
+        let program = [1105, 0, 0, 99];
+        match solve(&program, &[1], 0, 0..=99) {
+            Err(SymbolicError::SymbolicControlFlow { ptr: 0 }) => (),
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,420 @@
+use super::cfg::{Exit, Graph, Target};
+use super::instruction::{Instruction, Parameter};
+use super::{watch, ParameterMode};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+#[rustfmt::skip]
+pub mod day5;
+
+/// Everything the generated code needs apart from the program itself: how it
+/// reports stopping, growing memory, and a plain interpreter for any
+/// instruction that wasn't known when it was generated.
+const PRELUDE: &str = r#"//! Generated by `intcode::transpile`. Regenerate it rather than editing it.
+#![allow(unused, clippy::all)]
+
+use std::collections::VecDeque;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Stop {
+    Halted,
+    AwaitingInput { pc: usize },
+    /// The instruction at `pc` couldn't be carried out.
+    Fault { pc: usize },
+}
+
+fn cell(memory: &mut Vec<i32>, address: i32, pc: usize) -> Result<usize, usize> {
+    if address < 0 {
+        return Err(pc);
+    }
+    let address = address as usize;
+    if address >= memory.len() {
+        memory.resize(address + 1, 0);
+    }
+    Ok(address)
+}
+
+fn target(t: i32, pc: usize) -> Result<usize, usize> {
+    if t < 0 {
+        return Err(pc);
+    }
+    Ok(t as usize)
+}
+
+/// Arithmetic which overflowed faults, as it does on a `Machine`.
+fn checked(value: Option<i32>, pc: usize) -> Result<i32, usize> {
+    value.ok_or(pc)
+}
+
+fn address(memory: &mut Vec<i32>, at: usize, rb: i32, mode: i32, n: usize) -> Result<usize, usize> {
+    let parameter = memory[at + 1 + n];
+    let address = if mode == 2 {
+        parameter.checked_add(rb).ok_or(at)?
+    } else {
+        parameter
+    };
+    cell(memory, address, at)
+}
+
+fn read(memory: &mut Vec<i32>, at: usize, rb: i32, mode: i32, n: usize) -> Result<i32, usize> {
+    if mode == 1 {
+        return Ok(memory[at + 1 + n]);
+    }
+    let a = address(memory, at, rb, mode, n)?;
+    Ok(memory[a])
+}
+
+/// Runs whatever instruction is at `pc`, for code which changed or was
+/// jumped to at runtime.
+fn interpret(
+    memory: &mut Vec<i32>,
+    pc: &mut usize,
+    rb: &mut i32,
+    input: &mut VecDeque<i32>,
+    output: &mut Vec<i32>,
+) -> Result<Option<Stop>, usize> {
+    let at = *pc;
+    if at >= memory.len() {
+        return Ok(Some(Stop::Halted));
+    }
+    let head = memory[at];
+    let opcode = head % 100;
+    let modes = [head % 1_000 / 100, head % 10_000 / 1_000, head % 100_000 / 10_000];
+    if modes.iter().any(|mode| !(0..=2).contains(mode)) {
+        return Err(at);
+    }
+    let size = match opcode {
+        1 | 2 | 7 | 8 => 4,
+        5 | 6 => 3,
+        3 | 4 | 9 => 2,
+        99 => return Ok(Some(Stop::Halted)),
+        _ => return Err(at),
+    };
+    if at + size > memory.len() {
+        return Err(at);
+    }
+    if opcode == 3 && input.is_empty() {
+        return Ok(Some(Stop::AwaitingInput { pc: at }));
+    }
+
+    *pc = at + size;
+    match opcode {
+        1 | 2 | 7 | 8 => {
+            let x = read(memory, at, *rb, modes[0], 0)?;
+            let y = read(memory, at, *rb, modes[1], 1)?;
+            let d = address(memory, at, *rb, modes[2], 2)?;
+            memory[d] = match opcode {
+                1 => x.checked_add(y).ok_or(at)?,
+                2 => x.checked_mul(y).ok_or(at)?,
+                7 => (x < y) as i32,
+                _ => (x == y) as i32,
+            };
+        }
+        3 => {
+            let d = address(memory, at, *rb, modes[0], 0)?;
+            memory[d] = input.pop_front().unwrap();
+        }
+        4 => output.push(read(memory, at, *rb, modes[0], 0)?),
+        5 | 6 => {
+            let c = read(memory, at, *rb, modes[0], 0)?;
+            let t = read(memory, at, *rb, modes[1], 1)?;
+            if (c != 0) == (opcode == 5) {
+                *pc = target(t, at)?;
+            }
+        }
+        _ => {
+            let by = read(memory, at, *rb, modes[0], 0)?;
+            *rb = rb.checked_add(by).ok_or(at)?;
+        }
+    }
+    Ok(None)
+}
+
+/// Runs the program, starting from `memory` as it was generated from.
+pub fn run(memory: &mut Vec<i32>, input: &mut VecDeque<i32>, output: &mut Vec<i32>) -> Stop {
+    assert!(memory.len() >= LENGTH, "memory is shorter than the program");
+    match execute(memory, input, output) {
+        Ok(stop) => stop,
+        Err(pc) => Stop::Fault { pc },
+    }
+}
+"#;
+
+/// Writes code for reading an operand of the instruction at `pc`, in a
+/// program `length` words long.
+fn read(operand: Parameter, pc: usize, length: usize) -> String {
+    let value = operand.value;
+    match operand.mode {
+        ParameterMode::Immediate => value.to_string(),
+        ParameterMode::Position if value >= 0 && (value as usize) < length => {
+            format!("memory[{}]", value)
+        }
+        _ => format!("{{ let a = {}; memory[a] }}", address(operand, pc, length)),
+    }
+}
+
+/// Writes code for the address an operand refers to. Memory is never smaller
+/// than the program, so addresses inside it can be used as they are.
+fn address(operand: Parameter, pc: usize, length: usize) -> String {
+    let value = operand.value;
+    match operand.mode {
+        ParameterMode::Immediate | ParameterMode::Position => {
+            if value >= 0 && (value as usize) < length {
+                value.to_string()
+            } else {
+                format!("cell(memory, {}, {})?", value, pc)
+            }
+        }
+        ParameterMode::Relative => format!(
+            "cell(memory, checked(rb.checked_add({}), {})?, {})?",
+            value, pc, pc
+        ),
+    }
+}
+
+fn body(pc: usize, instruction: &Instruction, length: usize) -> Vec<String> {
+    let next = pc + instruction.size();
+    let parameters = instruction.parameters();
+    let r = |n: usize| read(parameters[n], pc, length);
+    let a = |n: usize| address(parameters[n], pc, length);
+    match instruction {
+        Instruction::Add(..)
+        | Instruction::Multiply(..)
+        | Instruction::LessThan(..)
+        | Instruction::Equals(..) => {
+            let result = match instruction {
+                Instruction::Add(..) => format!("checked(x.checked_add(y), {})?", pc),
+                Instruction::Multiply(..) => format!("checked(x.checked_mul(y), {})?", pc),
+                Instruction::LessThan(..) => "(x < y) as i32".to_string(),
+                _ => "(x == y) as i32".to_string(),
+            };
+            vec![
+                format!("let x: i32 = {};", r(0)),
+                format!("let y: i32 = {};", r(1)),
+                format!("let d = {};", a(2)),
+                format!("memory[d] = {};", result),
+                format!("pc = {};", next),
+            ]
+        }
+        Instruction::Input(_) => vec![
+            "if input.is_empty() {".to_string(),
+            format!("    return Ok(Stop::AwaitingInput {{ pc: {} }});", pc),
+            "}".to_string(),
+            format!("let d = {};", a(0)),
+            "memory[d] = input.pop_front().unwrap();".to_string(),
+            format!("pc = {};", next),
+        ],
+        Instruction::Output(_) => {
+            vec![format!("output.push({});", r(0)), format!("pc = {};", next)]
+        }
+        Instruction::JumpIfTrue(..) | Instruction::JumpIfFalse(..) => vec![
+            format!("let c = {};", r(0)),
+            format!("let t = {};", r(1)),
+            format!(
+                "pc = if c {} 0 {{ target(t, {})? }} else {{ {} }};",
+                if instruction.opcode() == 5 {
+                    "!="
+                } else {
+                    "=="
+                },
+                pc,
+                next
+            ),
+        ],
+        Instruction::AdjustRelativeBase(_) => {
+            vec![
+                format!("rb = checked(rb.checked_add({}), {})?;", r(0), pc),
+                format!("pc = {};", next),
+            ]
+        }
+        Instruction::Halt => vec!["return Ok(Stop::Halted);".to_string()],
+    }
+}
+
+/// Turns a program into a Rust module with a `run` function that does the
+/// same thing as running it on a `Machine`, each instruction compiled into
+/// an arm of a `match` on the instruction pointer.
+///
+/// Anything the control-flow graph can't see ahead of time, like jumps to
+/// computed addresses, runs through a small interpreter instead. Where the
+/// program might rewrite its own code, each compiled instruction first
+/// checks it's still what was compiled, and otherwise is interpreted too,
+/// so self-modifying programs still come out right, just slower.
+pub fn transpile(program: &[i32]) -> String {
+    let graph = Graph::build(program);
+    let mut instructions = BTreeMap::new();
+    let mut dynamic = false;
+    for block in graph.blocks.values() {
+        let mut ptr = block.start;
+        while let Some(instruction) = Instruction::decode(program, ptr)
+            .ok()
+            .filter(|_| ptr < block.end)
+        {
+            ptr += instruction.size();
+            instructions.insert(ptr - instruction.size(), instruction);
+        }
+        dynamic |= matches!(
+            block.exit,
+            Exit::Invalid(_)
+                | Exit::Jump(Target::Computed(_))
+                | Exit::Branch {
+                    target: Target::Computed(_),
+                    ..
+                }
+        );
+    }
+    // Code that isn't compiled, or writes through the relative base, could
+    // write anywhere.
+    dynamic |= instructions
+        .values()
+        .filter_map(Instruction::destination)
+        .any(|destination| destination.mode == ParameterMode::Relative);
+    let written: Vec<usize> = watch::analyse(program)
+        .iter()
+        .map(|modification| modification.address)
+        .collect();
+    // Once code changes, control can end up anywhere, so compile everything
+    // that looks like an instruction in case it ever runs.
+    if dynamic || !written.is_empty() {
+        for ptr in 0..program.len() {
+            if let Ok(instruction) = Instruction::decode(program, ptr) {
+                instructions.entry(ptr).or_insert(instruction);
+            }
+        }
+    }
+
+    let mut code = PRELUDE.to_string();
+    writeln!(code).unwrap();
+    writeln!(code, "const LENGTH: usize = {};", program.len()).unwrap();
+    writeln!(code).unwrap();
+    writeln!(
+        code,
+        "fn execute(memory: &mut Vec<i32>, input: &mut VecDeque<i32>, output: &mut Vec<i32>) -> Result<Stop, usize> {{"
+    )
+    .unwrap();
+    writeln!(code, "    let mut pc = 0;").unwrap();
+    writeln!(code, "    let mut rb: i32 = 0;").unwrap();
+    writeln!(code, "    loop {{").unwrap();
+    writeln!(code, "        match pc {{").unwrap();
+    for (&pc, instruction) in &instructions {
+        let words = instruction.encode();
+        let end = pc + words.len();
+        let guarded = dynamic
+            || written
+                .iter()
+                .any(|&address| pc <= address && address < end);
+        let words: Vec<String> = words.iter().map(|word| word.to_string()).collect();
+        if guarded {
+            writeln!(
+                code,
+                "            {} if memory[{}..{}] == [{}] => {{",
+                pc,
+                pc,
+                end,
+                words.join(", ")
+            )
+            .unwrap();
+        } else {
+            writeln!(code, "            // {}", words.join(",")).unwrap();
+            writeln!(code, "            {} => {{", pc).unwrap();
+        }
+        for line in body(pc, instruction, program.len()) {
+            writeln!(code, "                {}", line).unwrap();
+        }
+        writeln!(code, "            }}").unwrap();
+    }
+    writeln!(code, "            _ => {{").unwrap();
+    writeln!(
+        code,
+        "                if let Some(stop) = interpret(memory, &mut pc, &mut rb, input, output)? {{"
+    )
+    .unwrap();
+    writeln!(code, "                    return Ok(stop);").unwrap();
+    writeln!(code, "                }}").unwrap();
+    writeln!(code, "            }}").unwrap();
+    writeln!(code, "        }}").unwrap();
+    writeln!(code, "    }}").unwrap();
+    writeln!(code, "}}").unwrap();
+
+    code
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::intcode::{IntCodeError, Machine, State};
+    use std::collections::VecDeque;
+
+    fn day5_program() -> Vec<i32> {
+        crate::day5::converter(include_str!("../../input/2019/day5.txt"))
+    }
+
+    #[test]
+    fn generated_day5_is_up_to_date() {
+        // To regenerate: write `transpile(&day5_program())` to
+        // src/intcode/transpile/day5.rs.
+        assert!(transpile(&day5_program()) == include_str!("transpile/day5.rs"))
+    }
+
+    #[test]
+    fn transpiled_day5_matches_the_interpreter() {
+        let program = day5_program();
+        for input in 0..=10 {
+            let mut machine = Machine::new(&program);
+            machine.push_input(input);
+            let expected = machine.run();
+
+            let mut memory = program.clone();
+            let mut output = vec![];
+            let stop = day5::run(&mut memory, &mut VecDeque::from(vec![input]), &mut output);
+            assert_eq!(output, machine.take_output());
+            // Only 1 and 5 are valid system IDs.
+            match expected {
+                Ok(State::Halted) => assert_eq!(stop, day5::Stop::Halted),
+                Ok(_) => assert!(matches!(stop, day5::Stop::AwaitingInput { .. })),
+                Err(_) => assert!(matches!(stop, day5::Stop::Fault { .. })),
+            }
+            assert_eq!(memory, machine.memory())
+        }
+    }
+
+    #[test]
+    fn overflow_faults_like_the_interpreter() {
+        let day5 = day5_program();
+        let length = day5.len();
+        let padded = |program: &[i32]| {
+            let mut memory = program.to_vec();
+            memory.resize(length, 0);
+            memory
+        };
+        let mut multiply = day5.clone();
+        multiply[114] = i32::MAX;
+        let mut add = day5.clone();
+        add[114] = i32::MIN / 46;
+        // The patched day 5s overflow in compiled code, the rest run through
+        // the interpreter because they don't look like day 5.
+        let programs = [
+            (multiply, 12),
+            (add, 16),
+            (padded(&[1101, i32::MAX, 1, 0, 99]), 0),
+            (padded(&[1102, i32::MAX, 2, 0, 99]), 0),
+            (padded(&[109, i32::MAX, 109, 1, 99]), 2),
+            (padded(&[109, i32::MAX, 204, 1, 99]), 2),
+        ];
+        for (program, at) in &programs {
+            let mut machine = Machine::new(program);
+            machine.push_input(1);
+            match machine.run() {
+                Err(IntCodeError::Overflow { pc }) => assert_eq!(pc, *at),
+                other => panic!("{:?} didn't overflow: {:?}", program[..5].to_vec(), other),
+            }
+
+            let mut memory = program.clone();
+            let mut output = vec![];
+            let stop = day5::run(&mut memory, &mut VecDeque::from(vec![1]), &mut output);
+            assert_eq!(stop, day5::Stop::Fault { pc: *at });
+            assert_eq!(output, machine.take_output());
+            assert_eq!(memory, machine.memory())
+        }
+    }
+}
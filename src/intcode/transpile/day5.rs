@@ -0,0 +1,1845 @@
+//! Generated by `intcode::transpile`. Regenerate it rather than editing it.
+#![allow(unused, clippy::all)]
+
+use std::collections::VecDeque;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Stop {
+    Halted,
+    AwaitingInput { pc: usize },
+    /// The instruction at `pc` couldn't be carried out.
+    Fault { pc: usize },
+}
+
+fn cell(memory: &mut Vec<i32>, address: i32, pc: usize) -> Result<usize, usize> {
+    if address < 0 {
+        return Err(pc);
+    }
+    let address = address as usize;
+    if address >= memory.len() {
+        memory.resize(address + 1, 0);
+    }
+    Ok(address)
+}
+
+fn target(t: i32, pc: usize) -> Result<usize, usize> {
+    if t < 0 {
+        return Err(pc);
+    }
+    Ok(t as usize)
+}
+
+/// Arithmetic which overflowed faults, as it does on a `Machine`.
+fn checked(value: Option<i32>, pc: usize) -> Result<i32, usize> {
+    value.ok_or(pc)
+}
+
+fn address(memory: &mut Vec<i32>, at: usize, rb: i32, mode: i32, n: usize) -> Result<usize, usize> {
+    let parameter = memory[at + 1 + n];
+    let address = if mode == 2 {
+        parameter.checked_add(rb).ok_or(at)?
+    } else {
+        parameter
+    };
+    cell(memory, address, at)
+}
+
+fn read(memory: &mut Vec<i32>, at: usize, rb: i32, mode: i32, n: usize) -> Result<i32, usize> {
+    if mode == 1 {
+        return Ok(memory[at + 1 + n]);
+    }
+    let a = address(memory, at, rb, mode, n)?;
+    Ok(memory[a])
+}
+
+/// Runs whatever instruction is at `pc`, for code which changed or was
+/// jumped to at runtime.
+fn interpret(
+    memory: &mut Vec<i32>,
+    pc: &mut usize,
+    rb: &mut i32,
+    input: &mut VecDeque<i32>,
+    output: &mut Vec<i32>,
+) -> Result<Option<Stop>, usize> {
+    let at = *pc;
+    if at >= memory.len() {
+        return Ok(Some(Stop::Halted));
+    }
+    let head = memory[at];
+    let opcode = head % 100;
+    let modes = [head % 1_000 / 100, head % 10_000 / 1_000, head % 100_000 / 10_000];
+    if modes.iter().any(|mode| !(0..=2).contains(mode)) {
+        return Err(at);
+    }
+    let size = match opcode {
+        1 | 2 | 7 | 8 => 4,
+        5 | 6 => 3,
+        3 | 4 | 9 => 2,
+        99 => return Ok(Some(Stop::Halted)),
+        _ => return Err(at),
+    };
+    if at + size > memory.len() {
+        return Err(at);
+    }
+    if opcode == 3 && input.is_empty() {
+        return Ok(Some(Stop::AwaitingInput { pc: at }));
+    }
+
+    *pc = at + size;
+    match opcode {
+        1 | 2 | 7 | 8 => {
+            let x = read(memory, at, *rb, modes[0], 0)?;
+            let y = read(memory, at, *rb, modes[1], 1)?;
+            let d = address(memory, at, *rb, modes[2], 2)?;
+            memory[d] = match opcode {
+                1 => x.checked_add(y).ok_or(at)?,
+                2 => x.checked_mul(y).ok_or(at)?,
+                7 => (x < y) as i32,
+                _ => (x == y) as i32,
+            };
+        }
+        3 => {
+            let d = address(memory, at, *rb, modes[0], 0)?;
+            memory[d] = input.pop_front().unwrap();
+        }
+        4 => output.push(read(memory, at, *rb, modes[0], 0)?),
+        5 | 6 => {
+            let c = read(memory, at, *rb, modes[0], 0)?;
+            let t = read(memory, at, *rb, modes[1], 1)?;
+            if (c != 0) == (opcode == 5) {
+                *pc = target(t, at)?;
+            }
+        }
+        _ => {
+            let by = read(memory, at, *rb, modes[0], 0)?;
+            *rb = rb.checked_add(by).ok_or(at)?;
+        }
+    }
+    Ok(None)
+}
+
+/// Runs the program, starting from `memory` as it was generated from.
+pub fn run(memory: &mut Vec<i32>, input: &mut VecDeque<i32>, output: &mut Vec<i32>) -> Stop {
+    assert!(memory.len() >= LENGTH, "memory is shorter than the program");
+    match execute(memory, input, output) {
+        Ok(stop) => stop,
+        Err(pc) => Stop::Fault { pc },
+    }
+}
+
+const LENGTH: usize = 678;
+
+fn execute(memory: &mut Vec<i32>, input: &mut VecDeque<i32>, output: &mut Vec<i32>) -> Result<Stop, usize> {
+    let mut pc = 0;
+    let mut rb: i32 = 0;
+    loop {
+        match pc {
+            0 if memory[0..2] == [3, 225] => {
+                if input.is_empty() {
+                    return Ok(Stop::AwaitingInput { pc: 0 });
+                }
+                let d = 225;
+                memory[d] = input.pop_front().unwrap();
+                pc = 2;
+            }
+            2 if memory[2..6] == [1, 225, 6, 6] => {
+                let x: i32 = memory[225];
+                let y: i32 = memory[6];
+                let d = 6;
+                memory[d] = checked(x.checked_add(y), 2)?;
+                pc = 6;
+            }
+            4 if memory[4..7] == [6, 6, 1100] => {
+                let c = memory[6];
+                let t = { let a = cell(memory, 1100, 4)?; memory[a] };
+                pc = if c == 0 { target(t, 4)? } else { 7 };
+            }
+            5 if memory[5..8] == [6, 1100, 1] => {
+                let c = { let a = cell(memory, 1100, 5)?; memory[a] };
+                let t = memory[1];
+                pc = if c == 0 { target(t, 5)? } else { 8 };
+            }
+            7 if memory[7..11] == [1, 238, 225, 104] => {
+                let x: i32 = memory[238];
+                let y: i32 = memory[225];
+                let d = 104;
+                memory[d] = checked(x.checked_add(y), 7)?;
+                pc = 11;
+            }
+            10 if memory[10..12] == [104, 0] => {
+                output.push(0);
+                pc = 12;
+            }
+            12 if memory[12..16] == [1002, 114, 46, 224] => {
+                let x: i32 = memory[114];
+                let y: i32 = 46;
+                let d = 224;
+                memory[d] = checked(x.checked_mul(y), 12)?;
+                pc = 16;
+            }
+            16 if memory[16..20] == [1001, 224, -736, 224] => {
+                let x: i32 = memory[224];
+                let y: i32 = -736;
+                let d = 224;
+                memory[d] = checked(x.checked_add(y), 16)?;
+                pc = 20;
+            }
+            20 if memory[20..22] == [4, 224] => {
+                output.push(memory[224]);
+                pc = 22;
+            }
+            22 if memory[22..26] == [1002, 223, 8, 223] => {
+                let x: i32 = memory[223];
+                let y: i32 = 8;
+                let d = 223;
+                memory[d] = checked(x.checked_mul(y), 22)?;
+                pc = 26;
+            }
+            24 if memory[24..28] == [8, 223, 1001, 224] => {
+                let x: i32 = memory[223];
+                let y: i32 = { let a = cell(memory, 1001, 24)?; memory[a] };
+                let d = 224;
+                memory[d] = (x == y) as i32;
+                pc = 28;
+            }
+            26 if memory[26..30] == [1001, 224, 3, 224] => {
+                let x: i32 = memory[224];
+                let y: i32 = 3;
+                let d = 224;
+                memory[d] = checked(x.checked_add(y), 26)?;
+                pc = 30;
+            }
+            28 if memory[28..30] == [3, 224] => {
+                if input.is_empty() {
+                    return Ok(Stop::AwaitingInput { pc: 28 });
+                }
+                let d = 224;
+                memory[d] = input.pop_front().unwrap();
+                pc = 30;
+            }
+            30 if memory[30..34] == [1, 223, 224, 223] => {
+                let x: i32 = memory[223];
+                let y: i32 = memory[224];
+                let d = 223;
+                memory[d] = checked(x.checked_add(y), 30)?;
+                pc = 34;
+            }
+            34 if memory[34..38] == [1, 166, 195, 224] => {
+                let x: i32 = memory[166];
+                let y: i32 = memory[195];
+                let d = 224;
+                memory[d] = checked(x.checked_add(y), 34)?;
+                pc = 38;
+            }
+            38 if memory[38..42] == [1001, 224, -137, 224] => {
+                let x: i32 = memory[224];
+                let y: i32 = -137;
+                let d = 224;
+                memory[d] = checked(x.checked_add(y), 38)?;
+                pc = 42;
+            }
+            42 if memory[42..44] == [4, 224] => {
+                output.push(memory[224]);
+                pc = 44;
+            }
+            44 if memory[44..48] == [102, 8, 223, 223] => {
+                let x: i32 = 8;
+                let y: i32 = memory[223];
+                let d = 223;
+                memory[d] = checked(x.checked_mul(y), 44)?;
+                pc = 48;
+            }
+            45 if memory[45..49] == [8, 223, 223, 101] => {
+                let x: i32 = memory[223];
+                let y: i32 = memory[223];
+                let d = 101;
+                memory[d] = (x == y) as i32;
+                pc = 49;
+            }
+            48 if memory[48..52] == [101, 5, 224, 224] => {
+                let x: i32 = 5;
+                let y: i32 = memory[224];
+                let d = 224;
+                memory[d] = checked(x.checked_add(y), 48)?;
+                pc = 52;
+            }
+            49 if memory[49..52] == [5, 224, 224] => {
+                let c = memory[224];
+                let t = memory[224];
+                pc = if c != 0 { target(t, 49)? } else { 52 };
+            }
+            52 if memory[52..56] == [1, 223, 224, 223] => {
+                let x: i32 = memory[223];
+                let y: i32 = memory[224];
+                let d = 223;
+                memory[d] = checked(x.checked_add(y), 52)?;
+                pc = 56;
+            }
+            56 if memory[56..60] == [1001, 169, 83, 224] => {
+                let x: i32 = memory[169];
+                let y: i32 = 83;
+                let d = 224;
+                memory[d] = checked(x.checked_add(y), 56)?;
+                pc = 60;
+            }
+            60 if memory[60..64] == [1001, 224, -90, 224] => {
+                let x: i32 = memory[224];
+                let y: i32 = -90;
+                let d = 224;
+                memory[d] = checked(x.checked_add(y), 60)?;
+                pc = 64;
+            }
+            64 if memory[64..66] == [4, 224] => {
+                output.push(memory[224]);
+                pc = 66;
+            }
+            66 if memory[66..70] == [102, 8, 223, 223] => {
+                let x: i32 = 8;
+                let y: i32 = memory[223];
+                let d = 223;
+                memory[d] = checked(x.checked_mul(y), 66)?;
+                pc = 70;
+            }
+            67 if memory[67..71] == [8, 223, 223, 1001] => {
+                let x: i32 = memory[223];
+                let y: i32 = memory[223];
+                let d = cell(memory, 1001, 67)?;
+                memory[d] = (x == y) as i32;
+                pc = 71;
+            }
+            70 if memory[70..74] == [1001, 224, 2, 224] => {
+                let x: i32 = memory[224];
+                let y: i32 = 2;
+                let d = 224;
+                memory[d] = checked(x.checked_add(y), 70)?;
+                pc = 74;
+            }
+            72 if memory[72..76] == [2, 224, 1, 224] => {
+                let x: i32 = memory[224];
+                let y: i32 = memory[1];
+                let d = 224;
+                memory[d] = checked(x.checked_mul(y), 72)?;
+                pc = 76;
+            }
+            74 if memory[74..78] == [1, 224, 223, 223] => {
+                let x: i32 = memory[224];
+                let y: i32 = memory[223];
+                let d = 223;
+                memory[d] = checked(x.checked_add(y), 74)?;
+                pc = 78;
+            }
+            78 if memory[78..82] == [101, 44, 117, 224] => {
+                let x: i32 = 44;
+                let y: i32 = memory[117];
+                let d = 224;
+                memory[d] = checked(x.checked_add(y), 78)?;
+                pc = 82;
+            }
+            82 if memory[82..86] == [101, -131, 224, 224] => {
+                let x: i32 = -131;
+                let y: i32 = memory[224];
+                let d = 224;
+                memory[d] = checked(x.checked_add(y), 82)?;
+                pc = 86;
+            }
+            86 if memory[86..88] == [4, 224] => {
+                output.push(memory[224]);
+                pc = 88;
+            }
+            88 if memory[88..92] == [1002, 223, 8, 223] => {
+                let x: i32 = memory[223];
+                let y: i32 = 8;
+                let d = 223;
+                memory[d] = checked(x.checked_mul(y), 88)?;
+                pc = 92;
+            }
+            90 if memory[90..94] == [8, 223, 101, 5] => {
+                let x: i32 = memory[223];
+                let y: i32 = memory[101];
+                let d = 5;
+                memory[d] = (x == y) as i32;
+                pc = 94;
+            }
+            92 if memory[92..96] == [101, 5, 224, 224] => {
+                let x: i32 = 5;
+                let y: i32 = memory[224];
+                let d = 224;
+                memory[d] = checked(x.checked_add(y), 92)?;
+                pc = 96;
+            }
+            93 if memory[93..96] == [5, 224, 224] => {
+                let c = memory[224];
+                let t = memory[224];
+                pc = if c != 0 { target(t, 93)? } else { 96 };
+            }
+            96 if memory[96..100] == [1, 224, 223, 223] => {
+                let x: i32 = memory[224];
+                let y: i32 = memory[223];
+                let d = 223;
+                memory[d] = checked(x.checked_add(y), 96)?;
+                pc = 100;
+            }
+            100 if memory[100..104] == [1101, 80, 17, 225] => {
+                let x: i32 = 80;
+                let y: i32 = 17;
+                let d = 225;
+                memory[d] = checked(x.checked_add(y), 100)?;
+                pc = 104;
+            }
+            104 if memory[104..108] == [1101, 56, 51, 225] => {
+                let x: i32 = 56;
+                let y: i32 = 51;
+                let d = 225;
+                memory[d] = checked(x.checked_add(y), 104)?;
+                pc = 108;
+            }
+            108 if memory[108..112] == [1101, 78, 89, 225] => {
+                let x: i32 = 78;
+                let y: i32 = 89;
+                let d = 225;
+                memory[d] = checked(x.checked_add(y), 108)?;
+                pc = 112;
+            }
+            112 if memory[112..116] == [1102, 48, 16, 225] => {
+                let x: i32 = 48;
+                let y: i32 = 16;
+                let d = 225;
+                memory[d] = checked(x.checked_mul(y), 112)?;
+                pc = 116;
+            }
+            116 if memory[116..120] == [1101, 87, 78, 225] => {
+                let x: i32 = 87;
+                let y: i32 = 78;
+                let d = 225;
+                memory[d] = checked(x.checked_add(y), 116)?;
+                pc = 120;
+            }
+            120 if memory[120..124] == [1102, 34, 33, 224] => {
+                let x: i32 = 34;
+                let y: i32 = 33;
+                let d = 224;
+                memory[d] = checked(x.checked_mul(y), 120)?;
+                pc = 124;
+            }
+            124 if memory[124..128] == [101, -1122, 224, 224] => {
+                let x: i32 = -1122;
+                let y: i32 = memory[224];
+                let d = 224;
+                memory[d] = checked(x.checked_add(y), 124)?;
+                pc = 128;
+            }
+            128 if memory[128..130] == [4, 224] => {
+                output.push(memory[224]);
+                pc = 130;
+            }
+            130 if memory[130..134] == [1002, 223, 8, 223] => {
+                let x: i32 = memory[223];
+                let y: i32 = 8;
+                let d = 223;
+                memory[d] = checked(x.checked_mul(y), 130)?;
+                pc = 134;
+            }
+            132 if memory[132..136] == [8, 223, 101, 7] => {
+                let x: i32 = memory[223];
+                let y: i32 = memory[101];
+                let d = 7;
+                memory[d] = (x == y) as i32;
+                pc = 136;
+            }
+            134 if memory[134..138] == [101, 7, 224, 224] => {
+                let x: i32 = 7;
+                let y: i32 = memory[224];
+                let d = 224;
+                memory[d] = checked(x.checked_add(y), 134)?;
+                pc = 138;
+            }
+            135 if memory[135..139] == [7, 224, 224, 1] => {
+                let x: i32 = memory[224];
+                let y: i32 = memory[224];
+                let d = 1;
+                memory[d] = (x < y) as i32;
+                pc = 139;
+            }
+            138 if memory[138..142] == [1, 223, 224, 223] => {
+                let x: i32 = memory[223];
+                let y: i32 = memory[224];
+                let d = 223;
+                memory[d] = checked(x.checked_add(y), 138)?;
+                pc = 142;
+            }
+            142 if memory[142..146] == [1101, 66, 53, 224] => {
+                let x: i32 = 66;
+                let y: i32 = 53;
+                let d = 224;
+                memory[d] = checked(x.checked_add(y), 142)?;
+                pc = 146;
+            }
+            146 if memory[146..150] == [101, -119, 224, 224] => {
+                let x: i32 = -119;
+                let y: i32 = memory[224];
+                let d = 224;
+                memory[d] = checked(x.checked_add(y), 146)?;
+                pc = 150;
+            }
+            150 if memory[150..152] == [4, 224] => {
+                output.push(memory[224]);
+                pc = 152;
+            }
+            152 if memory[152..156] == [102, 8, 223, 223] => {
+                let x: i32 = 8;
+                let y: i32 = memory[223];
+                let d = 223;
+                memory[d] = checked(x.checked_mul(y), 152)?;
+                pc = 156;
+            }
+            153 if memory[153..157] == [8, 223, 223, 1001] => {
+                let x: i32 = memory[223];
+                let y: i32 = memory[223];
+                let d = cell(memory, 1001, 153)?;
+                memory[d] = (x == y) as i32;
+                pc = 157;
+            }
+            156 if memory[156..160] == [1001, 224, 5, 224] => {
+                let x: i32 = memory[224];
+                let y: i32 = 5;
+                let d = 224;
+                memory[d] = checked(x.checked_add(y), 156)?;
+                pc = 160;
+            }
+            158 if memory[158..161] == [5, 224, 1] => {
+                let c = memory[224];
+                let t = memory[1];
+                pc = if c != 0 { target(t, 158)? } else { 161 };
+            }
+            160 if memory[160..164] == [1, 223, 224, 223] => {
+                let x: i32 = memory[223];
+                let y: i32 = memory[224];
+                let d = 223;
+                memory[d] = checked(x.checked_add(y), 160)?;
+                pc = 164;
+            }
+            164 if memory[164..168] == [1102, 51, 49, 225] => {
+                let x: i32 = 51;
+                let y: i32 = 49;
+                let d = 225;
+                memory[d] = checked(x.checked_mul(y), 164)?;
+                pc = 168;
+            }
+            168 if memory[168..172] == [1101, 7, 15, 225] => {
+                let x: i32 = 7;
+                let y: i32 = 15;
+                let d = 225;
+                memory[d] = checked(x.checked_add(y), 168)?;
+                pc = 172;
+            }
+            169 if memory[169..173] == [7, 15, 225, 2] => {
+                let x: i32 = memory[15];
+                let y: i32 = memory[225];
+                let d = 2;
+                memory[d] = (x < y) as i32;
+                pc = 173;
+            }
+            172 if memory[172..176] == [2, 110, 106, 224] => {
+                let x: i32 = memory[110];
+                let y: i32 = memory[106];
+                let d = 224;
+                memory[d] = checked(x.checked_mul(y), 172)?;
+                pc = 176;
+            }
+            174 if memory[174..177] == [106, 224, 1001] => {
+                let c = 224;
+                let t = { let a = cell(memory, 1001, 174)?; memory[a] };
+                pc = if c == 0 { target(t, 174)? } else { 177 };
+            }
+            176 if memory[176..180] == [1001, 224, -4539, 224] => {
+                let x: i32 = memory[224];
+                let y: i32 = -4539;
+                let d = 224;
+                memory[d] = checked(x.checked_add(y), 176)?;
+                pc = 180;
+            }
+            180 if memory[180..182] == [4, 224] => {
+                output.push(memory[224]);
+                pc = 182;
+            }
+            182 if memory[182..186] == [102, 8, 223, 223] => {
+                let x: i32 = 8;
+                let y: i32 = memory[223];
+                let d = 223;
+                memory[d] = checked(x.checked_mul(y), 182)?;
+                pc = 186;
+            }
+            183 if memory[183..187] == [8, 223, 223, 101] => {
+                let x: i32 = memory[223];
+                let y: i32 = memory[223];
+                let d = 101;
+                memory[d] = (x == y) as i32;
+                pc = 187;
+            }
+            186 if memory[186..190] == [101, 3, 224, 224] => {
+                let x: i32 = 3;
+                let y: i32 = memory[224];
+                let d = 224;
+                memory[d] = checked(x.checked_add(y), 186)?;
+                pc = 190;
+            }
+            187 if memory[187..189] == [3, 224] => {
+                if input.is_empty() {
+                    return Ok(Stop::AwaitingInput { pc: 187 });
+                }
+                let d = 224;
+                memory[d] = input.pop_front().unwrap();
+                pc = 189;
+            }
+            190 if memory[190..194] == [1, 223, 224, 223] => {
+                let x: i32 = memory[223];
+                let y: i32 = memory[224];
+                let d = 223;
+                memory[d] = checked(x.checked_add(y), 190)?;
+                pc = 194;
+            }
+            194 if memory[194..198] == [1102, 88, 78, 225] => {
+                let x: i32 = 88;
+                let y: i32 = 78;
+                let d = 225;
+                memory[d] = checked(x.checked_mul(y), 194)?;
+                pc = 198;
+            }
+            198 if memory[198..202] == [102, 78, 101, 224] => {
+                let x: i32 = 78;
+                let y: i32 = memory[101];
+                let d = 224;
+                memory[d] = checked(x.checked_mul(y), 198)?;
+                pc = 202;
+            }
+            200 if memory[200..204] == [101, 224, 101, -6240] => {
+                let x: i32 = 224;
+                let y: i32 = memory[101];
+                let d = cell(memory, -6240, 200)?;
+                memory[d] = checked(x.checked_add(y), 200)?;
+                pc = 204;
+            }
+            202 if memory[202..206] == [101, -6240, 224, 224] => {
+                let x: i32 = -6240;
+                let y: i32 = memory[224];
+                let d = 224;
+                memory[d] = checked(x.checked_add(y), 202)?;
+                pc = 206;
+            }
+            206 if memory[206..208] == [4, 224] => {
+                output.push(memory[224]);
+                pc = 208;
+            }
+            208 if memory[208..212] == [1002, 223, 8, 223] => {
+                let x: i32 = memory[223];
+                let y: i32 = 8;
+                let d = 223;
+                memory[d] = checked(x.checked_mul(y), 208)?;
+                pc = 212;
+            }
+            210 if memory[210..214] == [8, 223, 101, 5] => {
+                let x: i32 = memory[223];
+                let y: i32 = memory[101];
+                let d = 5;
+                memory[d] = (x == y) as i32;
+                pc = 214;
+            }
+            212 if memory[212..216] == [101, 5, 224, 224] => {
+                let x: i32 = 5;
+                let y: i32 = memory[224];
+                let d = 224;
+                memory[d] = checked(x.checked_add(y), 212)?;
+                pc = 216;
+            }
+            213 if memory[213..216] == [5, 224, 224] => {
+                let c = memory[224];
+                let t = memory[224];
+                pc = if c != 0 { target(t, 213)? } else { 216 };
+            }
+            216 if memory[216..220] == [1, 224, 223, 223] => {
+                let x: i32 = memory[224];
+                let y: i32 = memory[223];
+                let d = 223;
+                memory[d] = checked(x.checked_add(y), 216)?;
+                pc = 220;
+            }
+            220 if memory[220..222] == [4, 223] => {
+                output.push(memory[223]);
+                pc = 222;
+            }
+            222 if memory[222..223] == [99] => {
+                return Ok(Stop::Halted);
+            }
+            238 if memory[238..241] == [1105, 0, 99999] => {
+                let c = 0;
+                let t = 99999;
+                pc = if c != 0 { target(t, 238)? } else { 241 };
+            }
+            241 if memory[241..244] == [1105, 227, 247] => {
+                let c = 227;
+                let t = 247;
+                pc = if c != 0 { target(t, 241)? } else { 244 };
+            }
+            244 if memory[244..247] == [1105, 1, 99999] => {
+                let c = 1;
+                let t = 99999;
+                pc = if c != 0 { target(t, 244)? } else { 247 };
+            }
+            245 if memory[245..249] == [1, 99999, 1005, 227] => {
+                let x: i32 = { let a = cell(memory, 99999, 245)?; memory[a] };
+                let y: i32 = { let a = cell(memory, 1005, 245)?; memory[a] };
+                let d = 227;
+                memory[d] = checked(x.checked_add(y), 245)?;
+                pc = 249;
+            }
+            247 if memory[247..250] == [1005, 227, 99999] => {
+                let c = memory[227];
+                let t = 99999;
+                pc = if c != 0 { target(t, 247)? } else { 250 };
+            }
+            250 if memory[250..253] == [1005, 0, 256] => {
+                let c = memory[0];
+                let t = 256;
+                pc = if c != 0 { target(t, 250)? } else { 253 };
+            }
+            253 if memory[253..256] == [1105, 1, 99999] => {
+                let c = 1;
+                let t = 99999;
+                pc = if c != 0 { target(t, 253)? } else { 256 };
+            }
+            254 if memory[254..258] == [1, 99999, 1106, 227] => {
+                let x: i32 = { let a = cell(memory, 99999, 254)?; memory[a] };
+                let y: i32 = { let a = cell(memory, 1106, 254)?; memory[a] };
+                let d = 227;
+                memory[d] = checked(x.checked_add(y), 254)?;
+                pc = 258;
+            }
+            256 if memory[256..259] == [1106, 227, 99999] => {
+                let c = 227;
+                let t = 99999;
+                pc = if c == 0 { target(t, 256)? } else { 259 };
+            }
+            259 if memory[259..262] == [1106, 0, 265] => {
+                let c = 0;
+                let t = 265;
+                pc = if c == 0 { target(t, 259)? } else { 262 };
+            }
+            262 if memory[262..265] == [1105, 1, 99999] => {
+                let c = 1;
+                let t = 99999;
+                pc = if c != 0 { target(t, 262)? } else { 265 };
+            }
+            263 if memory[263..267] == [1, 99999, 1006, 0] => {
+                let x: i32 = { let a = cell(memory, 99999, 263)?; memory[a] };
+                let y: i32 = { let a = cell(memory, 1006, 263)?; memory[a] };
+                let d = 0;
+                memory[d] = checked(x.checked_add(y), 263)?;
+                pc = 267;
+            }
+            265 if memory[265..268] == [1006, 0, 99999] => {
+                let c = memory[0];
+                let t = 99999;
+                pc = if c == 0 { target(t, 265)? } else { 268 };
+            }
+            268 if memory[268..271] == [1006, 227, 274] => {
+                let c = memory[227];
+                let t = 274;
+                pc = if c == 0 { target(t, 268)? } else { 271 };
+            }
+            271 if memory[271..274] == [1105, 1, 99999] => {
+                let c = 1;
+                let t = 99999;
+                pc = if c != 0 { target(t, 271)? } else { 274 };
+            }
+            272 if memory[272..276] == [1, 99999, 1105, 1] => {
+                let x: i32 = { let a = cell(memory, 99999, 272)?; memory[a] };
+                let y: i32 = { let a = cell(memory, 1105, 272)?; memory[a] };
+                let d = 1;
+                memory[d] = checked(x.checked_add(y), 272)?;
+                pc = 276;
+            }
+            274 if memory[274..277] == [1105, 1, 280] => {
+                let c = 1;
+                let t = 280;
+                pc = if c != 0 { target(t, 274)? } else { 277 };
+            }
+            275 if memory[275..279] == [1, 280, 1105, 1] => {
+                let x: i32 = memory[280];
+                let y: i32 = { let a = cell(memory, 1105, 275)?; memory[a] };
+                let d = 1;
+                memory[d] = checked(x.checked_add(y), 275)?;
+                pc = 279;
+            }
+            277 if memory[277..280] == [1105, 1, 99999] => {
+                let c = 1;
+                let t = 99999;
+                pc = if c != 0 { target(t, 277)? } else { 280 };
+            }
+            278 if memory[278..282] == [1, 99999, 1, 225] => {
+                let x: i32 = { let a = cell(memory, 99999, 278)?; memory[a] };
+                let y: i32 = memory[1];
+                let d = 225;
+                memory[d] = checked(x.checked_add(y), 278)?;
+                pc = 282;
+            }
+            280 if memory[280..284] == [1, 225, 225, 225] => {
+                let x: i32 = memory[225];
+                let y: i32 = memory[225];
+                let d = 225;
+                memory[d] = checked(x.checked_add(y), 280)?;
+                pc = 284;
+            }
+            284 if memory[284..288] == [1101, 294, 0, 0] => {
+                let x: i32 = 294;
+                let y: i32 = 0;
+                let d = 0;
+                memory[d] = checked(x.checked_add(y), 284)?;
+                pc = 288;
+            }
+            288 if memory[288..291] == [105, 1, 0] => {
+                let c = 1;
+                let t = memory[0];
+                pc = if c != 0 { target(t, 288)? } else { 291 };
+            }
+            289 if memory[289..293] == [1, 0, 1105, 1] => {
+                let x: i32 = memory[0];
+                let y: i32 = { let a = cell(memory, 1105, 289)?; memory[a] };
+                let d = 1;
+                memory[d] = checked(x.checked_add(y), 289)?;
+                pc = 293;
+            }
+            291 if memory[291..294] == [1105, 1, 99999] => {
+                let c = 1;
+                let t = 99999;
+                pc = if c != 0 { target(t, 291)? } else { 294 };
+            }
+            292 if memory[292..296] == [1, 99999, 1106, 0] => {
+                let x: i32 = { let a = cell(memory, 99999, 292)?; memory[a] };
+                let y: i32 = { let a = cell(memory, 1106, 292)?; memory[a] };
+                let d = 0;
+                memory[d] = checked(x.checked_add(y), 292)?;
+                pc = 296;
+            }
+            294 if memory[294..297] == [1106, 0, 300] => {
+                let c = 0;
+                let t = 300;
+                pc = if c == 0 { target(t, 294)? } else { 297 };
+            }
+            297 if memory[297..300] == [1105, 1, 99999] => {
+                let c = 1;
+                let t = 99999;
+                pc = if c != 0 { target(t, 297)? } else { 300 };
+            }
+            298 if memory[298..302] == [1, 99999, 1, 225] => {
+                let x: i32 = { let a = cell(memory, 99999, 298)?; memory[a] };
+                let y: i32 = memory[1];
+                let d = 225;
+                memory[d] = checked(x.checked_add(y), 298)?;
+                pc = 302;
+            }
+            300 if memory[300..304] == [1, 225, 225, 225] => {
+                let x: i32 = memory[225];
+                let y: i32 = memory[225];
+                let d = 225;
+                memory[d] = checked(x.checked_add(y), 300)?;
+                pc = 304;
+            }
+            304 if memory[304..308] == [1101, 314, 0, 0] => {
+                let x: i32 = 314;
+                let y: i32 = 0;
+                let d = 0;
+                memory[d] = checked(x.checked_add(y), 304)?;
+                pc = 308;
+            }
+            308 if memory[308..311] == [106, 0, 0] => {
+                let c = 0;
+                let t = memory[0];
+                pc = if c == 0 { target(t, 308)? } else { 311 };
+            }
+            311 if memory[311..314] == [1105, 1, 99999] => {
+                let c = 1;
+                let t = 99999;
+                pc = if c != 0 { target(t, 311)? } else { 314 };
+            }
+            312 if memory[312..316] == [1, 99999, 1107, 226] => {
+                let x: i32 = { let a = cell(memory, 99999, 312)?; memory[a] };
+                let y: i32 = { let a = cell(memory, 1107, 312)?; memory[a] };
+                let d = 226;
+                memory[d] = checked(x.checked_add(y), 312)?;
+                pc = 316;
+            }
+            314 if memory[314..318] == [1107, 226, 677, 224] => {
+                let x: i32 = 226;
+                let y: i32 = 677;
+                let d = 224;
+                memory[d] = (x < y) as i32;
+                pc = 318;
+            }
+            318 if memory[318..322] == [102, 2, 223, 223] => {
+                let x: i32 = 2;
+                let y: i32 = memory[223];
+                let d = 223;
+                memory[d] = checked(x.checked_mul(y), 318)?;
+                pc = 322;
+            }
+            319 if memory[319..323] == [2, 223, 223, 1006] => {
+                let x: i32 = memory[223];
+                let y: i32 = memory[223];
+                let d = cell(memory, 1006, 319)?;
+                memory[d] = checked(x.checked_mul(y), 319)?;
+                pc = 323;
+            }
+            322 if memory[322..325] == [1006, 224, 329] => {
+                let c = memory[224];
+                let t = 329;
+                pc = if c == 0 { target(t, 322)? } else { 325 };
+            }
+            325 if memory[325..329] == [101, 1, 223, 223] => {
+                let x: i32 = 1;
+                let y: i32 = memory[223];
+                let d = 223;
+                memory[d] = checked(x.checked_add(y), 325)?;
+                pc = 329;
+            }
+            326 if memory[326..330] == [1, 223, 223, 1108] => {
+                let x: i32 = memory[223];
+                let y: i32 = memory[223];
+                let d = cell(memory, 1108, 326)?;
+                memory[d] = checked(x.checked_add(y), 326)?;
+                pc = 330;
+            }
+            329 if memory[329..333] == [1108, 226, 677, 224] => {
+                let x: i32 = 226;
+                let y: i32 = 677;
+                let d = 224;
+                memory[d] = (x == y) as i32;
+                pc = 333;
+            }
+            333 if memory[333..337] == [1002, 223, 2, 223] => {
+                let x: i32 = memory[223];
+                let y: i32 = 2;
+                let d = 223;
+                memory[d] = checked(x.checked_mul(y), 333)?;
+                pc = 337;
+            }
+            335 if memory[335..339] == [2, 223, 1005, 224] => {
+                let x: i32 = memory[223];
+                let y: i32 = { let a = cell(memory, 1005, 335)?; memory[a] };
+                let d = 224;
+                memory[d] = checked(x.checked_mul(y), 335)?;
+                pc = 339;
+            }
+            337 if memory[337..340] == [1005, 224, 344] => {
+                let c = memory[224];
+                let t = 344;
+                pc = if c != 0 { target(t, 337)? } else { 340 };
+            }
+            340 if memory[340..344] == [101, 1, 223, 223] => {
+                let x: i32 = 1;
+                let y: i32 = memory[223];
+                let d = 223;
+                memory[d] = checked(x.checked_add(y), 340)?;
+                pc = 344;
+            }
+            341 if memory[341..345] == [1, 223, 223, 8] => {
+                let x: i32 = memory[223];
+                let y: i32 = memory[223];
+                let d = 8;
+                memory[d] = checked(x.checked_add(y), 341)?;
+                pc = 345;
+            }
+            344 if memory[344..348] == [8, 226, 677, 224] => {
+                let x: i32 = memory[226];
+                let y: i32 = memory[677];
+                let d = 224;
+                memory[d] = (x == y) as i32;
+                pc = 348;
+            }
+            348 if memory[348..352] == [102, 2, 223, 223] => {
+                let x: i32 = 2;
+                let y: i32 = memory[223];
+                let d = 223;
+                memory[d] = checked(x.checked_mul(y), 348)?;
+                pc = 352;
+            }
+            349 if memory[349..353] == [2, 223, 223, 1006] => {
+                let x: i32 = memory[223];
+                let y: i32 = memory[223];
+                let d = cell(memory, 1006, 349)?;
+                memory[d] = checked(x.checked_mul(y), 349)?;
+                pc = 353;
+            }
+            352 if memory[352..355] == [1006, 224, 359] => {
+                let c = memory[224];
+                let t = 359;
+                pc = if c == 0 { target(t, 352)? } else { 355 };
+            }
+            355 if memory[355..359] == [1001, 223, 1, 223] => {
+                let x: i32 = memory[223];
+                let y: i32 = 1;
+                let d = 223;
+                memory[d] = checked(x.checked_add(y), 355)?;
+                pc = 359;
+            }
+            357 if memory[357..361] == [1, 223, 1007, 226] => {
+                let x: i32 = memory[223];
+                let y: i32 = { let a = cell(memory, 1007, 357)?; memory[a] };
+                let d = 226;
+                memory[d] = checked(x.checked_add(y), 357)?;
+                pc = 361;
+            }
+            359 if memory[359..363] == [1007, 226, 677, 224] => {
+                let x: i32 = memory[226];
+                let y: i32 = 677;
+                let d = 224;
+                memory[d] = (x < y) as i32;
+                pc = 363;
+            }
+            363 if memory[363..367] == [1002, 223, 2, 223] => {
+                let x: i32 = memory[223];
+                let y: i32 = 2;
+                let d = 223;
+                memory[d] = checked(x.checked_mul(y), 363)?;
+                pc = 367;
+            }
+            365 if memory[365..369] == [2, 223, 1005, 224] => {
+                let x: i32 = memory[223];
+                let y: i32 = { let a = cell(memory, 1005, 365)?; memory[a] };
+                let d = 224;
+                memory[d] = checked(x.checked_mul(y), 365)?;
+                pc = 369;
+            }
+            367 if memory[367..370] == [1005, 224, 374] => {
+                let c = memory[224];
+                let t = 374;
+                pc = if c != 0 { target(t, 367)? } else { 370 };
+            }
+            370 if memory[370..374] == [101, 1, 223, 223] => {
+                let x: i32 = 1;
+                let y: i32 = memory[223];
+                let d = 223;
+                memory[d] = checked(x.checked_add(y), 370)?;
+                pc = 374;
+            }
+            371 if memory[371..375] == [1, 223, 223, 1008] => {
+                let x: i32 = memory[223];
+                let y: i32 = memory[223];
+                let d = cell(memory, 1008, 371)?;
+                memory[d] = checked(x.checked_add(y), 371)?;
+                pc = 375;
+            }
+            374 if memory[374..378] == [1008, 677, 677, 224] => {
+                let x: i32 = memory[677];
+                let y: i32 = 677;
+                let d = 224;
+                memory[d] = (x == y) as i32;
+                pc = 378;
+            }
+            378 if memory[378..382] == [1002, 223, 2, 223] => {
+                let x: i32 = memory[223];
+                let y: i32 = 2;
+                let d = 223;
+                memory[d] = checked(x.checked_mul(y), 378)?;
+                pc = 382;
+            }
+            380 if memory[380..384] == [2, 223, 1005, 224] => {
+                let x: i32 = memory[223];
+                let y: i32 = { let a = cell(memory, 1005, 380)?; memory[a] };
+                let d = 224;
+                memory[d] = checked(x.checked_mul(y), 380)?;
+                pc = 384;
+            }
+            382 if memory[382..385] == [1005, 224, 389] => {
+                let c = memory[224];
+                let t = 389;
+                pc = if c != 0 { target(t, 382)? } else { 385 };
+            }
+            385 if memory[385..389] == [1001, 223, 1, 223] => {
+                let x: i32 = memory[223];
+                let y: i32 = 1;
+                let d = 223;
+                memory[d] = checked(x.checked_add(y), 385)?;
+                pc = 389;
+            }
+            387 if memory[387..391] == [1, 223, 1108, 677] => {
+                let x: i32 = memory[223];
+                let y: i32 = { let a = cell(memory, 1108, 387)?; memory[a] };
+                let d = 677;
+                memory[d] = checked(x.checked_add(y), 387)?;
+                pc = 391;
+            }
+            389 if memory[389..393] == [1108, 677, 226, 224] => {
+                let x: i32 = 677;
+                let y: i32 = 226;
+                let d = 224;
+                memory[d] = (x == y) as i32;
+                pc = 393;
+            }
+            393 if memory[393..397] == [1002, 223, 2, 223] => {
+                let x: i32 = memory[223];
+                let y: i32 = 2;
+                let d = 223;
+                memory[d] = checked(x.checked_mul(y), 393)?;
+                pc = 397;
+            }
+            395 if memory[395..399] == [2, 223, 1006, 224] => {
+                let x: i32 = memory[223];
+                let y: i32 = { let a = cell(memory, 1006, 395)?; memory[a] };
+                let d = 224;
+                memory[d] = checked(x.checked_mul(y), 395)?;
+                pc = 399;
+            }
+            397 if memory[397..400] == [1006, 224, 404] => {
+                let c = memory[224];
+                let t = 404;
+                pc = if c == 0 { target(t, 397)? } else { 400 };
+            }
+            400 if memory[400..404] == [1001, 223, 1, 223] => {
+                let x: i32 = memory[223];
+                let y: i32 = 1;
+                let d = 223;
+                memory[d] = checked(x.checked_add(y), 400)?;
+                pc = 404;
+            }
+            402 if memory[402..406] == [1, 223, 1007, 226] => {
+                let x: i32 = memory[223];
+                let y: i32 = { let a = cell(memory, 1007, 402)?; memory[a] };
+                let d = 226;
+                memory[d] = checked(x.checked_add(y), 402)?;
+                pc = 406;
+            }
+            404 if memory[404..408] == [1007, 226, 226, 224] => {
+                let x: i32 = memory[226];
+                let y: i32 = 226;
+                let d = 224;
+                memory[d] = (x < y) as i32;
+                pc = 408;
+            }
+            408 if memory[408..412] == [1002, 223, 2, 223] => {
+                let x: i32 = memory[223];
+                let y: i32 = 2;
+                let d = 223;
+                memory[d] = checked(x.checked_mul(y), 408)?;
+                pc = 412;
+            }
+            410 if memory[410..414] == [2, 223, 1005, 224] => {
+                let x: i32 = memory[223];
+                let y: i32 = { let a = cell(memory, 1005, 410)?; memory[a] };
+                let d = 224;
+                memory[d] = checked(x.checked_mul(y), 410)?;
+                pc = 414;
+            }
+            412 if memory[412..415] == [1005, 224, 419] => {
+                let c = memory[224];
+                let t = 419;
+                pc = if c != 0 { target(t, 412)? } else { 415 };
+            }
+            415 if memory[415..419] == [1001, 223, 1, 223] => {
+                let x: i32 = memory[223];
+                let y: i32 = 1;
+                let d = 223;
+                memory[d] = checked(x.checked_add(y), 415)?;
+                pc = 419;
+            }
+            417 if memory[417..421] == [1, 223, 1107, 677] => {
+                let x: i32 = memory[223];
+                let y: i32 = { let a = cell(memory, 1107, 417)?; memory[a] };
+                let d = 677;
+                memory[d] = checked(x.checked_add(y), 417)?;
+                pc = 421;
+            }
+            419 if memory[419..423] == [1107, 677, 226, 224] => {
+                let x: i32 = 677;
+                let y: i32 = 226;
+                let d = 224;
+                memory[d] = (x < y) as i32;
+                pc = 423;
+            }
+            423 if memory[423..427] == [1002, 223, 2, 223] => {
+                let x: i32 = memory[223];
+                let y: i32 = 2;
+                let d = 223;
+                memory[d] = checked(x.checked_mul(y), 423)?;
+                pc = 427;
+            }
+            425 if memory[425..429] == [2, 223, 1006, 224] => {
+                let x: i32 = memory[223];
+                let y: i32 = { let a = cell(memory, 1006, 425)?; memory[a] };
+                let d = 224;
+                memory[d] = checked(x.checked_mul(y), 425)?;
+                pc = 429;
+            }
+            427 if memory[427..430] == [1006, 224, 434] => {
+                let c = memory[224];
+                let t = 434;
+                pc = if c == 0 { target(t, 427)? } else { 430 };
+            }
+            430 if memory[430..434] == [101, 1, 223, 223] => {
+                let x: i32 = 1;
+                let y: i32 = memory[223];
+                let d = 223;
+                memory[d] = checked(x.checked_add(y), 430)?;
+                pc = 434;
+            }
+            431 if memory[431..435] == [1, 223, 223, 108] => {
+                let x: i32 = memory[223];
+                let y: i32 = memory[223];
+                let d = 108;
+                memory[d] = checked(x.checked_add(y), 431)?;
+                pc = 435;
+            }
+            434 if memory[434..438] == [108, 677, 677, 224] => {
+                let x: i32 = 677;
+                let y: i32 = memory[677];
+                let d = 224;
+                memory[d] = (x == y) as i32;
+                pc = 438;
+            }
+            438 if memory[438..442] == [1002, 223, 2, 223] => {
+                let x: i32 = memory[223];
+                let y: i32 = 2;
+                let d = 223;
+                memory[d] = checked(x.checked_mul(y), 438)?;
+                pc = 442;
+            }
+            440 if memory[440..444] == [2, 223, 1005, 224] => {
+                let x: i32 = memory[223];
+                let y: i32 = { let a = cell(memory, 1005, 440)?; memory[a] };
+                let d = 224;
+                memory[d] = checked(x.checked_mul(y), 440)?;
+                pc = 444;
+            }
+            442 if memory[442..445] == [1005, 224, 449] => {
+                let c = memory[224];
+                let t = 449;
+                pc = if c != 0 { target(t, 442)? } else { 445 };
+            }
+            445 if memory[445..449] == [1001, 223, 1, 223] => {
+                let x: i32 = memory[223];
+                let y: i32 = 1;
+                let d = 223;
+                memory[d] = checked(x.checked_add(y), 445)?;
+                pc = 449;
+            }
+            447 if memory[447..451] == [1, 223, 1107, 677] => {
+                let x: i32 = memory[223];
+                let y: i32 = { let a = cell(memory, 1107, 447)?; memory[a] };
+                let d = 677;
+                memory[d] = checked(x.checked_add(y), 447)?;
+                pc = 451;
+            }
+            449 if memory[449..453] == [1107, 677, 677, 224] => {
+                let x: i32 = 677;
+                let y: i32 = 677;
+                let d = 224;
+                memory[d] = (x < y) as i32;
+                pc = 453;
+            }
+            453 if memory[453..457] == [102, 2, 223, 223] => {
+                let x: i32 = 2;
+                let y: i32 = memory[223];
+                let d = 223;
+                memory[d] = checked(x.checked_mul(y), 453)?;
+                pc = 457;
+            }
+            454 if memory[454..458] == [2, 223, 223, 1005] => {
+                let x: i32 = memory[223];
+                let y: i32 = memory[223];
+                let d = cell(memory, 1005, 454)?;
+                memory[d] = checked(x.checked_mul(y), 454)?;
+                pc = 458;
+            }
+            457 if memory[457..460] == [1005, 224, 464] => {
+                let c = memory[224];
+                let t = 464;
+                pc = if c != 0 { target(t, 457)? } else { 460 };
+            }
+            460 if memory[460..464] == [1001, 223, 1, 223] => {
+                let x: i32 = memory[223];
+                let y: i32 = 1;
+                let d = 223;
+                memory[d] = checked(x.checked_add(y), 460)?;
+                pc = 464;
+            }
+            462 if memory[462..466] == [1, 223, 108, 226] => {
+                let x: i32 = memory[223];
+                let y: i32 = memory[108];
+                let d = 226;
+                memory[d] = checked(x.checked_add(y), 462)?;
+                pc = 466;
+            }
+            464 if memory[464..468] == [108, 226, 226, 224] => {
+                let x: i32 = 226;
+                let y: i32 = memory[226];
+                let d = 224;
+                memory[d] = (x == y) as i32;
+                pc = 468;
+            }
+            468 if memory[468..472] == [1002, 223, 2, 223] => {
+                let x: i32 = memory[223];
+                let y: i32 = 2;
+                let d = 223;
+                memory[d] = checked(x.checked_mul(y), 468)?;
+                pc = 472;
+            }
+            470 if memory[470..474] == [2, 223, 1006, 224] => {
+                let x: i32 = memory[223];
+                let y: i32 = { let a = cell(memory, 1006, 470)?; memory[a] };
+                let d = 224;
+                memory[d] = checked(x.checked_mul(y), 470)?;
+                pc = 474;
+            }
+            472 if memory[472..475] == [1006, 224, 479] => {
+                let c = memory[224];
+                let t = 479;
+                pc = if c == 0 { target(t, 472)? } else { 475 };
+            }
+            475 if memory[475..479] == [1001, 223, 1, 223] => {
+                let x: i32 = memory[223];
+                let y: i32 = 1;
+                let d = 223;
+                memory[d] = checked(x.checked_add(y), 475)?;
+                pc = 479;
+            }
+            477 if memory[477..481] == [1, 223, 1008, 226] => {
+                let x: i32 = memory[223];
+                let y: i32 = { let a = cell(memory, 1008, 477)?; memory[a] };
+                let d = 226;
+                memory[d] = checked(x.checked_add(y), 477)?;
+                pc = 481;
+            }
+            479 if memory[479..483] == [1008, 226, 226, 224] => {
+                let x: i32 = memory[226];
+                let y: i32 = 226;
+                let d = 224;
+                memory[d] = (x == y) as i32;
+                pc = 483;
+            }
+            483 if memory[483..487] == [102, 2, 223, 223] => {
+                let x: i32 = 2;
+                let y: i32 = memory[223];
+                let d = 223;
+                memory[d] = checked(x.checked_mul(y), 483)?;
+                pc = 487;
+            }
+            484 if memory[484..488] == [2, 223, 223, 1005] => {
+                let x: i32 = memory[223];
+                let y: i32 = memory[223];
+                let d = cell(memory, 1005, 484)?;
+                memory[d] = checked(x.checked_mul(y), 484)?;
+                pc = 488;
+            }
+            487 if memory[487..490] == [1005, 224, 494] => {
+                let c = memory[224];
+                let t = 494;
+                pc = if c != 0 { target(t, 487)? } else { 490 };
+            }
+            490 if memory[490..494] == [101, 1, 223, 223] => {
+                let x: i32 = 1;
+                let y: i32 = memory[223];
+                let d = 223;
+                memory[d] = checked(x.checked_add(y), 490)?;
+                pc = 494;
+            }
+            491 if memory[491..495] == [1, 223, 223, 108] => {
+                let x: i32 = memory[223];
+                let y: i32 = memory[223];
+                let d = 108;
+                memory[d] = checked(x.checked_add(y), 491)?;
+                pc = 495;
+            }
+            494 if memory[494..498] == [108, 677, 226, 224] => {
+                let x: i32 = 677;
+                let y: i32 = memory[226];
+                let d = 224;
+                memory[d] = (x == y) as i32;
+                pc = 498;
+            }
+            498 if memory[498..502] == [102, 2, 223, 223] => {
+                let x: i32 = 2;
+                let y: i32 = memory[223];
+                let d = 223;
+                memory[d] = checked(x.checked_mul(y), 498)?;
+                pc = 502;
+            }
+            499 if memory[499..503] == [2, 223, 223, 1005] => {
+                let x: i32 = memory[223];
+                let y: i32 = memory[223];
+                let d = cell(memory, 1005, 499)?;
+                memory[d] = checked(x.checked_mul(y), 499)?;
+                pc = 503;
+            }
+            502 if memory[502..505] == [1005, 224, 509] => {
+                let c = memory[224];
+                let t = 509;
+                pc = if c != 0 { target(t, 502)? } else { 505 };
+            }
+            505 if memory[505..509] == [1001, 223, 1, 223] => {
+                let x: i32 = memory[223];
+                let y: i32 = 1;
+                let d = 223;
+                memory[d] = checked(x.checked_add(y), 505)?;
+                pc = 509;
+            }
+            507 if memory[507..511] == [1, 223, 8, 677] => {
+                let x: i32 = memory[223];
+                let y: i32 = memory[8];
+                let d = 677;
+                memory[d] = checked(x.checked_add(y), 507)?;
+                pc = 511;
+            }
+            509 if memory[509..513] == [8, 677, 226, 224] => {
+                let x: i32 = memory[677];
+                let y: i32 = memory[226];
+                let d = 224;
+                memory[d] = (x == y) as i32;
+                pc = 513;
+            }
+            513 if memory[513..517] == [1002, 223, 2, 223] => {
+                let x: i32 = memory[223];
+                let y: i32 = 2;
+                let d = 223;
+                memory[d] = checked(x.checked_mul(y), 513)?;
+                pc = 517;
+            }
+            515 if memory[515..519] == [2, 223, 1006, 224] => {
+                let x: i32 = memory[223];
+                let y: i32 = { let a = cell(memory, 1006, 515)?; memory[a] };
+                let d = 224;
+                memory[d] = checked(x.checked_mul(y), 515)?;
+                pc = 519;
+            }
+            517 if memory[517..520] == [1006, 224, 524] => {
+                let c = memory[224];
+                let t = 524;
+                pc = if c == 0 { target(t, 517)? } else { 520 };
+            }
+            520 if memory[520..524] == [101, 1, 223, 223] => {
+                let x: i32 = 1;
+                let y: i32 = memory[223];
+                let d = 223;
+                memory[d] = checked(x.checked_add(y), 520)?;
+                pc = 524;
+            }
+            521 if memory[521..525] == [1, 223, 223, 7] => {
+                let x: i32 = memory[223];
+                let y: i32 = memory[223];
+                let d = 7;
+                memory[d] = checked(x.checked_add(y), 521)?;
+                pc = 525;
+            }
+            524 if memory[524..528] == [7, 226, 677, 224] => {
+                let x: i32 = memory[226];
+                let y: i32 = memory[677];
+                let d = 224;
+                memory[d] = (x < y) as i32;
+                pc = 528;
+            }
+            528 if memory[528..532] == [1002, 223, 2, 223] => {
+                let x: i32 = memory[223];
+                let y: i32 = 2;
+                let d = 223;
+                memory[d] = checked(x.checked_mul(y), 528)?;
+                pc = 532;
+            }
+            530 if memory[530..534] == [2, 223, 1006, 224] => {
+                let x: i32 = memory[223];
+                let y: i32 = { let a = cell(memory, 1006, 530)?; memory[a] };
+                let d = 224;
+                memory[d] = checked(x.checked_mul(y), 530)?;
+                pc = 534;
+            }
+            532 if memory[532..535] == [1006, 224, 539] => {
+                let c = memory[224];
+                let t = 539;
+                pc = if c == 0 { target(t, 532)? } else { 535 };
+            }
+            535 if memory[535..539] == [101, 1, 223, 223] => {
+                let x: i32 = 1;
+                let y: i32 = memory[223];
+                let d = 223;
+                memory[d] = checked(x.checked_add(y), 535)?;
+                pc = 539;
+            }
+            536 if memory[536..540] == [1, 223, 223, 7] => {
+                let x: i32 = memory[223];
+                let y: i32 = memory[223];
+                let d = 7;
+                memory[d] = checked(x.checked_add(y), 536)?;
+                pc = 540;
+            }
+            539 if memory[539..543] == [7, 677, 226, 224] => {
+                let x: i32 = memory[677];
+                let y: i32 = memory[226];
+                let d = 224;
+                memory[d] = (x < y) as i32;
+                pc = 543;
+            }
+            543 if memory[543..547] == [102, 2, 223, 223] => {
+                let x: i32 = 2;
+                let y: i32 = memory[223];
+                let d = 223;
+                memory[d] = checked(x.checked_mul(y), 543)?;
+                pc = 547;
+            }
+            544 if memory[544..548] == [2, 223, 223, 1006] => {
+                let x: i32 = memory[223];
+                let y: i32 = memory[223];
+                let d = cell(memory, 1006, 544)?;
+                memory[d] = checked(x.checked_mul(y), 544)?;
+                pc = 548;
+            }
+            547 if memory[547..550] == [1006, 224, 554] => {
+                let c = memory[224];
+                let t = 554;
+                pc = if c == 0 { target(t, 547)? } else { 550 };
+            }
+            550 if memory[550..554] == [1001, 223, 1, 223] => {
+                let x: i32 = memory[223];
+                let y: i32 = 1;
+                let d = 223;
+                memory[d] = checked(x.checked_add(y), 550)?;
+                pc = 554;
+            }
+            552 if memory[552..556] == [1, 223, 7, 226] => {
+                let x: i32 = memory[223];
+                let y: i32 = memory[7];
+                let d = 226;
+                memory[d] = checked(x.checked_add(y), 552)?;
+                pc = 556;
+            }
+            554 if memory[554..558] == [7, 226, 226, 224] => {
+                let x: i32 = memory[226];
+                let y: i32 = memory[226];
+                let d = 224;
+                memory[d] = (x < y) as i32;
+                pc = 558;
+            }
+            558 if memory[558..562] == [1002, 223, 2, 223] => {
+                let x: i32 = memory[223];
+                let y: i32 = 2;
+                let d = 223;
+                memory[d] = checked(x.checked_mul(y), 558)?;
+                pc = 562;
+            }
+            560 if memory[560..564] == [2, 223, 1006, 224] => {
+                let x: i32 = memory[223];
+                let y: i32 = { let a = cell(memory, 1006, 560)?; memory[a] };
+                let d = 224;
+                memory[d] = checked(x.checked_mul(y), 560)?;
+                pc = 564;
+            }
+            562 if memory[562..565] == [1006, 224, 569] => {
+                let c = memory[224];
+                let t = 569;
+                pc = if c == 0 { target(t, 562)? } else { 565 };
+            }
+            565 if memory[565..569] == [101, 1, 223, 223] => {
+                let x: i32 = 1;
+                let y: i32 = memory[223];
+                let d = 223;
+                memory[d] = checked(x.checked_add(y), 565)?;
+                pc = 569;
+            }
+            566 if memory[566..570] == [1, 223, 223, 107] => {
+                let x: i32 = memory[223];
+                let y: i32 = memory[223];
+                let d = 107;
+                memory[d] = checked(x.checked_add(y), 566)?;
+                pc = 570;
+            }
+            569 if memory[569..573] == [107, 677, 677, 224] => {
+                let x: i32 = 677;
+                let y: i32 = memory[677];
+                let d = 224;
+                memory[d] = (x < y) as i32;
+                pc = 573;
+            }
+            573 if memory[573..577] == [102, 2, 223, 223] => {
+                let x: i32 = 2;
+                let y: i32 = memory[223];
+                let d = 223;
+                memory[d] = checked(x.checked_mul(y), 573)?;
+                pc = 577;
+            }
+            574 if memory[574..578] == [2, 223, 223, 1006] => {
+                let x: i32 = memory[223];
+                let y: i32 = memory[223];
+                let d = cell(memory, 1006, 574)?;
+                memory[d] = checked(x.checked_mul(y), 574)?;
+                pc = 578;
+            }
+            577 if memory[577..580] == [1006, 224, 584] => {
+                let c = memory[224];
+                let t = 584;
+                pc = if c == 0 { target(t, 577)? } else { 580 };
+            }
+            580 if memory[580..584] == [101, 1, 223, 223] => {
+                let x: i32 = 1;
+                let y: i32 = memory[223];
+                let d = 223;
+                memory[d] = checked(x.checked_add(y), 580)?;
+                pc = 584;
+            }
+            581 if memory[581..585] == [1, 223, 223, 1108] => {
+                let x: i32 = memory[223];
+                let y: i32 = memory[223];
+                let d = cell(memory, 1108, 581)?;
+                memory[d] = checked(x.checked_add(y), 581)?;
+                pc = 585;
+            }
+            584 if memory[584..588] == [1108, 677, 677, 224] => {
+                let x: i32 = 677;
+                let y: i32 = 677;
+                let d = 224;
+                memory[d] = (x == y) as i32;
+                pc = 588;
+            }
+            588 if memory[588..592] == [102, 2, 223, 223] => {
+                let x: i32 = 2;
+                let y: i32 = memory[223];
+                let d = 223;
+                memory[d] = checked(x.checked_mul(y), 588)?;
+                pc = 592;
+            }
+            589 if memory[589..593] == [2, 223, 223, 1006] => {
+                let x: i32 = memory[223];
+                let y: i32 = memory[223];
+                let d = cell(memory, 1006, 589)?;
+                memory[d] = checked(x.checked_mul(y), 589)?;
+                pc = 593;
+            }
+            592 if memory[592..595] == [1006, 224, 599] => {
+                let c = memory[224];
+                let t = 599;
+                pc = if c == 0 { target(t, 592)? } else { 595 };
+            }
+            595 if memory[595..599] == [1001, 223, 1, 223] => {
+                let x: i32 = memory[223];
+                let y: i32 = 1;
+                let d = 223;
+                memory[d] = checked(x.checked_add(y), 595)?;
+                pc = 599;
+            }
+            597 if memory[597..601] == [1, 223, 1008, 677] => {
+                let x: i32 = memory[223];
+                let y: i32 = { let a = cell(memory, 1008, 597)?; memory[a] };
+                let d = 677;
+                memory[d] = checked(x.checked_add(y), 597)?;
+                pc = 601;
+            }
+            599 if memory[599..603] == [1008, 677, 226, 224] => {
+                let x: i32 = memory[677];
+                let y: i32 = 226;
+                let d = 224;
+                memory[d] = (x == y) as i32;
+                pc = 603;
+            }
+            603 if memory[603..607] == [1002, 223, 2, 223] => {
+                let x: i32 = memory[223];
+                let y: i32 = 2;
+                let d = 223;
+                memory[d] = checked(x.checked_mul(y), 603)?;
+                pc = 607;
+            }
+            605 if memory[605..609] == [2, 223, 1005, 224] => {
+                let x: i32 = memory[223];
+                let y: i32 = { let a = cell(memory, 1005, 605)?; memory[a] };
+                let d = 224;
+                memory[d] = checked(x.checked_mul(y), 605)?;
+                pc = 609;
+            }
+            607 if memory[607..610] == [1005, 224, 614] => {
+                let c = memory[224];
+                let t = 614;
+                pc = if c != 0 { target(t, 607)? } else { 610 };
+            }
+            610 if memory[610..614] == [1001, 223, 1, 223] => {
+                let x: i32 = memory[223];
+                let y: i32 = 1;
+                let d = 223;
+                memory[d] = checked(x.checked_add(y), 610)?;
+                pc = 614;
+            }
+            612 if memory[612..616] == [1, 223, 8, 677] => {
+                let x: i32 = memory[223];
+                let y: i32 = memory[8];
+                let d = 677;
+                memory[d] = checked(x.checked_add(y), 612)?;
+                pc = 616;
+            }
+            614 if memory[614..618] == [8, 677, 677, 224] => {
+                let x: i32 = memory[677];
+                let y: i32 = memory[677];
+                let d = 224;
+                memory[d] = (x == y) as i32;
+                pc = 618;
+            }
+            618 if memory[618..622] == [1002, 223, 2, 223] => {
+                let x: i32 = memory[223];
+                let y: i32 = 2;
+                let d = 223;
+                memory[d] = checked(x.checked_mul(y), 618)?;
+                pc = 622;
+            }
+            620 if memory[620..624] == [2, 223, 1006, 224] => {
+                let x: i32 = memory[223];
+                let y: i32 = { let a = cell(memory, 1006, 620)?; memory[a] };
+                let d = 224;
+                memory[d] = checked(x.checked_mul(y), 620)?;
+                pc = 624;
+            }
+            622 if memory[622..625] == [1006, 224, 629] => {
+                let c = memory[224];
+                let t = 629;
+                pc = if c == 0 { target(t, 622)? } else { 625 };
+            }
+            625 if memory[625..629] == [1001, 223, 1, 223] => {
+                let x: i32 = memory[223];
+                let y: i32 = 1;
+                let d = 223;
+                memory[d] = checked(x.checked_add(y), 625)?;
+                pc = 629;
+            }
+            627 if memory[627..631] == [1, 223, 107, 226] => {
+                let x: i32 = memory[223];
+                let y: i32 = memory[107];
+                let d = 226;
+                memory[d] = checked(x.checked_add(y), 627)?;
+                pc = 631;
+            }
+            629 if memory[629..633] == [107, 226, 677, 224] => {
+                let x: i32 = 226;
+                let y: i32 = memory[677];
+                let d = 224;
+                memory[d] = (x < y) as i32;
+                pc = 633;
+            }
+            633 if memory[633..637] == [1002, 223, 2, 223] => {
+                let x: i32 = memory[223];
+                let y: i32 = 2;
+                let d = 223;
+                memory[d] = checked(x.checked_mul(y), 633)?;
+                pc = 637;
+            }
+            635 if memory[635..639] == [2, 223, 1006, 224] => {
+                let x: i32 = memory[223];
+                let y: i32 = { let a = cell(memory, 1006, 635)?; memory[a] };
+                let d = 224;
+                memory[d] = checked(x.checked_mul(y), 635)?;
+                pc = 639;
+            }
+            637 if memory[637..640] == [1006, 224, 644] => {
+                let c = memory[224];
+                let t = 644;
+                pc = if c == 0 { target(t, 637)? } else { 640 };
+            }
+            640 if memory[640..644] == [101, 1, 223, 223] => {
+                let x: i32 = 1;
+                let y: i32 = memory[223];
+                let d = 223;
+                memory[d] = checked(x.checked_add(y), 640)?;
+                pc = 644;
+            }
+            641 if memory[641..645] == [1, 223, 223, 1007] => {
+                let x: i32 = memory[223];
+                let y: i32 = memory[223];
+                let d = cell(memory, 1007, 641)?;
+                memory[d] = checked(x.checked_add(y), 641)?;
+                pc = 645;
+            }
+            644 if memory[644..648] == [1007, 677, 677, 224] => {
+                let x: i32 = memory[677];
+                let y: i32 = 677;
+                let d = 224;
+                memory[d] = (x < y) as i32;
+                pc = 648;
+            }
+            648 if memory[648..652] == [102, 2, 223, 223] => {
+                let x: i32 = 2;
+                let y: i32 = memory[223];
+                let d = 223;
+                memory[d] = checked(x.checked_mul(y), 648)?;
+                pc = 652;
+            }
+            649 if memory[649..653] == [2, 223, 223, 1006] => {
+                let x: i32 = memory[223];
+                let y: i32 = memory[223];
+                let d = cell(memory, 1006, 649)?;
+                memory[d] = checked(x.checked_mul(y), 649)?;
+                pc = 653;
+            }
+            652 if memory[652..655] == [1006, 224, 659] => {
+                let c = memory[224];
+                let t = 659;
+                pc = if c == 0 { target(t, 652)? } else { 655 };
+            }
+            655 if memory[655..659] == [101, 1, 223, 223] => {
+                let x: i32 = 1;
+                let y: i32 = memory[223];
+                let d = 223;
+                memory[d] = checked(x.checked_add(y), 655)?;
+                pc = 659;
+            }
+            656 if memory[656..660] == [1, 223, 223, 107] => {
+                let x: i32 = memory[223];
+                let y: i32 = memory[223];
+                let d = 107;
+                memory[d] = checked(x.checked_add(y), 656)?;
+                pc = 660;
+            }
+            659 if memory[659..663] == [107, 226, 226, 224] => {
+                let x: i32 = 226;
+                let y: i32 = memory[226];
+                let d = 224;
+                memory[d] = (x < y) as i32;
+                pc = 663;
+            }
+            663 if memory[663..667] == [1002, 223, 2, 223] => {
+                let x: i32 = memory[223];
+                let y: i32 = 2;
+                let d = 223;
+                memory[d] = checked(x.checked_mul(y), 663)?;
+                pc = 667;
+            }
+            665 if memory[665..669] == [2, 223, 1006, 224] => {
+                let x: i32 = memory[223];
+                let y: i32 = { let a = cell(memory, 1006, 665)?; memory[a] };
+                let d = 224;
+                memory[d] = checked(x.checked_mul(y), 665)?;
+                pc = 669;
+            }
+            667 if memory[667..670] == [1006, 224, 674] => {
+                let c = memory[224];
+                let t = 674;
+                pc = if c == 0 { target(t, 667)? } else { 670 };
+            }
+            670 if memory[670..674] == [1001, 223, 1, 223] => {
+                let x: i32 = memory[223];
+                let y: i32 = 1;
+                let d = 223;
+                memory[d] = checked(x.checked_add(y), 670)?;
+                pc = 674;
+            }
+            672 if memory[672..676] == [1, 223, 4, 223] => {
+                let x: i32 = memory[223];
+                let y: i32 = memory[4];
+                let d = 223;
+                memory[d] = checked(x.checked_add(y), 672)?;
+                pc = 676;
+            }
+            674 if memory[674..676] == [4, 223] => {
+                output.push(memory[223]);
+                pc = 676;
+            }
+            676 if memory[676..677] == [99] => {
+                return Ok(Stop::Halted);
+            }
+            _ => {
+                if let Some(stop) = interpret(memory, &mut pc, &mut rb, input, output)? {
+                    return Ok(stop);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,230 @@
+use super::cfg::Graph;
+use super::instruction::Parameter;
+use super::{IntCodeError, ParameterMode};
+use std::collections::{HashMap, HashSet};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Kind {
+    /// Wrote to an address which had already run as part of an instruction.
+    AlreadyExecuted,
+    /// Ran an instruction containing an address that an earlier write changed.
+    LaterExecuted,
+    /// Found without running the program: a reachable instruction writes
+    /// into another reachable instruction.
+    Static,
+}
+
+/// An instruction at `pc` writing into code at `address`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Modification {
+    pub pc: usize,
+    pub address: usize,
+    pub kind: Kind,
+}
+
+/// Keeps track of which addresses a `Machine` has executed and written to, so
+/// it can notice code changing while it runs. Memory patched before the
+/// program starts, like the noun and verb in day 2, never goes through here
+/// and so is never reported.
+#[derive(Clone, Debug, Default)]
+pub struct Watcher {
+    strict: bool,
+    executed: HashSet<usize>,
+    /// The instruction which last wrote to each address.
+    written: HashMap<usize, usize>,
+    modifications: Vec<Modification>,
+}
+
+impl Watcher {
+    pub fn new(strict: bool) -> Watcher {
+        Watcher {
+            strict,
+            ..Watcher::default()
+        }
+    }
+
+    pub fn modifications(&self) -> &[Modification] {
+        &self.modifications
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.executed.clear();
+        self.written.clear();
+        self.modifications.clear();
+    }
+
+    fn report(&mut self, modification: Modification) -> Result<(), IntCodeError> {
+        if self.strict {
+            return Err(IntCodeError::SelfModification {
+                pc: modification.pc,
+                address: modification.address,
+            });
+        }
+        self.modifications.push(modification);
+        Ok(())
+    }
+
+    /// Called before the instruction at `pc`, `size` words long, runs.
+    pub(crate) fn fetch(&mut self, pc: usize, size: usize) -> Result<(), IntCodeError> {
+        for address in pc..pc + size {
+            if let Some(writer) = self.written.remove(&address) {
+                self.report(Modification {
+                    pc: writer,
+                    address,
+                    kind: Kind::LaterExecuted,
+                })?;
+            }
+            self.executed.insert(address);
+        }
+        Ok(())
+    }
+
+    /// Called before the instruction at `pc` writes to `address`.
+    pub(crate) fn write(&mut self, pc: usize, address: usize) -> Result<(), IntCodeError> {
+        if self.executed.contains(&address) {
+            self.report(Modification {
+                pc,
+                address,
+                kind: Kind::AlreadyExecuted,
+            })?;
+        }
+        self.written.insert(address, pc);
+        Ok(())
+    }
+}
+
+/// Finds reachable instructions which write, in position mode, into the
+/// words of reachable instructions. Writes through relative mode can't be
+/// followed without running the program.
+pub fn analyse(program: &[i32]) -> Vec<Modification> {
+    let graph = Graph::build(program);
+    let code: HashSet<usize> = graph
+        .blocks
+        .values()
+        .flat_map(|block| block.start..block.end)
+        .collect();
+
+    let mut modifications = vec![];
+    for (ptr, instruction) in graph.blocks.values().flat_map(|b| &b.instructions) {
+        if let Some(Parameter {
+            value: address,
+            mode: ParameterMode::Position,
+        }) = instruction.destination()
+        {
+            if address >= 0 && code.contains(&(address as usize)) {
+                modifications.push(Modification {
+                    pc: *ptr,
+                    address: address as usize,
+                    kind: Kind::Static,
+                });
+            }
+        }
+    }
+
+    modifications
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::day2;
+    use crate::day5;
+    use crate::intcode::Machine;
+
+    fn day2_program() -> Vec<i32> {
+        day2::converter(include_str!("../../input/2019/day2.txt").trim())
+    }
+
+    fn day5_program() -> Vec<i32> {
+        day5::converter(include_str!("../../input/2019/day5.txt").trim())
+    }
+
+    #[test]
+    fn programs_that_leave_their_code_alone_run_in_strict_mode() {
+        let mut machine = Machine::new(&[3, 9, 1001, 9, 1, 9, 4, 9, 99, 0]);
+        machine.watch(true);
+        machine.push_input(1);
+        machine.run().unwrap();
+        assert_eq!(machine.take_output(), vec![2]);
+        assert!(analyse(&[3, 9, 1001, 9, 1, 9, 4, 9, 99, 0]).is_empty())
+    }
+
+    #[test]
+    fn patches_before_running_are_not_reported() {
+        let mut machine = Machine::new(&day2_program());
+        machine.watch(false);
+        machine.poke(1, 12);
+        machine.poke(2, 2);
+        machine.run().unwrap();
+
+        // Every instruction writes over an operand of one which has already run.
+        let modifications = machine.modifications();
+        assert_eq!(
+            modifications[0],
+            Modification {
+                pc: 0,
+                address: 3,
+                kind: Kind::AlreadyExecuted
+            }
+        );
+        assert!(modifications
+            .iter()
+            .all(|m| m.address != 1 && m.address != 2));
+        assert!(modifications
+            .iter()
+            .all(|m| m.kind == Kind::AlreadyExecuted))
+    }
+
+    #[test]
+    fn strict_mode_stops_day2_at_its_first_write() {
+        let mut machine = Machine::new(&day2_program());
+        machine.watch(true);
+        machine.poke(1, 12);
+        machine.poke(2, 2);
+        match machine.run() {
+            Err(IntCodeError::SelfModification { pc, address }) => {
+                assert_eq!((pc, address), (0, 3))
+            }
+            other => panic!("expected a self-modification error, got {:?}", other),
+        }
+        assert_eq!(machine.steps(), 0)
+    }
+
+    #[test]
+    fn writes_to_code_that_runs_later_are_reported() {
+        let mut machine = Machine::new(&day5_program());
+        machine.watch(false);
+        machine.push_input(1);
+        machine.run().unwrap();
+        assert!(machine.modifications().contains(&Modification {
+            pc: 2,
+            address: 6,
+            kind: Kind::LaterExecuted
+        }))
+    }
+
+    #[test]
+    fn strict_mode_stops_at_the_first_modification() {
+        let mut machine = Machine::new(&day5_program());
+        machine.watch(true);
+        machine.push_input(1);
+        match machine.run() {
+            Err(IntCodeError::SelfModification { pc, address }) => {
+                assert_eq!((pc, address), (2, 6))
+            }
+            other => panic!("expected a self-modification error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn static_analysis_finds_writes_into_code() {
+        assert!(analyse(&day5_program()).contains(&Modification {
+            pc: 2,
+            address: 6,
+            kind: Kind::Static
+        }));
+        let day2 = analyse(&day2_program());
+        assert_eq!(day2[0].pc, 0);
+        assert_eq!(day2[0].address, 3)
+    }
+}
@@ -1,5 +1,3 @@
-#![feature(try_trait)]
-
 use aoc_runner_derive::aoc_lib;
 
 extern crate failure;
@@ -11,6 +9,16 @@ pub mod day3;
 pub mod day4;
 pub mod day5;
 pub mod day6;
-pub(crate) mod intcode;
+pub mod day11;
+pub mod day13;
+pub mod day15;
+pub mod day17;
+pub mod day19;
+pub mod day21;
+pub mod day25;
+pub mod compress;
+pub(crate) mod grid;
+pub mod intcode;
+pub mod springscript;
 
 aoc_lib! { year = 2019 }
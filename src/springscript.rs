@@ -0,0 +1,954 @@
+use failure::Fail;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
+
+/// The springdroid's memory only has room for this many instructions.
+pub const MAX_INSTRUCTIONS: usize = 15;
+
+/// How many `(T, J)` states `shortest` may visit before giving up.
+const SEARCH_LIMIT: usize = 100_000;
+
+/// The longest run of instructions `assemble` works out in `T` at once.
+const CHAIN_LENGTH: usize = 3;
+
+#[derive(Debug, Fail, PartialEq)]
+pub enum SpringscriptError {
+    #[fail(display = "Line {}: not a recognised instruction: {}", line, text)]
+    UnknownInstruction { line: usize, text: String },
+
+    #[fail(display = "Line {}: not a recognised register: {}", line, register)]
+    UnknownRegister { line: usize, register: String },
+
+    #[fail(display = "Line {}: register {} can't be written to", line, register)]
+    ReadOnlyRegister { line: usize, register: Register },
+
+    #[fail(
+        display = "Register {} isn't available when the droid will {}",
+        register, mode
+    )]
+    UnavailableRegister { register: Register, mode: Mode },
+
+    #[fail(display = "Script has {} instructions, but only {} fit", count, limit)]
+    TooManyInstructions { count: usize, limit: usize },
+
+    #[fail(display = "Script must end with WALK or RUN")]
+    MissingCommand {},
+
+    #[fail(
+        display = "Invalid expression at position {}: {}",
+        position, expression
+    )]
+    InvalidExpression { position: usize, expression: String },
+
+    #[fail(display = "Gave up after visiting {} states", limit)]
+    SearchExhausted { limit: usize },
+
+    #[fail(display = "No script survives every hull")]
+    NoScriptFound {},
+
+    #[fail(display = "No script of {} instructions or fewer will do", limit)]
+    NoScriptFits { limit: usize },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Register {
+    /// Ground sensors `A` to `I`, where `A` is one tile ahead of the droid.
+    Sensor(usize),
+    T,
+    J,
+}
+
+impl Register {
+    fn parse(text: &str, line: usize) -> Result<Register, SpringscriptError> {
+        let unknown = || SpringscriptError::UnknownRegister {
+            line,
+            register: text.to_string(),
+        };
+
+        match text {
+            "T" => Ok(Register::T),
+            "J" => Ok(Register::J),
+            _ if text.len() == 1 => match text.as_bytes()[0] {
+                c @ b'A'..=b'I' => Ok(Register::Sensor((c - b'A') as usize)),
+                _ => Err(unknown()),
+            },
+            _ => Err(unknown()),
+        }
+    }
+}
+
+impl fmt::Display for Register {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Register::Sensor(n) => write!(f, "{}", (b'A' + *n as u8) as char),
+            Register::T => write!(f, "T"),
+            Register::J => write!(f, "J"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Mode {
+    Walk,
+    Run,
+}
+
+impl Mode {
+    pub fn sensors(self) -> usize {
+        match self {
+            Mode::Walk => 4,
+            Mode::Run => 9,
+        }
+    }
+
+    fn rows(self) -> usize {
+        1 << self.sensors()
+    }
+
+    fn readable(self) -> Vec<Register> {
+        let mut registers: Vec<Register> = (0..self.sensors()).map(Register::Sensor).collect();
+        registers.push(Register::T);
+        registers.push(Register::J);
+        registers
+    }
+}
+
+impl fmt::Display for Mode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Mode::Walk => write!(f, "WALK"),
+            Mode::Run => write!(f, "RUN"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Op {
+    And,
+    Or,
+    Not,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Instruction {
+    pub op: Op,
+    pub x: Register,
+    pub y: Register,
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let op = match self.op {
+            Op::And => "AND",
+            Op::Or => "OR",
+            Op::Not => "NOT",
+        };
+        write!(f, "{} {} {}", op, self.x, self.y)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Script {
+    instructions: Vec<Instruction>,
+    mode: Mode,
+}
+
+impl Script {
+    pub fn new(instructions: Vec<Instruction>, mode: Mode) -> Result<Script, SpringscriptError> {
+        if instructions.len() > MAX_INSTRUCTIONS {
+            return Err(SpringscriptError::TooManyInstructions {
+                count: instructions.len(),
+                limit: MAX_INSTRUCTIONS,
+            });
+        }
+
+        for instruction in &instructions {
+            if let Register::Sensor(n) = instruction.x {
+                if n >= mode.sensors() {
+                    return Err(SpringscriptError::UnavailableRegister {
+                        register: instruction.x,
+                        mode,
+                    });
+                }
+            }
+        }
+
+        Ok(Script { instructions, mode })
+    }
+
+    pub fn parse(text: &str) -> Result<Script, SpringscriptError> {
+        let lines: Vec<(usize, &str)> = text
+            .lines()
+            .enumerate()
+            .map(|(n, line)| (n + 1, line.trim()))
+            .filter(|(_, line)| !line.is_empty())
+            .collect();
+
+        let (command, instructions) = match lines.split_last() {
+            Some((&(_, "WALK"), instructions)) => (Mode::Walk, instructions),
+            Some((&(_, "RUN"), instructions)) => (Mode::Run, instructions),
+            _ => return Err(SpringscriptError::MissingCommand {}),
+        };
+
+        let instructions = instructions
+            .iter()
+            .map(|&(line, text)| {
+                let tokens: Vec<&str> = text.split_whitespace().collect();
+                let op = match tokens.as_slice() {
+                    ["AND", _, _] => Op::And,
+                    ["OR", _, _] => Op::Or,
+                    ["NOT", _, _] => Op::Not,
+                    _ => {
+                        return Err(SpringscriptError::UnknownInstruction {
+                            line,
+                            text: text.to_string(),
+                        })
+                    }
+                };
+                let x = Register::parse(tokens[1], line)?;
+                let y = Register::parse(tokens[2], line)?;
+                if let Register::Sensor(_) = y {
+                    return Err(SpringscriptError::ReadOnlyRegister { line, register: y });
+                }
+
+                Ok(Instruction { op, x, y })
+            })
+            .collect::<Result<Vec<Instruction>, SpringscriptError>>()?;
+
+        Script::new(instructions, command)
+    }
+
+    pub fn mode(&self) -> Mode {
+        self.mode
+    }
+
+    pub fn len(&self) -> usize {
+        self.instructions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.instructions.is_empty()
+    }
+
+    /// Whether the droid jumps, given a reading where bit `n` is set if sensor `n` sees ground.
+    pub fn jumps(&self, reading: usize) -> bool {
+        let mut t = false;
+        let mut j = false;
+        for instruction in &self.instructions {
+            let x = match instruction.x {
+                Register::Sensor(n) => reading & (1 << n) != 0,
+                Register::T => t,
+                Register::J => j,
+            };
+            let y = if instruction.y == Register::T {
+                &mut t
+            } else {
+                &mut j
+            };
+            *y = match instruction.op {
+                Op::And => x && *y,
+                Op::Or => x || *y,
+                Op::Not => !x,
+            };
+        }
+
+        j
+    }
+}
+
+impl fmt::Display for Script {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for instruction in &self.instructions {
+            writeln!(f, "{}", instruction)?;
+        }
+        writeln!(f, "{}", self.mode)
+    }
+}
+
+/// The contents of the `T` and `J` registers for every reading at once.
+type Registers = (Table, Table);
+
+/// A truth table with one bit per sensor reading, for up to nine sensors.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+struct Table([u64; 8]);
+
+impl Table {
+    fn from_fn<F: Fn(usize) -> bool>(rows: usize, f: F) -> Table {
+        let mut table = [0; 8];
+        for row in 0..rows {
+            if f(row) {
+                table[row / 64] |= 1 << (row % 64);
+            }
+        }
+        Table(table)
+    }
+
+    fn sensor(n: usize, rows: usize) -> Table {
+        Table::from_fn(rows, |row| row & (1 << n) != 0)
+    }
+
+    fn combine<F: Fn(u64, u64) -> u64>(self, other: Table, f: F) -> Table {
+        let mut table = [0; 8];
+        for (i, word) in table.iter_mut().enumerate() {
+            *word = f(self.0[i], other.0[i]);
+        }
+        Table(table)
+    }
+
+    fn and(self, other: Table) -> Table {
+        self.combine(other, |x, y| x & y)
+    }
+
+    fn or(self, other: Table) -> Table {
+        self.combine(other, |x, y| x | y)
+    }
+
+    /// The table with every row in `everything` flipped.
+    fn not(self, everything: Table) -> Table {
+        self.combine(everything, |x, all| !x & all)
+    }
+
+    fn get(self, row: usize) -> bool {
+        self.0[row / 64] & 1 << (row % 64) != 0
+    }
+
+    /// Whether both tables agree on every row set in `care`.
+    fn agrees(self, other: Table, care: Table) -> bool {
+        (0..8).all(|i| (self.0[i] ^ other.0[i]) & care.0[i] == 0)
+    }
+}
+
+/// A boolean expression over the ground sensors, e.g. `!(A & B & C) & D`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Expression {
+    Sensor(usize),
+    Not(Box<Expression>),
+    And(Box<Expression>, Box<Expression>),
+    Or(Box<Expression>, Box<Expression>),
+}
+
+impl Expression {
+    /// Parses `!`, `&`, `|` and parentheses over sensors `A` to `I`, with the
+    /// usual precedence.
+    pub fn parse(text: &str) -> Result<Expression, SpringscriptError> {
+        let tokens: Vec<(usize, char)> = text
+            .char_indices()
+            .filter(|(_, c)| !c.is_whitespace())
+            .collect();
+        let mut position = 0;
+        let expression = Expression::parse_or(&tokens, &mut position, text)?;
+        match tokens.get(position) {
+            None => Ok(expression),
+            Some(&(index, _)) => Err(Expression::error(index, text)),
+        }
+    }
+
+    fn error(position: usize, text: &str) -> SpringscriptError {
+        SpringscriptError::InvalidExpression {
+            position,
+            expression: text.to_string(),
+        }
+    }
+
+    fn parse_or(
+        tokens: &[(usize, char)],
+        position: &mut usize,
+        text: &str,
+    ) -> Result<Expression, SpringscriptError> {
+        let mut left = Expression::parse_and(tokens, position, text)?;
+        while let Some((_, '|')) = tokens.get(*position) {
+            *position += 1;
+            let right = Expression::parse_and(tokens, position, text)?;
+            left = Expression::Or(Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn parse_and(
+        tokens: &[(usize, char)],
+        position: &mut usize,
+        text: &str,
+    ) -> Result<Expression, SpringscriptError> {
+        let mut left = Expression::parse_not(tokens, position, text)?;
+        while let Some((_, '&')) = tokens.get(*position) {
+            *position += 1;
+            let right = Expression::parse_not(tokens, position, text)?;
+            left = Expression::And(Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn parse_not(
+        tokens: &[(usize, char)],
+        position: &mut usize,
+        text: &str,
+    ) -> Result<Expression, SpringscriptError> {
+        let (index, token) = match tokens.get(*position) {
+            Some(&token) => token,
+            None => return Err(Expression::error(text.len(), text)),
+        };
+        *position += 1;
+
+        match token {
+            '!' => Ok(Expression::Not(Box::new(Expression::parse_not(
+                tokens, position, text,
+            )?))),
+            '(' => {
+                let inner = Expression::parse_or(tokens, position, text)?;
+                match tokens.get(*position) {
+                    Some((_, ')')) => {
+                        *position += 1;
+                        Ok(inner)
+                    }
+                    Some(&(index, _)) => Err(Expression::error(index, text)),
+                    None => Err(Expression::error(text.len(), text)),
+                }
+            }
+            'A'..='I' => Ok(Expression::Sensor(token as usize - 'A' as usize)),
+            _ => Err(Expression::error(index, text)),
+        }
+    }
+
+    fn sensors(&self) -> usize {
+        match self {
+            Expression::Sensor(n) => n + 1,
+            Expression::Not(e) => e.sensors(),
+            Expression::And(a, b) | Expression::Or(a, b) => a.sensors().max(b.sensors()),
+        }
+    }
+
+    fn evaluate(&self, reading: usize) -> bool {
+        match self {
+            Expression::Sensor(n) => reading & (1 << n) != 0,
+            Expression::Not(e) => !e.evaluate(reading),
+            Expression::And(a, b) => a.evaluate(reading) && b.evaluate(reading),
+            Expression::Or(a, b) => a.evaluate(reading) || b.evaluate(reading),
+        }
+    }
+}
+
+/// The readings a search cares about, so that tables only need to hold those
+/// and scripts which differ on the rest are only looked at once.
+struct Readings(Vec<usize>);
+
+impl Readings {
+    fn new(care: Table, mode: Mode) -> Readings {
+        Readings((0..mode.rows()).filter(|&row| care.get(row)).collect())
+    }
+
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    fn project(&self, table: Table) -> Table {
+        Table::from_fn(self.len(), |row| table.get(self.0[row]))
+    }
+
+    fn sensors(&self, mode: Mode) -> Vec<Table> {
+        (0..mode.sensors())
+            .map(|n| self.project(Table::sensor(n, mode.rows())))
+            .collect()
+    }
+}
+
+/// Finds the shortest script leaving `target` in `J` for every reading set in
+/// `care`, by breadth-first search over the truth tables held in `T` and `J`.
+fn shortest(target: Table, care: Table, mode: Mode) -> Result<Vec<Instruction>, SpringscriptError> {
+    let readings = Readings::new(care, mode);
+    let rows = readings.len();
+    let (target, care) = (readings.project(target), readings.project(care));
+    let sensors = readings.sensors(mode);
+
+    let everything = Table::from_fn(rows, |_| true);
+    let mut candidates = vec![];
+    for &op in [Op::And, Op::Or, Op::Not].iter() {
+        for &x in mode.readable().iter() {
+            for &y in [Register::T, Register::J].iter() {
+                candidates.push(Instruction { op, x, y });
+            }
+        }
+    }
+
+    let start = (Table([0; 8]), Table([0; 8]));
+    let mut visited: HashMap<Registers, Option<(Registers, Instruction)>> = HashMap::new();
+    let mut queue = VecDeque::new();
+    visited.insert(start, None);
+    queue.push_back((start, 0));
+
+    if start.1.agrees(target, care) {
+        return Ok(vec![]);
+    }
+
+    let mut found = None;
+    'search: while let Some(((t, j), depth)) = queue.pop_front() {
+        if depth == MAX_INSTRUCTIONS {
+            continue;
+        }
+
+        for &instruction in &candidates {
+            let x = match instruction.x {
+                Register::Sensor(n) => sensors[n],
+                Register::T => t,
+                Register::J => j,
+            };
+            let y = if instruction.y == Register::T { t } else { j };
+            let result = match instruction.op {
+                Op::And => x.and(y),
+                Op::Or => x.or(y),
+                Op::Not => x.not(everything),
+            };
+            let next = if instruction.y == Register::T {
+                (result, j)
+            } else {
+                (t, result)
+            };
+
+            if visited.contains_key(&next) {
+                continue;
+            }
+            visited.insert(next, Some(((t, j), instruction)));
+            if next.1.agrees(target, care) {
+                found = Some(next);
+                break 'search;
+            }
+            if visited.len() > SEARCH_LIMIT {
+                return Err(SpringscriptError::SearchExhausted {
+                    limit: SEARCH_LIMIT,
+                });
+            }
+            queue.push_back((next, depth + 1));
+        }
+    }
+
+    let mut state = found.ok_or(SpringscriptError::NoScriptFits {
+        limit: MAX_INSTRUCTIONS,
+    })?;
+    let mut instructions = vec![];
+    while let Some((previous, instruction)) = visited[&state] {
+        instructions.push(instruction);
+        state = previous;
+    }
+    instructions.reverse();
+
+    Ok(instructions)
+}
+
+/// Builds a script leaving `target` in `J` for every reading set in `care`,
+/// for when `shortest` gives up, though it may not be the shortest. It only
+/// looks at scripts in the shape they're usually written in by hand: a value
+/// is worked out in `T` from the sensors, starting with a `NOT` which
+/// overwrites whatever was there, and folded into `J` with `AND` or `OR`,
+/// and so on. Working back from the end, folding a value in with `AND` means
+/// `J` doesn't matter before it wherever the value is false, so there are
+/// fewer readings to care about with each step back.
+fn assemble(target: Table, care: Table, mode: Mode) -> Result<Vec<Instruction>, SpringscriptError> {
+    let readings = Readings::new(care, mode);
+    let everything = Table::from_fn(readings.len(), |_| true);
+    let target = readings.project(target);
+    let sensors = readings.sensors(mode);
+
+    // Each value that can be folded into `J`, with the instructions leaving it
+    // in `T` first if it isn't a sensor.
+    let mut values: Vec<(Table, Vec<Instruction>, Register)> = sensors
+        .iter()
+        .enumerate()
+        .map(|(n, &sensor)| (sensor, vec![], Register::Sensor(n)))
+        .collect();
+    let mut seen = HashSet::new();
+    let mut frontier = vec![];
+    for (n, &sensor) in sensors.iter().enumerate() {
+        let value = sensor.not(everything);
+        if seen.insert(value) {
+            frontier.push((
+                value,
+                vec![instruction(Op::Not, Register::Sensor(n), Register::T)],
+            ));
+        }
+    }
+    for length in 1..=CHAIN_LENGTH {
+        let mut next = vec![];
+        for (t, chain) in frontier {
+            if length < CHAIN_LENGTH {
+                let mut steps = vec![(t.not(everything), Op::Not, Register::T)];
+                for (n, &sensor) in sensors.iter().enumerate() {
+                    steps.push((t.and(sensor), Op::And, Register::Sensor(n)));
+                    steps.push((t.or(sensor), Op::Or, Register::Sensor(n)));
+                }
+                for (value, op, x) in steps {
+                    if seen.insert(value) {
+                        let mut chain = chain.clone();
+                        chain.push(instruction(op, x, Register::T));
+                        next.push((value, chain));
+                    }
+                }
+            }
+            values.push((t, chain, Register::T));
+        }
+        frontier = next;
+    }
+
+    let mut assembler = Assembler {
+        everything,
+        sensors,
+        values,
+        failed: HashSet::new(),
+    };
+    (1..=MAX_INSTRUCTIONS)
+        .find_map(|length| assembler.assemble(target, everything, length))
+        .ok_or(SpringscriptError::NoScriptFits {
+            limit: MAX_INSTRUCTIONS,
+        })
+}
+
+fn instruction(op: Op, x: Register, y: Register) -> Instruction {
+    Instruction { op, x, y }
+}
+
+struct Assembler {
+    everything: Table,
+    sensors: Vec<Table>,
+    values: Vec<(Table, Vec<Instruction>, Register)>,
+    /// Targets already known to need more than so many instructions.
+    failed: HashSet<(Table, Table, usize)>,
+}
+
+impl Assembler {
+    fn assemble(&mut self, target: Table, care: Table, length: usize) -> Option<Vec<Instruction>> {
+        // `J` starts off false.
+        if target.and(care) == Table::default() {
+            return Some(vec![]);
+        }
+        if length == 0 || self.failed.contains(&(target.and(care), care, length)) {
+            return None;
+        }
+        for (n, &sensor) in self.sensors.iter().enumerate() {
+            if sensor.not(self.everything).and(care) == target.and(care) {
+                return Some(vec![instruction(Op::Not, Register::Sensor(n), Register::J)]);
+            }
+        }
+
+        if let Some(mut instructions) = self.assemble(target.not(self.everything), care, length - 1)
+        {
+            instructions.push(instruction(Op::Not, Register::J, Register::J));
+            return Some(instructions);
+        }
+        for i in 0..self.values.len() {
+            let (value, ref chain, x) = self.values[i];
+            if chain.len() + 1 > length {
+                continue;
+            }
+            let rest = length - chain.len() - 1;
+            let trues = target.and(care);
+            let falses = target.not(self.everything).and(care);
+            // With `AND`, `J` only matters where the value is true, and with
+            // `OR` where it's false.
+            let folds = [
+                (Op::And, value, trues.and(value) == trues),
+                (
+                    Op::Or,
+                    value.not(self.everything),
+                    falses.and(value) == Table::default(),
+                ),
+            ];
+            for &(op, matters, allowed) in folds.iter() {
+                if !allowed || care.and(matters) == care {
+                    continue;
+                }
+                let chain = self.values[i].1.clone();
+                if let Some(mut instructions) = self.assemble(target, care.and(matters), rest) {
+                    instructions.extend(chain);
+                    instructions.push(instruction(op, x, Register::J));
+                    return Some(instructions);
+                }
+            }
+        }
+
+        self.failed.insert((target.and(care), care, length));
+        None
+    }
+}
+
+/// The shortest script leaving `target` in `J` for every reading set in
+/// `care` if `shortest` can find it in time, or else one that `assemble`
+/// puts together.
+fn synthesise(
+    target: Table,
+    care: Table,
+    mode: Mode,
+) -> Result<Vec<Instruction>, SpringscriptError> {
+    match shortest(target, care, mode) {
+        Err(SpringscriptError::SearchExhausted { limit }) => {
+            assemble(target, care, mode).map_err(|_| SpringscriptError::SearchExhausted { limit })
+        }
+        result => result,
+    }
+}
+
+/// Compiles an expression into the shortest script it can find which jumps
+/// exactly when it's true.
+pub fn compile(expression: &Expression, mode: Mode) -> Result<Script, SpringscriptError> {
+    let sensors = expression.sensors();
+    if sensors > mode.sensors() {
+        return Err(SpringscriptError::UnavailableRegister {
+            register: Register::Sensor(sensors - 1),
+            mode,
+        });
+    }
+
+    let rows = mode.rows();
+    let target = Table::from_fn(rows, |row| expression.evaluate(row));
+    let care = Table::from_fn(rows, |_| true);
+    Script::new(synthesise(target, care, mode)?, mode)
+}
+
+/// A stretch of hull the droid must cross, drawn as `#` for ground and `.`
+/// for holes. The droid starts on the first tile.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Hull(Vec<bool>);
+
+impl Hull {
+    pub fn parse(text: &str) -> Hull {
+        Hull(text.trim().chars().map(|c| c == '#').collect())
+    }
+
+    fn reading(&self, position: usize, mode: Mode) -> usize {
+        (0..mode.sensors())
+            .filter(|n| *self.0.get(position + n + 1).unwrap_or(&true))
+            .fold(0, |reading, n| reading | 1 << n)
+    }
+
+    /// Walks the droid across, asking `jumps` what to do at each tile.
+    /// Stops early if `jumps` can't decide.
+    fn cross<F>(&self, mode: Mode, jumps: F) -> Crossing
+    where
+        F: Fn(usize) -> Option<bool>,
+    {
+        let mut position = 0;
+        while position < self.0.len() {
+            if !self.0[position] {
+                return Crossing::Fell;
+            }
+            let reading = self.reading(position, mode);
+            position += match jumps(reading) {
+                Some(true) => 4,
+                Some(false) => 1,
+                None => return Crossing::Undecided(reading),
+            };
+        }
+
+        Crossing::Survived
+    }
+
+    pub fn survives(&self, script: &Script) -> bool {
+        self.cross(script.mode(), |reading| Some(script.jumps(reading))) == Crossing::Survived
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Crossing {
+    Survived,
+    Fell,
+    Undecided(usize),
+}
+
+/// Searches for a script that gets the droid across every hull. Readings are
+/// assigned jump or walk one at a time as the droid meets them, backtracking
+/// when it falls; any reading it never meets is left free for `synthesise`.
+pub fn search(hulls: &[Hull], mode: Mode) -> Result<Script, SpringscriptError> {
+    let mut search = Search {
+        hulls,
+        mode,
+        decisions: HashMap::new(),
+        scripts: HashMap::new(),
+        too_long: false,
+    };
+    match search.decide()? {
+        Some(instructions) => Script::new(instructions, mode),
+        None if search.too_long => Err(SpringscriptError::NoScriptFits {
+            limit: MAX_INSTRUCTIONS,
+        }),
+        None => Err(SpringscriptError::NoScriptFound {}),
+    }
+}
+
+struct Search<'a> {
+    hulls: &'a [Hull],
+    mode: Mode,
+    /// Whether to jump, for each reading decided so far.
+    decisions: HashMap<usize, bool>,
+    /// Scripts already looked for, by target and the readings that matter,
+    /// so backtracking to the same decisions doesn't search again.
+    scripts: HashMap<(Table, Table), Option<Vec<Instruction>>>,
+    /// Whether some decisions got every droid across but needed too long a
+    /// script.
+    too_long: bool,
+}
+
+impl<'a> Search<'a> {
+    /// Finds a script for the decisions so far, or `None` if there isn't one
+    /// however the rest are made.
+    fn decide(&mut self) -> Result<Option<Vec<Instruction>>, SpringscriptError> {
+        for hull in self.hulls {
+            let decisions = &self.decisions;
+            match hull.cross(self.mode, |reading| decisions.get(&reading).copied()) {
+                Crossing::Survived => continue,
+                Crossing::Fell => return Ok(None),
+                Crossing::Undecided(reading) => {
+                    for &jump in [false, true].iter() {
+                        self.decisions.insert(reading, jump);
+                        if let Some(instructions) = self.decide()? {
+                            return Ok(Some(instructions));
+                        }
+                    }
+                    self.decisions.remove(&reading);
+                    return Ok(None);
+                }
+            }
+        }
+
+        let rows = self.mode.rows();
+        let target = Table::from_fn(rows, |row| self.decisions.get(&row) == Some(&true));
+        let care = Table::from_fn(rows, |row| self.decisions.contains_key(&row));
+        if let Some(instructions) = self.scripts.get(&(target, care)) {
+            return Ok(instructions.clone());
+        }
+        let instructions = match synthesise(target, care, self.mode) {
+            Ok(instructions) => Some(instructions),
+            Err(SpringscriptError::NoScriptFits { .. }) => {
+                self.too_long = true;
+                None
+            }
+            Err(error) => return Err(error),
+        };
+        self.scripts.insert((target, care), instructions.clone());
+        Ok(instructions)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_round_trips() {
+        let text = "NOT A J\nNOT B T\nOR T J\nAND D J\nWALK\n";
+        assert_eq!(Script::parse(text).unwrap().to_string(), text)
+    }
+
+    #[test]
+    fn parse_rejects_writing_to_sensors() {
+        assert_eq!(
+            Script::parse("NOT A J\nOR J B\nWALK"),
+            Err(SpringscriptError::ReadOnlyRegister {
+                line: 2,
+                register: Register::Sensor(1),
+            })
+        )
+    }
+
+    #[test]
+    fn parse_rejects_run_sensors_when_walking() {
+        assert_eq!(
+            Script::parse("NOT E J\nWALK"),
+            Err(SpringscriptError::UnavailableRegister {
+                register: Register::Sensor(4),
+                mode: Mode::Walk,
+            })
+        )
+    }
+
+    #[test]
+    fn parse_rejects_long_scripts() {
+        let text = format!("{}WALK", "NOT A J\n".repeat(16));
+        let error = Script::parse(&text).unwrap_err();
+        assert_eq!(
+            error,
+            SpringscriptError::TooManyInstructions {
+                count: 16,
+                limit: MAX_INSTRUCTIONS
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            format!(
+                "Script has 16 instructions, but only {} fit",
+                MAX_INSTRUCTIONS
+            )
+        )
+    }
+
+    #[test]
+    fn expression_parse_reports_position() {
+        assert_eq!(
+            Expression::parse("A & (B | X)"),
+            Err(SpringscriptError::InvalidExpression {
+                position: 9,
+                expression: "A & (B | X)".to_string(),
+            })
+        )
+    }
+
+    #[test]
+    fn compile_finds_shortest_script() {
+        let expression = Expression::parse("!(A & B & C) & D").unwrap();
+        let script = compile(&expression, Mode::Walk).unwrap();
+        assert_eq!(script.len(), 5);
+        for reading in 0..16 {
+            assert_eq!(script.jumps(reading), expression.evaluate(reading));
+        }
+    }
+
+    #[test]
+    fn compile_single_negation() {
+        let script = compile(&Expression::parse("!A").unwrap(), Mode::Walk).unwrap();
+        assert_eq!(script.to_string(), "NOT A J\nWALK\n")
+    }
+
+    #[test]
+    fn search_survives_every_hull() {
+        let hulls: Vec<Hull> = [
+            "#####...##.#.####",
+            "#####...##.#.####",
+            "#####...#########",
+        ]
+        .iter()
+        .map(|hull| Hull::parse(hull))
+        .collect();
+        let script = search(&hulls, Mode::Walk).unwrap();
+        assert!(hulls.iter().all(|hull| hull.survives(&script)))
+    }
+
+    #[test]
+    fn search_runs_across_part_two_hulls() {
+        // Hulls a running droid fell into while surveying a puzzle input.
+        let hulls: Vec<Hull> = [
+            "#####...##.#.####",
+            "#####...#########",
+            "#####...##.#.####",
+            "#####.#..########",
+            "#####.#.##.#.####",
+            "#####.##.##.#.###",
+            "#####.##..#.#####",
+            "#####...##.#.####",
+            "#####.#.#...#.###",
+        ]
+        .iter()
+        .map(|hull| Hull::parse(hull))
+        .collect();
+        let script = search(&hulls, Mode::Run).unwrap();
+        assert_eq!(script.mode(), Mode::Run);
+        assert!(
+            hulls.iter().all(|hull| hull.survives(&script)),
+            "{}",
+            script
+        )
+    }
+
+    #[test]
+    fn search_gives_up_on_impossible_hulls() {
+        assert_eq!(
+            search(&[Hull::parse("#.....#")], Mode::Walk),
+            Err(SpringscriptError::NoScriptFound {})
+        )
+    }
+}